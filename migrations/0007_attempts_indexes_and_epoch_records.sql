@@ -0,0 +1,81 @@
+-- Migration number: 0007 	 2026-10-19T09:12:41.503Z
+
+-- Best/worst lookups for a channel's current epoch, both channel-wide
+-- and for a specific chatter.
+CREATE INDEX AttemptsByEpochDifference
+ON Attempts (broadcaster_user_id, epoch, forty_five_difference, forty_five_timestamp);
+
+CREATE INDEX AttemptsByChatterDifference
+ON Attempts (broadcaster_user_id, chatter_user_id, epoch, forty_five_difference, forty_five_timestamp);
+
+-- Counting perfect 45s to get the current epoch and the hall of fame.
+CREATE INDEX AttemptsByPerfect
+ON Attempts (broadcaster_user_id, forty_five_difference, epoch);
+
+-- The current best and worst attempt of every epoch of a channel. This
+-- is kept up to date by the trigger below so that `!45 best` and
+-- `!45 worst` don't have to scan the epoch.
+CREATE TABLE EpochRecords (
+    broadcaster_user_id TEXT NOT NULL,
+    epoch INTEGER NOT NULL,
+    best_attempt_id INTEGER NOT NULL,
+    best_difference REAL NOT NULL,
+    worst_attempt_id INTEGER NOT NULL,
+    worst_difference REAL NOT NULL,
+    PRIMARY KEY (broadcaster_user_id, epoch)
+);
+
+-- Ties are won by the latest attempt, same as ordering by the timestamp
+-- descending.
+INSERT INTO EpochRecords
+SELECT
+    a.broadcaster_user_id,
+    a.epoch,
+    (
+        SELECT b.id FROM Attempts b
+        WHERE b.broadcaster_user_id = a.broadcaster_user_id AND b.epoch = a.epoch
+        ORDER BY b.forty_five_difference ASC, b.forty_five_timestamp DESC, b.id DESC
+        LIMIT 1
+    ),
+    MIN(a.forty_five_difference),
+    (
+        SELECT w.id FROM Attempts w
+        WHERE w.broadcaster_user_id = a.broadcaster_user_id AND w.epoch = a.epoch
+        ORDER BY w.forty_five_difference DESC, w.forty_five_timestamp DESC, w.id DESC
+        LIMIT 1
+    ),
+    MAX(a.forty_five_difference)
+FROM Attempts a
+GROUP BY a.broadcaster_user_id, a.epoch;
+
+CREATE TRIGGER AttemptsUpdateEpochRecords
+AFTER INSERT ON Attempts
+BEGIN
+    INSERT INTO EpochRecords (
+        broadcaster_user_id,
+        epoch,
+        best_attempt_id,
+        best_difference,
+        worst_attempt_id,
+        worst_difference
+    )
+    VALUES (
+        NEW.broadcaster_user_id,
+        NEW.epoch,
+        NEW.id,
+        NEW.forty_five_difference,
+        NEW.id,
+        NEW.forty_five_difference
+    )
+    ON CONFLICT (broadcaster_user_id, epoch) DO UPDATE SET
+        best_attempt_id = CASE
+            WHEN excluded.best_difference <= best_difference THEN excluded.best_attempt_id
+            ELSE best_attempt_id
+        END,
+        best_difference = MIN(best_difference, excluded.best_difference),
+        worst_attempt_id = CASE
+            WHEN excluded.worst_difference >= worst_difference THEN excluded.worst_attempt_id
+            ELSE worst_attempt_id
+        END,
+        worst_difference = MAX(worst_difference, excluded.worst_difference);
+END;
@@ -0,0 +1,79 @@
+-- Migration number: 0008 	 2026-10-19T10:03:17.218Z
+
+-- Channels can have other games besides !45, each game has its own
+-- attempts, epochs and records. Existing attempts are all from !45.
+ALTER TABLE Attempts ADD COLUMN game TEXT NOT NULL DEFAULT '45';
+
+DROP INDEX AttemptsByEpochDifference;
+DROP INDEX AttemptsByChatterDifference;
+DROP INDEX AttemptsByPerfect;
+
+CREATE INDEX AttemptsByEpochDifference
+ON Attempts (broadcaster_user_id, game, epoch, forty_five_difference, forty_five_timestamp);
+
+CREATE INDEX AttemptsByChatterDifference
+ON Attempts (broadcaster_user_id, game, chatter_user_id, epoch, forty_five_difference, forty_five_timestamp);
+
+CREATE INDEX AttemptsByPerfect
+ON Attempts (broadcaster_user_id, game, forty_five_difference, epoch);
+
+DROP TRIGGER AttemptsUpdateEpochRecords;
+
+CREATE TABLE temp_epoch_records (
+    broadcaster_user_id TEXT NOT NULL,
+    game TEXT NOT NULL,
+    epoch INTEGER NOT NULL,
+    best_attempt_id INTEGER NOT NULL,
+    best_difference REAL NOT NULL,
+    worst_attempt_id INTEGER NOT NULL,
+    worst_difference REAL NOT NULL,
+    PRIMARY KEY (broadcaster_user_id, game, epoch)
+);
+
+INSERT INTO temp_epoch_records
+SELECT
+    broadcaster_user_id,
+    '45',
+    epoch,
+    best_attempt_id,
+    best_difference,
+    worst_attempt_id,
+    worst_difference
+FROM EpochRecords;
+
+DROP TABLE EpochRecords;
+ALTER TABLE temp_epoch_records RENAME TO EpochRecords;
+
+CREATE TRIGGER AttemptsUpdateEpochRecords
+AFTER INSERT ON Attempts
+BEGIN
+    INSERT INTO EpochRecords (
+        broadcaster_user_id,
+        game,
+        epoch,
+        best_attempt_id,
+        best_difference,
+        worst_attempt_id,
+        worst_difference
+    )
+    VALUES (
+        NEW.broadcaster_user_id,
+        NEW.game,
+        NEW.epoch,
+        NEW.id,
+        NEW.forty_five_difference,
+        NEW.id,
+        NEW.forty_five_difference
+    )
+    ON CONFLICT (broadcaster_user_id, game, epoch) DO UPDATE SET
+        best_attempt_id = CASE
+            WHEN excluded.best_difference <= best_difference THEN excluded.best_attempt_id
+            ELSE best_attempt_id
+        END,
+        best_difference = MIN(best_difference, excluded.best_difference),
+        worst_attempt_id = CASE
+            WHEN excluded.worst_difference >= worst_difference THEN excluded.worst_attempt_id
+            ELSE worst_attempt_id
+        END,
+        worst_difference = MAX(worst_difference, excluded.worst_difference);
+END;
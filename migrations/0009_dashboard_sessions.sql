@@ -0,0 +1,16 @@
+-- Migration number: 0009 	 2026-10-19T13:41:52.630Z
+
+-- Logins to the dashboard. The channels a user can manage are looked up
+-- once when logging in and kept here as a JSON array, so logging in again
+-- picks up channels they became a moderator of since.
+CREATE TABLE DashboardSessions (
+    id TEXT PRIMARY KEY NOT NULL,
+    user_id TEXT NOT NULL,
+    user_login TEXT NOT NULL,
+    channels TEXT NOT NULL,
+    csrf_token TEXT NOT NULL,
+    expires_at INTEGER NOT NULL
+);
+
+CREATE INDEX DashboardSessionsByExpiry
+ON DashboardSessions (expires_at);
@@ -0,0 +1,6 @@
+-- Migration number: 0010 	 2026-10-19T15:22:08.914Z
+
+-- Paging through all attempts of a channel in insertion order, for
+-- exports.
+CREATE INDEX AttemptsByBroadcaster
+ON Attempts (broadcaster_user_id, id);
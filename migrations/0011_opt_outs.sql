@@ -0,0 +1,11 @@
+-- Migration number: 0011 	 2026-10-19T16:05:37.142Z
+
+-- Chatters that asked the bot not to record them, in every channel.
+CREATE TABLE OptOuts (
+    chatter_user_id TEXT PRIMARY KEY NOT NULL,
+    opted_out_at INTEGER NOT NULL
+);
+
+-- Finding every attempt of a chatter across channels, for purging them.
+CREATE INDEX AttemptsByChatter
+ON Attempts (chatter_user_id);
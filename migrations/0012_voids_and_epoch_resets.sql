@@ -0,0 +1,21 @@
+-- Migration number: 0012 	 2026-10-19T16:48:20.577Z
+
+-- Attempts a moderator voided with `!45 void`. Voided attempts are kept
+-- but left out of records, the hall of fame and stats. Voided perfect hits
+-- still count for epochs, so the epochs of later attempts don't change.
+ALTER TABLE Attempts ADD COLUMN voided_by TEXT;
+
+-- Epochs a moderator ended with `!45 reset` instead of a perfect hit. The
+-- epoch of a game is the number of perfect hits plus the number of resets.
+CREATE TABLE EpochResets (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    broadcaster_user_id TEXT NOT NULL,
+    game TEXT NOT NULL,
+    -- The epoch that was ended.
+    epoch INTEGER NOT NULL,
+    moderator_user_id TEXT NOT NULL,
+    reset_at INTEGER NOT NULL
+);
+
+CREATE INDEX EpochResetsByGame
+ON EpochResets (broadcaster_user_id, game, reset_at);
@@ -0,0 +1,23 @@
+-- Migration number: 0013 	 2026-10-19T17:31:06.268Z
+
+-- Time-boxed leaderboards of a channel, independent of epochs. A season
+-- covers every game of the channel, from starts_at up to but not
+-- including ends_at.
+CREATE TABLE Seasons (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    broadcaster_user_id TEXT NOT NULL,
+    name TEXT NOT NULL,
+    starts_at INTEGER NOT NULL,
+    ends_at INTEGER NOT NULL,
+    -- When the winners were announced in chat, NULL until then.
+    closed_at INTEGER,
+    UNIQUE (broadcaster_user_id, name)
+);
+
+-- Finding seasons that are over but weren't announced yet.
+CREATE INDEX SeasonsToClose
+ON Seasons (closed_at, ends_at);
+
+-- Records within a span of time rather than an epoch.
+CREATE INDEX AttemptsByTime
+ON Attempts (broadcaster_user_id, game, forty_five_timestamp);
@@ -0,0 +1,13 @@
+-- Migration number: 0014 	 2026-10-19T18:12:44.931Z
+
+-- Achievements chatters unlocked in a game of a channel, each of them
+-- once. attempt_id is the attempt that unlocked it.
+CREATE TABLE Achievements (
+    broadcaster_user_id TEXT NOT NULL,
+    game TEXT NOT NULL,
+    chatter_user_id TEXT NOT NULL,
+    achievement TEXT NOT NULL,
+    attempt_id INTEGER NOT NULL,
+    unlocked_at INTEGER NOT NULL,
+    PRIMARY KEY (broadcaster_user_id, game, chatter_user_id, achievement)
+);
@@ -0,0 +1,18 @@
+-- Migration number: 0015 	 2026-10-19T19:02:17.512Z
+
+-- Broadcasts of a channel, from stream.online and stream.offline events.
+-- id is the stream ID from Twitch, ended_at is NULL while live.
+CREATE TABLE Streams (
+    id TEXT PRIMARY KEY,
+    broadcaster_user_id TEXT NOT NULL,
+    started_at INTEGER NOT NULL,
+    ended_at INTEGER
+);
+
+-- Finding the live stream of a channel.
+CREATE INDEX StreamsByBroadcaster
+ON Streams (broadcaster_user_id, started_at);
+
+-- The stream an attempt was made during, NULL for attempts made offline
+-- or before streams were tracked.
+ALTER TABLE Attempts ADD COLUMN stream_id TEXT;
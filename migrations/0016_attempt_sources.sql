@@ -0,0 +1,5 @@
+-- Migration number: 0016 	 2026-10-19T19:48:53.207Z
+
+-- What an attempt was played through: chat, channel_points, cheer,
+-- subscription or import. Attempts from before are taken as chat.
+ALTER TABLE Attempts ADD COLUMN source TEXT NOT NULL DEFAULT 'chat';
@@ -0,0 +1,12 @@
+-- Migration number: 0017 	 2026-10-19T20:21:38.640Z
+
+-- Shared Chat messages already answered by one of the channels of the
+-- session, so the other channels don't answer them again. Rows are
+-- removed by the cron trigger after a while.
+CREATE TABLE SharedChatMessages (
+    message_id TEXT PRIMARY KEY,
+    claimed_at INTEGER NOT NULL
+);
+
+CREATE INDEX SharedChatMessagesByTime
+ON SharedChatMessages (claimed_at);
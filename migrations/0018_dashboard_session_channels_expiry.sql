@@ -0,0 +1,7 @@
+-- Migration number: 0018 	 2026-10-20T09:12:44.318Z
+
+-- When the channels a user moderates stop being trusted for a session.
+-- Moderators can be removed at any time, so past this only the user's own
+-- channel can be managed until they log in again. Sessions from before
+-- only keep their own channel.
+ALTER TABLE DashboardSessions ADD COLUMN channels_expire_at INTEGER NOT NULL DEFAULT 0;
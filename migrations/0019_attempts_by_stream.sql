@@ -0,0 +1,5 @@
+-- Migration number: 0019 	 2026-10-20T15:41:08.276Z
+
+-- Records and ranks of the stream a channel is live with.
+CREATE INDEX AttemptsByStream
+ON Attempts (broadcaster_user_id, game, stream_id);
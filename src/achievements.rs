@@ -0,0 +1,145 @@
+//! Milestones chatters unlock by playing, checked after every attempt of `!45` and announced
+//! along with it. Each achievement is unlocked once per game of a channel.
+
+use rust_decimal::prelude::*;
+use sqlx_d1::D1Connection;
+
+use crate::db::{self, Attempt};
+use crate::prelude::*;
+use crate::time::{Calendar, calendar_span};
+
+/// How many attempts in a row have to be close to the target for [`Achievement::CloseStreak`].
+const STREAK: i64 = 3;
+
+/// How many steps from the target count as close, 0.1 for !45.
+const CLOSE_STEPS: i64 = 20;
+
+/// How many attempts [`Achievement::Hundredth`] takes.
+const HUNDREDTH: i64 = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Achievement {
+    /// Several attempts in a row close to the target.
+    CloseStreak,
+    /// The first attempt of the day in the channel, in the channel's timezone.
+    FirstOfTheDay,
+    /// The chatter's 100th attempt.
+    Hundredth,
+    /// The lowest value the game can generate, 0.000 for !45.
+    Minimum,
+    /// The highest value the game can generate, 90.000 for !45.
+    Maximum,
+    /// Beating one's own best of the epoch.
+    PersonalBest,
+}
+
+impl Achievement {
+    pub const ALL: [Self; 6] = [
+        Self::CloseStreak,
+        Self::FirstOfTheDay,
+        Self::Hundredth,
+        Self::Minimum,
+        Self::Maximum,
+        Self::PersonalBest,
+    ];
+
+    /// How the achievement is stored.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::CloseStreak => "close_streak",
+            Self::FirstOfTheDay => "first_of_the_day",
+            Self::Hundredth => "hundredth",
+            Self::Minimum => "minimum",
+            Self::Maximum => "maximum",
+            Self::PersonalBest => "personal_best",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|achievement| achievement.name() == name)
+    }
+
+    /// How the achievement is shown in chat.
+    pub fn title(self, config: &CommandConfig) -> String {
+        let key = match self {
+            Self::CloseStreak => MessageKey::AchievementCloseStreak,
+            Self::FirstOfTheDay => MessageKey::AchievementFirstOfTheDay,
+            Self::Hundredth => MessageKey::AchievementHundredth,
+            Self::Minimum => MessageKey::AchievementMinimum,
+            Self::Maximum => MessageKey::AchievementMaximum,
+            Self::PersonalBest => MessageKey::AchievementPersonalBest,
+        };
+
+        config.template(key).render(&Vars::new())
+    }
+}
+
+/// Checks the achievements an attempt earned and stores them, returning the ones the chatter
+/// didn't have yet.
+pub async fn unlock(
+    db_conn: &D1Connection,
+    config: &CommandConfig,
+    broadcaster_user_id: &str,
+    game: &GameConfig,
+    attempt: &Attempt,
+) -> BotResult<Vec<Achievement>> {
+    let rules = &game.rules;
+    let value = Decimal::from_f64(attempt.forty_five_value).unwrap_or_default();
+    let threshold = rules.step * Decimal::from(CLOSE_STEPS);
+
+    let (day_start, _) = calendar_span(
+        Calendar::Day,
+        attempt.forty_five_timestamp,
+        config.timezone(),
+    );
+
+    let facts = db::achievement_facts(
+        db_conn,
+        broadcaster_user_id,
+        attempt,
+        day_start,
+        STREAK,
+        threshold.to_f64().unwrap_or_default(),
+    )
+    .await?;
+
+    let earned = Achievement::ALL
+        .into_iter()
+        .filter(|achievement| match achievement {
+            Achievement::CloseStreak => facts.close_attempts >= STREAK,
+            Achievement::FirstOfTheDay => facts.earlier_today == 0,
+            Achievement::Hundredth => facts.attempts >= HUNDREDTH,
+            Achievement::Minimum => value == rules.min,
+            Achievement::Maximum => value == rules.max,
+            Achievement::PersonalBest => facts
+                .previous_best
+                .is_some_and(|best| attempt.forty_five_difference < best),
+        })
+        .map(Achievement::name)
+        // Most rolls earn achievements the chatter has already, such as
+        // every roll after the hundredth.
+        .filter(|name| !facts.unlocked.iter().any(|unlocked| unlocked == name))
+        .collect::<Vec<_>>();
+
+    if earned.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let unlocked = db::unlock_achievements(
+        db_conn,
+        broadcaster_user_id,
+        &game.name,
+        &attempt.chatter_user_id,
+        &earned,
+        attempt.id,
+        attempt.forty_five_timestamp,
+    )
+    .await?;
+
+    Ok(unlocked
+        .iter()
+        .filter_map(|name| Achievement::from_name(name))
+        .collect())
+}
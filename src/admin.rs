@@ -0,0 +1,281 @@
+//! Endpoints for whoever runs the bot, authenticated with the ADMIN_TOKEN secret.
+
+use serde::Serialize;
+use twitch_api::types::UserId;
+use worker::*;
+
+use crate::config::{CommandConfig, FORTYFIVE_DATA};
+use crate::error::BotError;
+use crate::export::Format;
+
+/// Checks the `Authorization: Bearer <ADMIN_TOKEN>` header of a request.
+pub fn is_authorized(req: &Request, env: &Env) -> Result<bool> {
+    let token = env.secret("ADMIN_TOKEN")?.to_string();
+
+    let Some(header) = req.headers().get("Authorization")? else {
+        return Ok(false);
+    };
+
+    let Some(provided) = header.strip_prefix("Bearer ") else {
+        return Ok(false);
+    };
+
+    Ok(constant_time_eq::constant_time_eq(
+        provided.as_bytes(),
+        token.as_bytes(),
+    ))
+}
+
+#[derive(Serialize)]
+struct ConfigStatus {
+    /// What's stored in KV, as-is.
+    raw: Option<serde_json::Value>,
+    /// The configuration after migrating it to the current version.
+    config: Option<CommandConfig>,
+    /// Why the configuration couldn't be read, the channel uses the defaults if set.
+    error: Option<String>,
+    /// Why the configuration is invalid, the invalid parts use the defaults if set.
+    validation_error: Option<String>,
+}
+
+/// `GET /admin/config/:broadcaster_user_id`: Shows how a channel's configuration is read, since
+/// the bot falls back to the defaults instead of failing when it can't be.
+pub async fn config(req: Request, ctx: RouteContext<Context>) -> Result<Response> {
+    if !is_authorized(&req, &ctx.env)? {
+        return Ok(Response::builder()
+            .with_status(401)
+            .body(ResponseBody::Empty));
+    }
+
+    let Some(broadcaster_user_id) = ctx.param("broadcaster_user_id") else {
+        return Ok(Response::builder()
+            .with_status(400)
+            .body(ResponseBody::Empty));
+    };
+
+    let broadcaster_user_id = UserId::new(broadcaster_user_id.to_owned());
+
+    let raw = CommandConfig::read_raw(&ctx.env, &broadcaster_user_id).await;
+    let read = CommandConfig::read(&ctx.env, &broadcaster_user_id).await;
+
+    let status = match read {
+        Ok(config) => ConfigStatus {
+            raw: raw.ok().flatten(),
+            validation_error: config
+                .as_ref()
+                .and_then(|config| config.validate().err())
+                .map(|e| e.to_string()),
+            config,
+            error: None,
+        },
+        Err(e) => ConfigStatus {
+            raw: raw.ok().flatten(),
+            config: None,
+            error: Some(e.to_string()),
+            validation_error: None,
+        },
+    };
+
+    Response::from_json(&status)
+}
+
+/// `PUT /admin/config/:broadcaster_user_id`: Replaces a channel's configuration with the JSON body,
+/// which can be of an older version. Invalid configuration is rejected with the reason.
+pub async fn update_config(mut req: Request, ctx: RouteContext<Context>) -> Result<Response> {
+    if !is_authorized(&req, &ctx.env)? {
+        return Ok(Response::builder()
+            .with_status(401)
+            .body(ResponseBody::Empty));
+    }
+
+    let Some(broadcaster_user_id) = ctx.param("broadcaster_user_id") else {
+        return Ok(Response::builder()
+            .with_status(400)
+            .body(ResponseBody::Empty));
+    };
+
+    let broadcaster_user_id = UserId::new(broadcaster_user_id.to_owned());
+
+    let body: serde_json::Value = req.json().await?;
+
+    let saved: std::result::Result<(), BotError> = async {
+        let config: CommandConfig = serde_json::from_value(crate::config::migrate(body)?)?;
+
+        config.save(&ctx.env, &broadcaster_user_id).await
+    }
+    .await;
+
+    match saved {
+        Ok(()) => Ok(Response::builder()
+            .with_status(204)
+            .body(ResponseBody::Empty)),
+        Err(e @ (BotError::JsonError(_) | BotError::ConfigError(_))) => {
+            Ok(Response::error(e.to_string(), 400)?)
+        }
+        Err(e) => Err(Error::RustError(e.to_string())),
+    }
+}
+
+/// `POST /admin/import/:broadcaster_user_id?format=csv&game=45&dry_run=true`: Imports attempts
+/// from another bot, see [`crate::import`]. Responds with what was imported, or what would be for
+/// dry runs.
+pub async fn import(mut req: Request, ctx: RouteContext<Context>) -> Result<Response> {
+    if !is_authorized(&req, &ctx.env)? {
+        return Ok(Response::builder()
+            .with_status(401)
+            .body(ResponseBody::Empty));
+    }
+
+    let Some(broadcaster_user_id) = ctx.param("broadcaster_user_id") else {
+        return Ok(Response::builder()
+            .with_status(400)
+            .body(ResponseBody::Empty));
+    };
+
+    let broadcaster_user_id = UserId::new(broadcaster_user_id.to_owned());
+
+    let query = req
+        .url()?
+        .query_pairs()
+        .map(|(key, value)| (key.into_owned(), value.into_owned()))
+        .collect::<std::collections::HashMap<_, _>>();
+
+    let Some(format) = Format::from_name(query.get("format").map_or("csv", String::as_str)) else {
+        return Response::error("format has to be csv or ndjson", 400);
+    };
+
+    let dry_run = query
+        .get("dry_run")
+        .is_some_and(|value| value != "false" && value != "0");
+
+    let config = CommandConfig::load(&ctx.env, &broadcaster_user_id).await;
+    let game_name = query
+        .get("game")
+        .map_or(crate::config::DEFAULT_GAME, String::as_str);
+
+    let Some(game) = config.game(game_name) else {
+        return Response::error(format!("{game_name} isn't a game of the channel"), 400);
+    };
+
+    let body = req.text().await?;
+
+    match crate::import::run(
+        &ctx.env,
+        broadcaster_user_id.as_str(),
+        &game,
+        format,
+        &body,
+        dry_run,
+    )
+    .await
+    {
+        Ok(report) => Response::from_json(&report),
+        Err(e) => Err(Error::RustError(e.to_string())),
+    }
+}
+
+/// `POST /admin/subscriptions/:broadcaster_user_id`: Subscribes to the channel going live and
+/// offline, delivered to this worker's `/eventsub`, see [`crate::streams`]. Channels with
+/// [`CommandConfig::rewards`] are subscribed to their redemptions as well, see
+/// [`crate::redemptions`], and channels with bonus rolls to cheers and subscriptions, see
+/// [`crate::bonus`].
+pub async fn subscribe(req: Request, ctx: RouteContext<Context>) -> Result<Response> {
+    if !is_authorized(&req, &ctx.env)? {
+        return Ok(Response::builder()
+            .with_status(401)
+            .body(ResponseBody::Empty));
+    }
+
+    let Some(broadcaster_user_id) = ctx.param("broadcaster_user_id") else {
+        return Ok(Response::builder()
+            .with_status(400)
+            .body(ResponseBody::Empty));
+    };
+
+    let broadcaster_user_id = UserId::new(broadcaster_user_id.to_owned());
+    let callback = req
+        .url()?
+        .join("/eventsub")
+        .map_err(|e| Error::RustError(e.to_string()))?;
+
+    let config = CommandConfig::load(&ctx.env, &broadcaster_user_id).await;
+
+    let subscribed: std::result::Result<(), BotError> = async {
+        crate::streams::subscribe(&ctx.env, &broadcaster_user_id, callback.as_str()).await?;
+
+        if !config.rewards.is_empty() {
+            crate::redemptions::subscribe(&ctx.env, &broadcaster_user_id, callback.as_str())
+                .await?;
+        }
+
+        if config
+            .all_games()
+            .iter()
+            .any(|game| game.rules.bonus.is_enabled())
+        {
+            crate::bonus::subscribe(&ctx.env, &broadcaster_user_id, callback.as_str()).await?;
+        }
+
+        Ok(())
+    }
+    .await;
+
+    match subscribed {
+        Ok(()) => Ok(Response::builder()
+            .with_status(204)
+            .body(ResponseBody::Empty)),
+        Err(e) => Err(Error::RustError(e.to_string())),
+    }
+}
+
+/// `POST /admin/subscriptions`: Subscribes to whispers sent to the bot, delivered to this worker's
+/// `/eventsub`, see [`crate::whispers`].
+pub async fn subscribe_whispers(req: Request, ctx: RouteContext<Context>) -> Result<Response> {
+    if !is_authorized(&req, &ctx.env)? {
+        return Ok(Response::builder()
+            .with_status(401)
+            .body(ResponseBody::Empty));
+    }
+
+    let callback = req
+        .url()?
+        .join("/eventsub")
+        .map_err(|e| Error::RustError(e.to_string()))?;
+
+    match crate::whispers::subscribe(&ctx.env, callback.as_str()).await {
+        Ok(()) => Ok(Response::builder()
+            .with_status(204)
+            .body(ResponseBody::Empty)),
+        Err(e) => Err(Error::RustError(e.to_string())),
+    }
+}
+
+/// `DELETE /admin/users/:user_id`: Removes every attempt of a user in every channel, for data
+/// deletion requests. Their perfect hits are kept without them so epochs don't change, see
+/// [`crate::db::purge_user`]. Responds with how many attempts were affected.
+///
+/// Attempts in the user's own channel from other chatters are kept, and so is their opt-out, so the
+/// bot keeps not recording them. Timeouts of the user expire on their own.
+pub async fn purge_user(req: Request, ctx: RouteContext<Context>) -> Result<Response> {
+    if !is_authorized(&req, &ctx.env)? {
+        return Ok(Response::builder()
+            .with_status(401)
+            .body(ResponseBody::Empty));
+    }
+
+    let Some(user_id) = ctx
+        .param("user_id")
+        .filter(|user_id| user_id.as_str() != crate::db::DELETED_USER_ID)
+    else {
+        return Ok(Response::builder()
+            .with_status(400)
+            .body(ResponseBody::Empty));
+    };
+
+    let db = ctx.env.d1(FORTYFIVE_DATA)?;
+
+    match crate::db::purge_user(&db, user_id).await {
+        Ok(purge) => Response::from_json(&purge),
+        Err(e) => Err(Error::RustError(e.to_string())),
+    }
+}
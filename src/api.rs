@@ -0,0 +1,519 @@
+//! A public, read-only JSON API of the !45 standings of a channel, for embedding them elsewhere.
+//! Responses can be fetched from any origin and are cached for [`CACHE_SECS`].
+//!
+//! Every endpoint takes a `game` query parameter, which defaults to the built-in game. Lists take
+//! `limit` and `offset`, and tell the offset of the next page if there is one.
+
+use std::collections::HashMap;
+
+use rust_decimal::Decimal;
+use serde::Serialize;
+use sqlx_d1::D1Connection;
+use worker::{Cache, Context, Request, Response, Result, RouteContext, Url};
+
+use crate::db;
+use crate::prelude::*;
+
+/// How long responses are cached for.
+const CACHE_SECS: u32 = 30;
+
+const DEFAULT_LIMIT: i64 = 25;
+const MAX_LIMIT: i64 = 100;
+
+#[derive(Serialize)]
+struct ApiError {
+    error: &'static str,
+}
+
+#[derive(Serialize)]
+struct Page<T> {
+    data: Vec<T>,
+    pagination: Pagination,
+}
+
+#[derive(Serialize)]
+struct Pagination {
+    limit: i64,
+    offset: i64,
+    /// The offset of the next page, if there are more entries.
+    next_offset: Option<i64>,
+}
+
+#[derive(Serialize)]
+struct LeaderboardEntry {
+    rank: i64,
+    user_id: String,
+    user_name: Option<String>,
+    value: f64,
+    difference: f64,
+}
+
+#[derive(Serialize)]
+struct HallOfFameEntry {
+    epoch: i64,
+    user_id: String,
+    user_name: Option<String>,
+    /// Milliseconds since the Unix epoch.
+    timestamp: i64,
+}
+
+#[derive(Serialize)]
+struct Attempt {
+    user_id: String,
+    user_name: Option<String>,
+    value: f64,
+    difference: f64,
+}
+
+#[derive(Serialize)]
+struct Counts {
+    attempts: i64,
+    chatters: i64,
+    perfects: i64,
+    /// Attempts granted for cheering or subscribing, included in `attempts`.
+    bonus_attempts: i64,
+}
+
+impl From<db::Stats> for Counts {
+    fn from(stats: db::Stats) -> Self {
+        Self {
+            attempts: stats.attempts,
+            chatters: stats.chatters,
+            perfects: stats.perfects,
+            bonus_attempts: stats.bonus_attempts,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct ChannelStats {
+    broadcaster_user_id: String,
+    game: String,
+    target: Decimal,
+    epoch: i64,
+    best: Option<Attempt>,
+    worst: Option<Attempt>,
+    /// Counts of the current epoch.
+    current_epoch: Counts,
+    all_time: Counts,
+}
+
+#[derive(Serialize)]
+struct PersonalRecord {
+    rank: i64,
+    value: f64,
+    difference: f64,
+}
+
+#[derive(Serialize)]
+struct UserStats {
+    user_id: String,
+    user_name: String,
+    game: String,
+    epoch: i64,
+    best: Option<PersonalRecord>,
+    worst: Option<PersonalRecord>,
+    /// Counts of the current epoch, `chatters` is always 0 or 1.
+    current_epoch: Counts,
+    all_time: Counts,
+}
+
+/// The channel and game a request is about.
+struct Channel {
+    broadcaster_user_id: UserId,
+    game: GameConfig,
+    url: Url,
+}
+
+impl Channel {
+    fn param(&self, name: &str) -> Option<String> {
+        self.url
+            .query_pairs()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.into_owned())
+    }
+
+    fn pagination(&self) -> (i64, i64) {
+        let limit = self
+            .param("limit")
+            .and_then(|limit| limit.parse().ok())
+            .unwrap_or(DEFAULT_LIMIT)
+            .clamp(1, MAX_LIMIT);
+
+        let offset = self
+            .param("offset")
+            .and_then(|offset| offset.parse().ok())
+            .unwrap_or(0)
+            .max(0);
+
+        (limit, offset)
+    }
+}
+
+/// `GET /api/channels/:broadcaster_login/best`: Everyone's personal best of the current epoch.
+pub async fn best(req: Request, ctx: RouteContext<Context>) -> Result<Response> {
+    cached(req, ctx, |channel, env| leaderboard(channel, env, false)).await
+}
+
+/// `GET /api/channels/:broadcaster_login/worst`: Everyone's personal worst of the current epoch.
+pub async fn worst(req: Request, ctx: RouteContext<Context>) -> Result<Response> {
+    cached(req, ctx, |channel, env| leaderboard(channel, env, true)).await
+}
+
+/// `GET /api/channels/:broadcaster_login/hof`: Perfect hits, latest first. Takes an `epoch` to
+/// only get the one that ended it.
+pub async fn hof(req: Request, ctx: RouteContext<Context>) -> Result<Response> {
+    cached(req, ctx, hall_of_fame).await
+}
+
+/// `GET /api/channels/:broadcaster_login/stats`
+pub async fn stats(req: Request, ctx: RouteContext<Context>) -> Result<Response> {
+    cached(req, ctx, channel_stats).await
+}
+
+/// `GET /api/channels/:broadcaster_login/users/:user_login`
+pub async fn user(req: Request, ctx: RouteContext<Context>) -> Result<Response> {
+    let user_login = ctx.param("user_login").cloned().unwrap_or_default();
+
+    cached(req, ctx, move |channel, env| {
+        user_stats(channel, env, user_login)
+    })
+    .await
+}
+
+/// `OPTIONS /api/*path`: Lets browsers fetch the API from other origins.
+pub fn preflight(_: Request, _: RouteContext<Context>) -> Result<Response> {
+    let mut response = Response::empty()?.with_status(204);
+    cors(&mut response)?;
+    response
+        .headers_mut()
+        .set("Access-Control-Max-Age", "86400")?;
+
+    Ok(response)
+}
+
+fn cors(response: &mut Response) -> Result<()> {
+    let headers = response.headers_mut();
+    headers.set("Access-Control-Allow-Origin", "*")?;
+    headers.set("Access-Control-Allow-Methods", "GET, OPTIONS")?;
+
+    Ok(())
+}
+
+fn error(status: u16, error: &'static str) -> Result<Response> {
+    Ok(Response::from_json(&ApiError { error })?.with_status(status))
+}
+
+/// Serves a response from the cache, or resolves the channel and game of the request and caches
+/// what `handler` responds with.
+async fn cached<F, Fut>(req: Request, ctx: RouteContext<Context>, handler: F) -> Result<Response>
+where
+    F: FnOnce(Channel, Env) -> Fut,
+    Fut: Future<Output = BotResult<Response>>,
+{
+    let url = req.url()?;
+    let cache = Cache::default();
+
+    if let Some(response) = cache.get(url.as_str(), false).await? {
+        return Ok(response);
+    }
+
+    let Some(broadcaster_login) = ctx.param("broadcaster_login").cloned() else {
+        return error(400, "No channel given");
+    };
+
+    let env = ctx.env;
+
+    let result: BotResult<Response> = async {
+        let http_client = HelixClient::with_client(FetchClient::default());
+        let credentials = get_credentials(&env).await?;
+
+        let Some(broadcaster) = http_client
+            .get_user_from_login(&broadcaster_login.to_lowercase(), &credentials)
+            .await?
+        else {
+            return Ok(error(404, "Channel not found")?);
+        };
+
+        let config = CommandConfig::load(&env, &broadcaster.id).await;
+
+        let game = url
+            .query_pairs()
+            .find(|(key, _)| key == "game")
+            .map(|(_, value)| value.into_owned())
+            .unwrap_or_else(|| DEFAULT_GAME.to_owned());
+
+        let Some(game) = config.game(&game) else {
+            return Ok(error(404, "Game not found")?);
+        };
+
+        let channel = Channel {
+            broadcaster_user_id: broadcaster.id,
+            game,
+            url: url.clone(),
+        };
+
+        handler(channel, env.clone()).await
+    }
+    .await;
+
+    let mut response = match result {
+        Ok(response) => response,
+        Err(e) => {
+            console_error!("Error serving the API: {:?}", e);
+            error(500, "Something went wrong")?
+        }
+    };
+
+    cors(&mut response)?;
+
+    if response.status_code() == 200 {
+        response
+            .headers_mut()
+            .set("Cache-Control", &format!("public, max-age={CACHE_SECS}"))?;
+
+        cache.put(url.as_str(), response.cloned()?).await?;
+    }
+
+    Ok(response)
+}
+
+fn page<T>(mut data: Vec<T>, limit: i64, offset: i64) -> Page<T> {
+    // One more entry than asked for is fetched to know if there's a next page.
+    let has_more = data.len() as i64 > limit;
+    data.truncate(limit as usize);
+
+    Page {
+        data,
+        pagination: Pagination {
+            limit,
+            offset,
+            next_offset: has_more.then_some(offset + limit),
+        },
+    }
+}
+
+async fn display_names(env: &Env, user_ids: &[&str]) -> BotResult<HashMap<String, String>> {
+    let http_client = HelixClient::with_client(FetchClient::default());
+    let credentials = get_credentials(env).await?;
+
+    let mut user_ids = user_ids.to_vec();
+    user_ids.sort_unstable();
+    user_ids.dedup();
+
+    let user_ids = user_ids
+        .into_iter()
+        .map(UserIdRef::from_str)
+        .collect::<Vec<_>>();
+
+    Ok(get_display_names(&http_client, &user_ids, &credentials).await?)
+}
+
+async fn leaderboard(channel: Channel, env: Env, is_worst: bool) -> BotResult<Response> {
+    let db_conn = D1Connection::new(env.d1(FORTYFIVE_DATA)?);
+    let broadcaster_user_id = channel.broadcaster_user_id.as_str();
+    let (limit, offset) = channel.pagination();
+
+    let epoch = db::current_epoch(&db_conn, broadcaster_user_id, &channel.game.name).await?;
+    let records = db::leaderboard(
+        &db_conn,
+        broadcaster_user_id,
+        &channel.game.name,
+        epoch,
+        is_worst,
+        limit + 1,
+        offset,
+    )
+    .await?;
+
+    let names = display_names(
+        &env,
+        &records
+            .iter()
+            .map(|record| record.chatter_user_id.as_str())
+            .collect::<Vec<_>>(),
+    )
+    .await?;
+
+    let entries = records
+        .into_iter()
+        .zip(offset + 1..)
+        .map(|(record, rank)| LeaderboardEntry {
+            rank,
+            user_name: names.get(&record.chatter_user_id).cloned(),
+            user_id: record.chatter_user_id,
+            value: record.forty_five_value,
+            difference: record.forty_five_difference,
+        })
+        .collect();
+
+    Ok(Response::from_json(&page(entries, limit, offset))?)
+}
+
+async fn hall_of_fame(channel: Channel, env: Env) -> BotResult<Response> {
+    let db_conn = D1Connection::new(env.d1(FORTYFIVE_DATA)?);
+    let (limit, offset) = channel.pagination();
+
+    let epoch = match channel.param("epoch").map(|epoch| epoch.parse::<i64>()) {
+        Some(Ok(epoch)) => Some(epoch),
+        Some(Err(_)) => return Ok(error(400, "epoch must be a number")?),
+        None => None,
+    };
+
+    let entries = db::hall_of_fame(
+        &db_conn,
+        channel.broadcaster_user_id.as_str(),
+        &channel.game.name,
+        epoch,
+        limit + 1,
+        offset,
+    )
+    .await?;
+
+    let names = display_names(
+        &env,
+        &entries
+            .iter()
+            .map(|entry| entry.chatter_user_id.as_str())
+            .collect::<Vec<_>>(),
+    )
+    .await?;
+
+    let entries = entries
+        .into_iter()
+        .map(|entry| HallOfFameEntry {
+            epoch: entry.epoch,
+            user_name: names.get(&entry.chatter_user_id).cloned(),
+            user_id: entry.chatter_user_id,
+            timestamp: entry.forty_five_timestamp,
+        })
+        .collect();
+
+    Ok(Response::from_json(&page(entries, limit, offset))?)
+}
+
+async fn channel_stats(channel: Channel, env: Env) -> BotResult<Response> {
+    let db_conn = D1Connection::new(env.d1(FORTYFIVE_DATA)?);
+    let broadcaster_user_id = channel.broadcaster_user_id.as_str();
+    let game = &channel.game.name;
+
+    let epoch = db::current_epoch(&db_conn, broadcaster_user_id, game).await?;
+
+    let best =
+        db::optional(db::epoch_record(&db_conn, broadcaster_user_id, game, epoch, false).await)?;
+    let worst =
+        db::optional(db::epoch_record(&db_conn, broadcaster_user_id, game, epoch, true).await)?;
+
+    let current_epoch = db::stats(&db_conn, broadcaster_user_id, game, Some(epoch), None).await?;
+    let all_time = db::stats(&db_conn, broadcaster_user_id, game, None, None).await?;
+
+    let names = display_names(
+        &env,
+        &best
+            .iter()
+            .chain(&worst)
+            .map(|record| record.chatter_user_id.as_str())
+            .collect::<Vec<_>>(),
+    )
+    .await?;
+
+    let attempt = |record: db::Record| Attempt {
+        user_name: names.get(&record.chatter_user_id).cloned(),
+        user_id: record.chatter_user_id,
+        value: record.forty_five_value,
+        difference: record.forty_five_difference,
+    };
+
+    let stats = ChannelStats {
+        broadcaster_user_id: broadcaster_user_id.to_owned(),
+        game: game.clone(),
+        target: channel.game.rules.target,
+        epoch,
+        best: best.map(attempt),
+        worst: worst.map(attempt),
+        current_epoch: current_epoch.into(),
+        all_time: all_time.into(),
+    };
+
+    Ok(Response::from_json(&stats)?)
+}
+
+async fn user_stats(channel: Channel, env: Env, user_login: String) -> BotResult<Response> {
+    let http_client = HelixClient::with_client(FetchClient::default());
+    let credentials = get_credentials(&env).await?;
+
+    let Some(user) = http_client
+        .get_user_from_login(&user_login.to_lowercase(), &credentials)
+        .await?
+    else {
+        return Ok(error(404, "User not found")?);
+    };
+
+    let db_conn = D1Connection::new(env.d1(FORTYFIVE_DATA)?);
+    let broadcaster_user_id = channel.broadcaster_user_id.as_str();
+    let game = &channel.game.name;
+    let user_id = user.id.as_str();
+
+    let epoch = db::current_epoch(&db_conn, broadcaster_user_id, game).await?;
+
+    let mut records = Vec::with_capacity(2);
+
+    for is_worst in [false, true] {
+        let record = db::optional(
+            db::personal_record(
+                &db_conn,
+                broadcaster_user_id,
+                game,
+                epoch,
+                user_id,
+                is_worst,
+            )
+            .await,
+        )?;
+
+        let record = match record {
+            Some(record) => Some(PersonalRecord {
+                rank: db::personal_rank(
+                    &db_conn,
+                    broadcaster_user_id,
+                    game,
+                    epoch,
+                    user_id,
+                    is_worst,
+                )
+                .await?,
+                value: record.forty_five_value,
+                difference: record.forty_five_difference,
+            }),
+            None => None,
+        };
+
+        records.push(record);
+    }
+
+    let worst = records.pop().flatten();
+    let best = records.pop().flatten();
+
+    let current_epoch = db::stats(
+        &db_conn,
+        broadcaster_user_id,
+        game,
+        Some(epoch),
+        Some(user_id),
+    )
+    .await?;
+    let all_time = db::stats(&db_conn, broadcaster_user_id, game, None, Some(user_id)).await?;
+
+    let stats = UserStats {
+        user_id: user_id.to_owned(),
+        user_name: user.display_name.to_string(),
+        game: game.clone(),
+        epoch,
+        best,
+        worst,
+        current_epoch: current_epoch.into(),
+        all_time: all_time.into(),
+    };
+
+    Ok(Response::from_json(&stats)?)
+}
@@ -0,0 +1,202 @@
+//! Bonus rolls for supporters. Cheers of enough bits and new subscriptions or resubscriptions play
+//! the channel's games for the supporter, as configured by [`crate::config::BonusConfig`] of every
+//! game. Each roll is sent to chat like a `!45` and recorded with its source.
+//!
+//! Twitch only delivers cheers and subscriptions once the broadcaster logged in to the dashboard
+//! through `/dashboard/login?supporters`.
+
+use twitch_api::eventsub::{
+    Transport,
+    channel::{
+        ChannelCheerV1, ChannelCheerV1Payload, ChannelSubscribeV1, ChannelSubscribeV1Payload,
+        ChannelSubscriptionMessageV1, ChannelSubscriptionMessageV1Payload,
+    },
+};
+
+use crate::commands::{
+    CallableV2, Context, Deferred, Invocation, claim_message, forty_five::Generate,
+};
+use crate::config::BonusConfig;
+use crate::db::AttemptSource;
+use crate::prelude::*;
+
+/// The supporter bonus rolls are played for.
+struct Supporter {
+    broadcaster_user_id: UserId,
+    user_id: UserId,
+    user_name: DisplayName,
+    source: AttemptSource,
+    bypasses_timeouts: bool,
+}
+
+impl Invocation for Supporter {
+    fn broadcaster_user_id(&self) -> &UserId {
+        &self.broadcaster_user_id
+    }
+
+    fn chatter_user_id(&self) -> &UserId {
+        &self.user_id
+    }
+
+    fn chatter_user_name(&self) -> &DisplayName {
+        &self.user_name
+    }
+
+    fn source(&self) -> AttemptSource {
+        self.source
+    }
+
+    fn bypasses_timeouts(&self) -> bool {
+        self.bypasses_timeouts
+    }
+}
+
+/// Subscribes to cheers, subscriptions and resubscriptions of a channel, delivered to `callback`.
+pub async fn subscribe(env: &Env, broadcaster_user_id: &UserId, callback: &str) -> BotResult<()> {
+    let http_client = HelixClient::with_client(FetchClient::default());
+    let token = get_credentials(env).await?;
+    let secret = env.secret("HMAC_SECRET")?.to_string();
+
+    http_client
+        .create_eventsub_subscription(
+            ChannelCheerV1::broadcaster_user_id(broadcaster_user_id.clone()),
+            Transport::webhook(callback, secret.clone()),
+            &token,
+        )
+        .await?;
+
+    http_client
+        .create_eventsub_subscription(
+            ChannelSubscribeV1::broadcaster_user_id(broadcaster_user_id.clone()),
+            Transport::webhook(callback, secret.clone()),
+            &token,
+        )
+        .await?;
+
+    http_client
+        .create_eventsub_subscription(
+            ChannelSubscriptionMessageV1::broadcaster_user_id(broadcaster_user_id.clone()),
+            Transport::webhook(callback, secret),
+            &token,
+        )
+        .await?;
+
+    Ok(())
+}
+
+pub async fn cheer(env: Env, message_id: String, payload: ChannelCheerV1Payload) {
+    // Anonymous cheers can't be played for anyone.
+    let (Some(user_id), Some(user_name)) = (payload.user_id, payload.user_name) else {
+        return;
+    };
+
+    grant(
+        env,
+        &message_id,
+        payload.broadcaster_user_id,
+        user_id,
+        user_name,
+        AttemptSource::Cheer,
+        |bonus| bonus.rolls_for_cheer(payload.bits),
+    )
+    .await;
+}
+
+pub async fn subscription(env: Env, message_id: String, payload: ChannelSubscribeV1Payload) {
+    if payload.is_gift {
+        return;
+    }
+
+    grant(
+        env,
+        &message_id,
+        payload.broadcaster_user_id,
+        payload.user_id,
+        payload.user_name,
+        AttemptSource::Subscription,
+        |bonus| bonus.sub_rolls,
+    )
+    .await;
+}
+
+pub async fn resubscription(
+    env: Env,
+    message_id: String,
+    payload: ChannelSubscriptionMessageV1Payload,
+) {
+    grant(
+        env,
+        &message_id,
+        payload.broadcaster_user_id,
+        payload.user_id,
+        payload.user_name,
+        AttemptSource::Subscription,
+        |bonus| bonus.sub_rolls,
+    )
+    .await;
+}
+
+/// Plays every game of the channel as many times as `rolls` says for its bonus configuration,
+/// unless the notification `message_id` was already handled.
+async fn grant(
+    env: Env,
+    message_id: &str,
+    broadcaster_user_id: UserId,
+    user_id: UserId,
+    user_name: DisplayName,
+    source: AttemptSource,
+    rolls: impl Fn(&BonusConfig) -> u32,
+) {
+    if !claim_message(&env, message_id).await {
+        // Twitch delivered the notification again.
+        return;
+    }
+
+    let config = CommandConfig::load(&env, &broadcaster_user_id).await;
+
+    for game in config.all_games() {
+        let bonus = &game.rules.bonus;
+
+        for _ in 0..rolls(bonus) {
+            let deferred = Deferred::default();
+
+            let context = Context {
+                env: env.clone(),
+                payload: Supporter {
+                    broadcaster_user_id: broadcaster_user_id.clone(),
+                    user_id: user_id.clone(),
+                    user_name: user_name.clone(),
+                    source,
+                    bypasses_timeouts: bonus.bypass_timeouts,
+                },
+                config: config.clone(),
+                game: game.clone(),
+                deferred: deferred.clone(),
+            };
+
+            let played: BotResult<()> = async {
+                let message = Generate {}.call(context).await?;
+
+                if let Some(message) = message {
+                    send_message(&env, &broadcaster_user_id, &message).await?;
+                }
+
+                Ok(())
+            }
+            .await;
+
+            deferred.run().await;
+
+            if let Err(e) = played {
+                console_error!(
+                    "Error playing a bonus {} for {} in {}: {:?}",
+                    game.name,
+                    user_id,
+                    broadcaster_user_id,
+                    e
+                );
+                break;
+            }
+        }
+    }
+}
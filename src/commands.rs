@@ -1,18 +1,121 @@
+use std::cell::RefCell;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::time::Duration;
+
+use crate::db::AttemptSource;
 use crate::prelude::*;
 use argh::FromArgs;
+use twitch_api::eventsub::channel::ChannelPointsCustomRewardRedemptionAddV1Payload;
+use worker::Delay;
 
-mod forty_five;
+pub(crate) mod forty_five;
 
 pub struct Context<T> {
     pub env: Env,
     pub payload: T,
     pub config: CommandConfig,
+    /// The game the command was invoked for, !45 subcommands work the same way for every game.
+    pub game: GameConfig,
+    pub deferred: Deferred,
+}
+
+type DeferredFuture = Pin<Box<dyn Future<Output = ()>>>;
+
+/// Work that doesn't change a command's response, such as updating the overlay. Whoever runs the
+/// command runs it once the response is sent, so that chatters don't wait for it.
+#[derive(Clone, Default)]
+pub struct Deferred(Rc<RefCell<Vec<DeferredFuture>>>);
+
+impl Deferred {
+    pub fn push(&self, future: impl Future<Output = ()> + 'static) {
+        self.0.borrow_mut().push(Box::pin(future));
+    }
+
+    pub async fn run(self) {
+        let futures = std::mem::take(&mut *self.0.borrow_mut());
+
+        for future in futures {
+            future.await;
+        }
+    }
+}
+
+impl Context<ChannelChatMessageV1Payload> {
+    pub fn is_broadcaster(&self) -> bool {
+        self.payload.chatter_user_id == self.payload.broadcaster_user_id
+    }
+
+    /// Whether the chatter moderates the channel, which the broadcaster does as well.
+    pub fn is_mod(&self) -> bool {
+        self.payload
+            .badges
+            .iter()
+            .any(|x| x.set_id.as_str() == "moderator" || x.set_id.as_str() == "broadcaster")
+    }
+
+    /// Whether the chatter can change the channel's configuration, which moderators only can when
+    /// [`CommandConfig::mods_can_configure`] is set.
+    pub fn can_configure(&self) -> bool {
+        self.is_broadcaster() || (self.is_mod() && self.config.mods_can_configure)
+    }
 }
 
 pub trait CallableV2<Payload>: argh::FromArgs {
     async fn call(self, context: Context<Payload>) -> BotResult<Option<String>>;
 }
 
+/// Events that play a game for a chatter, so commands such as `!45 gen` can be run by more than
+/// chat messages.
+pub trait Invocation {
+    fn broadcaster_user_id(&self) -> &UserId;
+    fn chatter_user_id(&self) -> &UserId;
+    fn chatter_user_name(&self) -> &DisplayName;
+
+    /// What attempts are recorded as being played through.
+    fn source(&self) -> AttemptSource {
+        AttemptSource::Chat
+    }
+
+    /// Whether the game is played even while the chatter is timed out from it.
+    fn bypasses_timeouts(&self) -> bool {
+        false
+    }
+}
+
+impl Invocation for ChannelChatMessageV1Payload {
+    fn broadcaster_user_id(&self) -> &UserId {
+        &self.broadcaster_user_id
+    }
+
+    fn chatter_user_id(&self) -> &UserId {
+        &self.chatter_user_id
+    }
+
+    fn chatter_user_name(&self) -> &DisplayName {
+        &self.chatter_user_name
+    }
+}
+
+/// Channel point redemptions, see [`crate::redemptions`].
+impl Invocation for ChannelPointsCustomRewardRedemptionAddV1Payload {
+    fn broadcaster_user_id(&self) -> &UserId {
+        &self.broadcaster_user_id
+    }
+
+    fn chatter_user_id(&self) -> &UserId {
+        &self.user_id
+    }
+
+    fn chatter_user_name(&self) -> &DisplayName {
+        &self.user_name
+    }
+
+    fn source(&self) -> AttemptSource {
+        AttemptSource::ChannelPoints
+    }
+}
+
 #[derive(argh::FromArgs, Debug)]
 pub struct Root {
     #[argh(subcommand)]
@@ -37,40 +140,96 @@ pub async fn parse(env: Env, payload: ChannelChatMessageV1Payload) {
         .filter(|x| *x != "\u{34f}")
         .collect::<Vec<_>>();
 
-    if !args[0].starts_with("!") {
+    // Prefixes can only be ASCII punctuation, so anything else can be
+    // skipped before reading the configuration.
+    let Some(first) = args
+        .first()
+        .and_then(|first| first.chars().next())
+        .filter(|first| first.is_ascii_punctuation())
+    else {
         return;
-    }
+    };
 
     if payload.chatter_user_id == UserId::from_static(BOT_USER_ID) {
         return;
     }
 
-    if let Some(shared_chat_channel_id) = &payload.source_broadcaster_user_id
-        && shared_chat_channel_id != &payload.broadcaster_user_id
+    // During Shared Chat, messages of every channel in the session are
+    // delivered to every channel in it. Copies from other channels are
+    // handled as the channel's policy says.
+    let foreign = payload
+        .source_broadcaster_user_id
+        .as_ref()
+        .is_some_and(|source| source != &payload.broadcaster_user_id);
+
+    // Messages such as `@someone` or `...` are common, so unless they use
+    // the default prefix they're skipped when the channel is known to use
+    // another one. Copies from other channels go by the other channel's.
+    if first != DEFAULT_PREFIX
+        && !foreign
+        && known_prefix(&payload.broadcaster_user_id).is_some_and(|prefix| prefix != first)
     {
         return;
     }
 
+    let mut config = CommandConfig::load(&env, &payload.broadcaster_user_id).await;
+    let mut played = payload.clone();
+    let mut reply_to = payload.broadcaster_user_id.clone();
+
+    if let Some(source_broadcaster_user_id) = &payload.source_broadcaster_user_id
+        && foreign
+    {
+        let policy = config.shared_chat;
+
+        if policy == SharedChatPolicy::Ignore {
+            return;
+        }
+
+        config = CommandConfig::load(&env, source_broadcaster_user_id).await;
+        played.broadcaster_user_id = source_broadcaster_user_id.clone();
+        // Permissions are the ones of the channel that's played.
+        played.badges = payload.source_badges.clone().unwrap_or_default();
+
+        if policy == SharedChatPolicy::Origin {
+            reply_to = source_broadcaster_user_id.clone();
+        }
+    }
+
+    let Some(mut args) = config.resolve_command(&args) else {
+        return;
+    };
+
+    // Every game is played through the !45 subcommands, so point argh at
+    // them and remember which game it was for.
+    let Some(game) = config.game(args[0]) else {
+        return;
+    };
+
+    args[0] = "!45";
+
+    let whisper = args
+        .get(1)
+        .is_some_and(|command| config.whispered.contains(*command));
+
     let run = Root::from_args(&[], &args);
 
     match run {
         Ok(root) => {
-            let config_binding = env
-                .kv(CONFIG_KV)
-                .expect("Failed to open configuration values");
-
-            let config = config_binding
-                .get(payload.broadcaster_user_id.as_str())
-                .json()
-                .await
-                .ok()
-                .flatten()
-                .unwrap_or_default();
+            if payload.source_broadcaster_user_id.is_some()
+                && !claim_shared_message(&env, &payload, foreign).await
+            {
+                // Another channel of the Shared Chat session answers it.
+                return;
+            }
+
+            let deferred = Deferred::default();
 
             let context = Context {
                 env: env.clone(),
-                payload: payload.clone(),
+                payload: played,
                 config,
+                game,
+                deferred: deferred.clone(),
             };
 
             match root.sub {
@@ -82,10 +241,20 @@ pub async fn parse(env: Env, payload: ChannelChatMessageV1Payload) {
                         forty_five::Subcommands::Pb(pb) => pb.call(context).await,
                         forty_five::Subcommands::Pw(pw) => pw.call(context).await,
                         forty_five::Subcommands::Hof(hof) => hof.call(context).await,
+                        forty_five::Subcommands::Season(season) => season.call(context).await,
+                        forty_five::Subcommands::Achievements(achievements) => {
+                            achievements.call(context).await
+                        }
+                        forty_five::Subcommands::Stats(stats) => stats.call(context).await,
+                        forty_five::Subcommands::Optout(opt_out) => opt_out.call(context).await,
+                        forty_five::Subcommands::Optin(opt_in) => opt_in.call(context).await,
                         forty_five::Subcommands::Timeout(timeout) => timeout.call(context).await,
                         forty_five::Subcommands::Untimeout(untimeout) => {
                             untimeout.call(context).await
                         }
+                        forty_five::Subcommands::Reset(reset) => reset.call(context).await,
+                        forty_five::Subcommands::Void(void) => void.call(context).await,
+                        forty_five::Subcommands::Config(config) => config.call(context).await,
                     };
 
                     let scoped: Result<(), BotError> = async move {
@@ -94,13 +263,19 @@ pub async fn parse(env: Env, payload: ChannelChatMessageV1Payload) {
                             return Ok(());
                         };
 
+                        if whisper
+                            && crate::whispers::send(&env, &payload.chatter_user_id, &resp).await?
+                        {
+                            return Ok(());
+                        }
+
                         // Twitch-related things
                         let token = get_credentials(&env).await?;
                         let http_client = HelixClient::with_client(FetchClient::default());
 
                         http_client
                             .send_chat_message(
-                                &payload.broadcaster_user_id,
+                                &reply_to,
                                 UserId::from_static(BOT_USER_ID),
                                 &*resp,
                                 &token,
@@ -114,6 +289,8 @@ pub async fn parse(env: Env, payload: ChannelChatMessageV1Payload) {
                     if let Err(e) = scoped {
                         console_error!("Error processing command: {:?}", e);
                     }
+
+                    deferred.run().await;
                 }
             }
         }
@@ -122,3 +299,65 @@ pub async fn parse(env: Env, payload: ChannelChatMessageV1Payload) {
         }
     }
 }
+
+/// How long copies of a Shared Chat message from other channels wait before claiming it, so that
+/// the copy of the channel it was sent in comes first when the bot is in that channel as well.
+const FOREIGN_COPY_DELAY: Duration = Duration::from_secs(2);
+
+/// Whether this channel's copy of a Shared Chat message is the one to answer, so that the message
+/// isn't answered once for every channel of the session. The copy of the channel the message was
+/// sent in is always answered, copies from other channels only when no other copy came first.
+async fn claim_shared_message(
+    env: &Env,
+    payload: &ChannelChatMessageV1Payload,
+    foreign: bool,
+) -> bool {
+    if foreign {
+        Delay::from(FOREIGN_COPY_DELAY).await;
+    }
+
+    let claimed = claim_message(env, payload.message_id.as_str()).await;
+
+    !foreign || claimed
+}
+
+/// Marks a message as handled, false if it already was. Used for events Twitch can deliver more
+/// than once, and for messages every channel of a Shared Chat session receives.
+pub(crate) async fn claim_message(env: &Env, message_id: &str) -> bool {
+    let claimed: BotResult<bool> = async {
+        let db_conn = sqlx_d1::D1Connection::new(env.d1(FORTYFIVE_DATA)?);
+
+        let now: i64 = web_time::SystemTime::now()
+            .duration_since(web_time::SystemTime::UNIX_EPOCH)?
+            .as_millis()
+            .try_into()?;
+
+        Ok(crate::db::claim_message(&db_conn, message_id, now).await?)
+    }
+    .await;
+
+    claimed.unwrap_or_else(|e| {
+        // Answering twice is better than not at all.
+        console_error!("Error claiming message {}: {:?}", message_id, e);
+        true
+    })
+}
+
+/// How long claimed messages are remembered for. Twitch delivers every copy of a Shared Chat
+/// message within seconds, and EventSub notifications older than this are dropped when they
+/// arrive, so a retried notification can't be handled twice.
+pub const CLAIMED_MESSAGE_MILLIS: i64 = 10 * 60 * 1000;
+
+/// Forgets messages that were claimed long enough ago, run on a schedule.
+pub async fn prune_claimed_messages(env: &Env) -> BotResult<()> {
+    let db_conn = sqlx_d1::D1Connection::new(env.d1(FORTYFIVE_DATA)?);
+
+    let now: i64 = web_time::SystemTime::now()
+        .duration_since(web_time::SystemTime::UNIX_EPOCH)?
+        .as_millis()
+        .try_into()?;
+
+    crate::db::prune_claimed_messages(&db_conn, now - CLAIMED_MESSAGE_MILLIS).await?;
+
+    Ok(())
+}
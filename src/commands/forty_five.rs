@@ -2,15 +2,23 @@ use rand::Rng;
 
 use crate::{
     commands::{
-        CallableV2,
+        CallableV2, Invocation,
         forty_five::timeouts::{TimeoutData, timeout_key},
     },
+    overlay::{OverlayBest, OverlayEvent},
     prelude::*,
+    webhooks::{self, WebhookPayload},
 };
 
-mod best_worst;
+pub(crate) mod achievements;
+pub(crate) mod best_worst;
+mod config;
 mod hof;
-mod timeouts;
+mod moderation;
+mod opt_out;
+mod season;
+pub(crate) mod stats;
+pub(crate) mod timeouts;
 
 #[derive(argh::FromArgs, Debug)]
 #[argh(subcommand, name = "!45")]
@@ -29,9 +37,18 @@ pub enum Subcommands {
     Pb(best_worst::PersonalBest),
     Pw(best_worst::PersonalWorst),
     Hof(hof::HallOfFame),
+    Season(season::Season),
+    Achievements(achievements::Achievements),
+    Stats(stats::Stats),
+    Optout(opt_out::OptOut),
+    Optin(opt_out::OptIn),
     // Moderator-only command
     Timeout(timeouts::Timeout),
     Untimeout(timeouts::Untimeout),
+    Reset(moderation::Reset),
+    Void(moderation::Void),
+    // Broadcaster-only command, moderators can be allowed to use it.
+    Config(config::Config),
 }
 
 impl Default for Subcommands {
@@ -44,11 +61,8 @@ impl Default for Subcommands {
 #[argh(subcommand, name = "gen")]
 pub struct Generate {}
 
-impl CallableV2<ChannelChatMessageV1Payload> for Generate {
-    async fn call(
-        self,
-        context: super::Context<ChannelChatMessageV1Payload>,
-    ) -> BotResult<Option<String>> {
+impl<P: Invocation> CallableV2<P> for Generate {
+    async fn call(self, context: super::Context<P>) -> BotResult<Option<String>> {
         use rust_decimal::prelude::*;
 
         let timeouts = context.env.kv(TIMEOUTS_KV)?;
@@ -58,17 +72,31 @@ impl CallableV2<ChannelChatMessageV1Payload> for Generate {
 
         let chatter_timeout: Option<TimeoutData> = timeouts
             .get(&timeout_key(
-                &context.payload.broadcaster_user_id,
-                Some(&context.payload.chatter_user_id),
+                context.payload.broadcaster_user_id(),
+                Some(context.payload.chatter_user_id()),
             ))
             .json()
             .await?;
 
-        if let Some(_data) = chatter_timeout {
+        if chatter_timeout.is_some() && !context.payload.bypasses_timeouts() {
             // The user is timed out and such dont generate !45s.
             return Ok(None);
         }
 
+        if crate::db::is_opted_out(&db_conn, context.payload.chatter_user_id().as_str()).await? {
+            // The user asked not to be recorded, so they don't play either.
+            return Ok(None);
+        }
+
+        let stream =
+            crate::db::live_stream(&db_conn, context.payload.broadcaster_user_id().as_str())
+                .await?;
+
+        if stream.is_none() && context.config.live_only {
+            // The channel only plays while live.
+            return Ok(None);
+        }
+
         let mut rng = rand::rng();
 
         let time: i64 = web_time::SystemTime::now()
@@ -76,48 +104,276 @@ impl CallableV2<ChannelChatMessageV1Payload> for Generate {
             .as_millis()
             .try_into()?;
 
-        let raw_45 = rng.random_range(dec!(0)..=dec!(18000));
-        let refined_45 = raw_45 * dec!(0.005);
-        let difference = if refined_45 >= dec!(45.005) {
-            refined_45 - dec!(45)
+        let rules = &context.game.rules;
+        let locale = context.config.locale();
+
+        let raw_45 = rng.random_range(dec!(0)..=rules.steps());
+        let refined_45 = rules.value_at(raw_45);
+        let difference = rules.difference(refined_45);
+
+        let template = if difference.is_zero() {
+            rules.perfect_template(&context.config)
         } else {
-            dec!(45) - refined_45
+            context.config.template(MessageKey::Roll)
         };
 
-        let message = if raw_45 == dec!(9000) {
-            let message = context
-                .config
-                .forty_five
-                .perfect_45_message
-                .unwrap_or_else(|| "{{ chatter_user_name }} has achieved perfect 45!".to_owned());
+        let broadcaster_user_id = context.payload.broadcaster_user_id().as_str();
+        let chatter_user_id = context.payload.chatter_user_id().as_str();
 
-            message.replace(
-                "{{ chatter_user_name }}",
-                context.payload.chatter_user_name.as_str(),
-            )
+        // New records can only be told apart from the records before the
+        // attempt, so only look them up for channels that want to know.
+        let wants_records = context.config.webhooks.iter().any(|webhook| {
+            webhook.wants(WebhookEvent::NewBest) || webhook.wants(WebhookEvent::NewWorst)
+        });
+
+        let previous_records = if wants_records {
+            crate::db::current_extremes(&db_conn, broadcaster_user_id, &context.game.name).await?
         } else {
-            format!("{}, {}", context.payload.chatter_user_name, refined_45)
+            None
         };
 
-        sqlx_d1::query(
+        let attempt = sqlx_d1::query!(
             "
             WITH EpochCTE AS (
-                SELECT COUNT(*) AS epoch
-                FROM Attempts
-                WHERE forty_five_difference = 0 AND broadcaster_user_id  = ?1
+                SELECT (
+                    SELECT COUNT(*)
+                    FROM Attempts
+                    WHERE forty_five_difference = 0 AND broadcaster_user_id  = ?1 AND game = ?6
+                ) + (
+                    SELECT COUNT(*)
+                    FROM EpochResets
+                    WHERE broadcaster_user_id = ?1 AND game = ?6
+                ) AS epoch
             )
-            INSERT INTO Attempts (epoch, broadcaster_user_id, chatter_user_id, forty_five_value, forty_five_difference, forty_five_timestamp)
-            SELECT epoch, ?1, ?2, ?3, ?4, ?5 FROM EpochCTE;
-            "
+            INSERT INTO Attempts (epoch, broadcaster_user_id, chatter_user_id, forty_five_value, forty_five_difference, forty_five_timestamp, game, stream_id, source)
+            SELECT epoch, ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8 FROM EpochCTE
+            RETURNING id, epoch;
+            ",
+            broadcaster_user_id,
+            chatter_user_id,
+            refined_45.to_f64().expect("Failed to convert decimal to f64"),
+            difference.to_f64().expect("Failed to convert decimal to f64"),
+            time,
+            context.game.name,
+            stream.map(|stream| stream.id),
+            context.payload.source().name()
         )
-        .bind(context.payload.broadcaster_user_id.as_str())
-        .bind(context.payload.chatter_user_id.as_str())
-        .bind(refined_45.to_f64().expect("Failed to convert decimal to f64"))
-        .bind(difference.to_f64().expect("Failed to convert decimal to f64"))
-        .bind(time)
-        .execute(&db_conn)
+        .fetch_one(&db_conn)
         .await?;
 
+        let mut vars = Vars::new()
+            .with(Variable::User, context.payload.chatter_user_name())
+            .with(Variable::Value, rules.format_value(refined_45, locale))
+            .with(Variable::Difference, rules.format_value(difference, locale))
+            .with(Variable::Epoch, attempt.epoch)
+            .with(Variable::Game, &context.game.name)
+            .with(Variable::Target, rules.format_value(rules.target, locale));
+
+        // From here on the roll is recorded, so lookups for the response
+        // can't fail it, or a redemption would be refunded anyway.
+        if template.uses(Variable::Pb) {
+            let pb = crate::db::personal_record(
+                &db_conn,
+                broadcaster_user_id,
+                &context.game.name,
+                attempt.epoch,
+                chatter_user_id,
+                false,
+            )
+            .await;
+
+            match pb {
+                Ok(pb) => {
+                    vars = vars.with(
+                        Variable::Pb,
+                        rules.format_value(pb.forty_five_value, locale),
+                    );
+                }
+                Err(e) => console_error!("Error looking up the personal best: {:?}", e),
+            }
+        }
+
+        if template.uses(Variable::Rank) {
+            let rank = crate::db::personal_rank(
+                &db_conn,
+                broadcaster_user_id,
+                &context.game.name,
+                attempt.epoch,
+                chatter_user_id,
+                false,
+            )
+            .await;
+
+            match rank {
+                Ok(rank) => vars = vars.with(Variable::Rank, rank),
+                Err(e) => console_error!("Error looking up the personal rank: {:?}", e),
+            }
+        }
+
+        let mut message = template.render(&vars);
+
+        let stored = crate::db::Attempt {
+            id: attempt.id,
+            epoch: attempt.epoch,
+            game: context.game.name.clone(),
+            chatter_user_id: chatter_user_id.to_owned(),
+            forty_five_value: refined_45
+                .to_f64()
+                .expect("Failed to convert decimal to f64"),
+            forty_five_difference: difference
+                .to_f64()
+                .expect("Failed to convert decimal to f64"),
+            forty_five_timestamp: time,
+        };
+
+        match crate::achievements::unlock(
+            &db_conn,
+            &context.config,
+            broadcaster_user_id,
+            &context.game,
+            &stored,
+        )
+        .await
+        {
+            Ok(unlocked) => {
+                for achievement in unlocked {
+                    let vars = Vars::new()
+                        .with(Variable::User, context.payload.chatter_user_name())
+                        .with(Variable::Achievement, achievement.title(&context.config))
+                        .with(Variable::Game, &context.game.name);
+
+                    message.push(' ');
+                    message.push_str(
+                        &context
+                            .config
+                            .template(MessageKey::AchievementUnlocked)
+                            .render(&vars),
+                    );
+                }
+            }
+            // Achievements are secondary too, the roll has been recorded already.
+            Err(e) => console_error!("Error unlocking achievements: {:?}", e),
+        }
+
+        // The overlay and webhooks don't change the response, so they're
+        // left for after it's sent.
+        context.deferred.push({
+            let env = context.env.clone();
+            let broadcaster_user_id = broadcaster_user_id.to_owned();
+            let chatter_user_id = chatter_user_id.to_owned();
+            let rules = rules.clone();
+            let event = OverlayEvent {
+                game: context.game.name.clone(),
+                epoch: attempt.epoch,
+                user_name: context.payload.chatter_user_name().to_string(),
+                value: rules.format_value(refined_45, locale),
+                difference: rules.format_value(difference, locale),
+                perfect: difference.is_zero(),
+                best: None,
+            };
+
+            async move {
+                let published = publish_to_overlay(
+                    &env,
+                    &broadcaster_user_id,
+                    &chatter_user_id,
+                    &rules,
+                    locale,
+                    event,
+                )
+                .await;
+
+                if let Err(e) = published {
+                    // The overlay is secondary, the chatter still gets their !45.
+                    console_error!("Error publishing to the overlay: {:?}", e);
+                }
+            }
+        });
+
+        if !context.config.webhooks.is_empty() {
+            let difference_f64 = difference
+                .to_f64()
+                .expect("Failed to convert decimal to f64");
+
+            let payloads = webhooks::events_for(difference_f64, previous_records)
+                .into_iter()
+                .map(|event| WebhookPayload {
+                    event,
+                    broadcaster_user_id: broadcaster_user_id.to_owned(),
+                    game: context.game.name.clone(),
+                    epoch: match event {
+                        WebhookEvent::EpochRollover => attempt.epoch + 1,
+                        _ => attempt.epoch,
+                    },
+                    user_id: chatter_user_id.to_owned(),
+                    user_name: context.payload.chatter_user_name().to_string(),
+                    value: rules.format_value(refined_45, locale),
+                    difference: rules.format_value(difference, locale),
+                    timestamp: time,
+                })
+                .collect::<Vec<_>>();
+
+            let env = context.env.clone();
+            let config = context.config.clone();
+
+            context.deferred.push(async move {
+                if let Err(e) = webhooks::dispatch(&env, &config, &payloads).await {
+                    console_error!("Error queueing webhooks: {:?}", e);
+                }
+            });
+        }
+
         Ok(Some(message))
     }
 }
+
+/// Shows a roll on the channel's overlay along with the best attempt of its epoch, if the channel
+/// has an overlay.
+async fn publish_to_overlay(
+    env: &Env,
+    broadcaster_user_id: &str,
+    chatter_user_id: &str,
+    rules: &FortyFiveConfig,
+    locale: Locale,
+    mut event: OverlayEvent,
+) -> BotResult<()> {
+    if crate::overlay::secret(env, broadcaster_user_id)
+        .await?
+        .is_none()
+    {
+        return Ok(());
+    }
+
+    let db_conn = sqlx_d1::D1Connection::new(env.d1(FORTYFIVE_DATA)?);
+
+    let best = crate::db::epoch_record(
+        &db_conn,
+        broadcaster_user_id,
+        &event.game,
+        event.epoch,
+        false,
+    )
+    .await?;
+
+    let best_user_name = if best.chatter_user_id == chatter_user_id {
+        Some(event.user_name.clone())
+    } else {
+        let http_client = HelixClient::with_client(FetchClient::default());
+        let token = get_credentials(env).await?;
+
+        http_client
+            .get_user_from_id(&best.chatter_user_id, &token)
+            .await?
+            .map(|user| user.display_name.to_string())
+    };
+
+    event.best = best_user_name.map(|user_name| OverlayBest {
+        user_name,
+        value: rules.format_value(best.forty_five_value, locale),
+        difference: rules.format_value(best.forty_five_difference, locale),
+    });
+
+    crate::overlay::publish(env, broadcaster_user_id, &event).await?;
+
+    Ok(())
+}
@@ -0,0 +1,88 @@
+use sqlx_d1::D1Connection;
+
+use crate::{
+    achievements::Achievement,
+    commands::{CallableV2, Context, Invocation},
+    db,
+    prelude::*,
+};
+
+/// Lists the achievements a chatter unlocked in the game, e.g. `!45 achievements @user`.
+#[derive(argh::FromArgs, Debug)]
+#[argh(subcommand, name = "achievements")]
+pub struct Achievements {
+    #[argh(positional)]
+    pub chatter: Option<String>,
+}
+
+impl<P: Invocation> CallableV2<P> for Achievements {
+    async fn call(self, context: Context<P>) -> BotResult<Option<String>> {
+        let db_conn = D1Connection::new(context.env.d1(FORTYFIVE_DATA)?);
+
+        let (user_id, user_name) = if let Some(login) = &self.chatter {
+            let token = get_credentials(&context.env).await?;
+            let http_client = HelixClient::with_client(FetchClient::default());
+
+            let user = http_client
+                .get_user_from_login(&login.replace("@", "").to_lowercase(), &token)
+                .await?;
+
+            match user {
+                Some(user) => (
+                    user.id.as_str().to_owned(),
+                    user.display_name.as_str().to_owned(),
+                ),
+                None => {
+                    let vars = Vars::new().with(Variable::User, login);
+                    return Ok(Some(
+                        context
+                            .config
+                            .template(MessageKey::UserNotFound)
+                            .render(&vars),
+                    ));
+                }
+            }
+        } else {
+            (
+                context.payload.chatter_user_id().as_str().to_owned(),
+                context.payload.chatter_user_name().as_str().to_owned(),
+            )
+        };
+
+        let unlocked = db::achievements(
+            &db_conn,
+            context.payload.broadcaster_user_id().as_str(),
+            &context.game.name,
+            &user_id,
+        )
+        .await?;
+
+        let titles = unlocked
+            .iter()
+            .filter_map(|name| Achievement::from_name(name))
+            .map(|achievement| achievement.title(&context.config))
+            .collect::<Vec<_>>();
+
+        let vars = Vars::new()
+            .with(Variable::User, &user_name)
+            .with(Variable::Game, &context.game.name);
+
+        if titles.is_empty() {
+            return Ok(Some(
+                context
+                    .config
+                    .template(MessageKey::NoAchievements)
+                    .render(&vars),
+            ));
+        }
+
+        let vars = vars.with(Variable::Achievement, titles.join(", "));
+
+        Ok(Some(
+            context
+                .config
+                .template(MessageKey::AchievementList)
+                .render(&vars),
+        ))
+    }
+}
@@ -1,69 +1,177 @@
-use crate::commands::{CallableV2, Context};
+use crate::commands::{CallableV2, Context, Invocation};
+use crate::db::{
+    self, current_epoch, epoch_record, period_rank, period_record, personal_rank, personal_record,
+    stream_rank, stream_record,
+};
 use crate::prelude::*;
+use crate::time::{Calendar, calendar_span};
 
+/// `!45 best [period]`, the period being `season`, `stream`, `today`, `week`, `month` or `all`.
 #[derive(argh::FromArgs, Debug)]
 #[argh(subcommand, name = "best")]
-pub struct Best {}
+pub struct Best {
+    #[argh(positional)]
+    pub period: Option<String>,
+}
 
+/// `!45 pb [period] [user]`
 #[derive(argh::FromArgs, Debug)]
 #[argh(subcommand, name = "pb")]
 pub struct PersonalBest {
     #[argh(positional)]
-    pub chatter_user_name: Option<String>,
+    pub args: Vec<String>,
 }
 
+/// `!45 worst [period]`
 #[derive(argh::FromArgs, Debug)]
 #[argh(subcommand, name = "worst")]
-pub struct Worst {}
+pub struct Worst {
+    #[argh(positional)]
+    pub period: Option<String>,
+}
 
+/// `!45 pw [period] [user]`
 #[derive(argh::FromArgs, Debug)]
 #[argh(subcommand, name = "pw")]
 pub struct PersonalWorst {
     #[argh(positional)]
-    pub chatter_user_name: Option<String>,
+    pub args: Vec<String>,
 }
 
-impl CallableV2<ChannelChatMessageV1Payload> for Best {
-    async fn call(
+/// What records are taken from instead of the current epoch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Period {
+    /// The season running right now.
+    Season,
+    /// The broadcast the channel is live with.
+    Stream,
+    /// The day, week or month so far, in the channel's timezone.
+    Calendar(Calendar),
+    /// Every attempt regardless of epochs.
+    AllTime,
+}
+
+/// The attempts a period covers.
+enum Scope {
+    /// Made between two timestamps, `from` inclusive and `to` exclusive.
+    Between(i64, i64),
+    /// Made during the stream with this ID.
+    Stream(String),
+}
+
+impl Period {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "season" => Some(Self::Season),
+            "stream" => Some(Self::Stream),
+            "today" => Some(Self::Calendar(Calendar::Day)),
+            "week" => Some(Self::Calendar(Calendar::Week)),
+            "month" => Some(Self::Calendar(Calendar::Month)),
+            "all" => Some(Self::AllTime),
+            _ => None,
+        }
+    }
+
+    /// The name and scope of the period at `now`. `None` if there's no such period right now.
+    async fn scope(
         self,
-        context: crate::commands::Context<ChannelChatMessageV1Payload>,
-    ) -> BotResult<Option<String>> {
-        best_worst_impl(false, false, None, context).await
+        config: &CommandConfig,
+        db_conn: &sqlx_d1::D1Connection,
+        broadcaster_user_id: &str,
+        now: i64,
+    ) -> BotResult<Option<(String, Scope)>> {
+        let name = |key: MessageKey| config.template(key).render(&Vars::new());
+
+        let scope = match self {
+            Self::Season => db::current_season(db_conn, broadcaster_user_id, now)
+                .await?
+                .map(|season| {
+                    (
+                        season.name,
+                        Scope::Between(season.starts_at, season.ends_at),
+                    )
+                }),
+            Self::Stream => db::live_stream(db_conn, broadcaster_user_id)
+                .await?
+                .map(|stream| (name(MessageKey::PeriodStream), Scope::Stream(stream.id))),
+            Self::Calendar(calendar) => {
+                let (from, to) = calendar_span(calendar, now, config.timezone());
+                let key = match calendar {
+                    Calendar::Day => MessageKey::PeriodToday,
+                    Calendar::Week => MessageKey::PeriodWeek,
+                    Calendar::Month => MessageKey::PeriodMonth,
+                };
+
+                Some((name(key), Scope::Between(from, to)))
+            }
+            Self::AllTime => Some((name(MessageKey::PeriodAllTime), Scope::Between(0, now + 1))),
+        };
+
+        Ok(scope)
     }
 }
 
-impl CallableV2<ChannelChatMessageV1Payload> for Worst {
-    async fn call(
-        self,
-        context: crate::commands::Context<ChannelChatMessageV1Payload>,
-    ) -> BotResult<Option<String>> {
-        best_worst_impl(false, true, None, context).await
+/// Splits the arguments of `!45 pb` and `!45 pw` into the period and the chatter, both optional.
+fn period_and_chatter(args: Vec<String>) -> (Option<Period>, Option<String>) {
+    let mut args = args.into_iter();
+    let first = args.next();
+
+    match first.as_deref().and_then(Period::from_name) {
+        Some(period) => (Some(period), args.next()),
+        None => (None, first),
     }
 }
 
-impl CallableV2<ChannelChatMessageV1Payload> for PersonalBest {
-    async fn call(
-        self,
-        context: crate::commands::Context<ChannelChatMessageV1Payload>,
-    ) -> BotResult<Option<String>> {
-        best_worst_impl(true, false, self.chatter_user_name, context).await
+/// Reads the period of `!45 best` and `!45 worst`, `Err` for anything that isn't one.
+fn parse_period(period: Option<String>) -> Result<Option<Period>, ()> {
+    match period {
+        Some(name) => Period::from_name(&name).map(Some).ok_or(()),
+        None => Ok(None),
     }
 }
 
-impl CallableV2<ChannelChatMessageV1Payload> for PersonalWorst {
-    async fn call(
-        self,
-        context: crate::commands::Context<ChannelChatMessageV1Payload>,
-    ) -> BotResult<Option<String>> {
-        best_worst_impl(true, true, self.chatter_user_name, context).await
+impl<P: Invocation> CallableV2<P> for Best {
+    async fn call(self, context: crate::commands::Context<P>) -> BotResult<Option<String>> {
+        let Ok(period) = parse_period(self.period) else {
+            return Ok(None);
+        };
+
+        best_worst_impl(false, false, None, period, context).await
     }
 }
 
-async fn best_worst_impl(
+impl<P: Invocation> CallableV2<P> for Worst {
+    async fn call(self, context: crate::commands::Context<P>) -> BotResult<Option<String>> {
+        let Ok(period) = parse_period(self.period) else {
+            return Ok(None);
+        };
+
+        best_worst_impl(false, true, None, period, context).await
+    }
+}
+
+impl<P: Invocation> CallableV2<P> for PersonalBest {
+    async fn call(self, context: crate::commands::Context<P>) -> BotResult<Option<String>> {
+        let (period, chatter_user_name) = period_and_chatter(self.args);
+
+        best_worst_impl(true, false, chatter_user_name, period, context).await
+    }
+}
+
+impl<P: Invocation> CallableV2<P> for PersonalWorst {
+    async fn call(self, context: crate::commands::Context<P>) -> BotResult<Option<String>> {
+        let (period, chatter_user_name) = period_and_chatter(self.args);
+
+        best_worst_impl(true, true, chatter_user_name, period, context).await
+    }
+}
+
+async fn best_worst_impl<P: Invocation>(
     is_personal: bool,
     is_worst: bool,
     chatter_user_name: Option<String>,
-    context: Context<ChannelChatMessageV1Payload>,
+    period: Option<Period>,
+    context: Context<P>,
 ) -> BotResult<Option<String>> {
     // Database related init
     let database = context.env.d1(FORTYFIVE_DATA)?;
@@ -73,6 +181,11 @@ async fn best_worst_impl(
     let token = get_credentials(&context.env).await?;
     let http_client = HelixClient::with_client(FetchClient::default());
 
+    let broadcaster_user_id = context.payload.broadcaster_user_id().as_str();
+    let game = &context.game;
+    let rules = &game.rules;
+    let locale = context.config.locale();
+
     // Personal Bests and Worsts
     let user_id = if let Some(login) = &chatter_user_name {
         let user = http_client
@@ -83,43 +196,163 @@ async fn best_worst_impl(
             Some(user) => {
                 let id = user.id.as_str().to_owned();
 
-                (Some(id), user.display_name.as_str().to_owned())
+                (id, user.display_name.as_str().to_owned())
+            }
+            None => {
+                let vars = Vars::new().with(Variable::User, login);
+                return Ok(Some(
+                    context
+                        .config
+                        .template(MessageKey::UserNotFound)
+                        .render(&vars),
+                ));
             }
-            None => return Ok(Some(format!("User {login} not found."))),
         }
     } else {
         (
-            Some(context.payload.chatter_user_id.as_str().to_owned()),
-            context.payload.chatter_user_name.as_str().to_owned(),
+            context.payload.chatter_user_id().as_str().to_owned(),
+            context.payload.chatter_user_name().as_str().to_owned(),
         )
     };
 
-    let result = sqlx_d1::query!(
-        "
-        SELECT chatter_user_id, forty_five_value
-        FROM Attempts
-        WHERE
-            Attempts.epoch = (SELECT COUNT(*) FROM Attempts WHERE forty_five_difference = 0 AND broadcaster_user_id = ?3)
-            AND Attempts.broadcaster_user_id = ?3
-            AND (?1 = 0 OR Attempts.chatter_user_id = ?4)
-        ORDER BY
-            (CASE
-                WHEN ?2 = 0 THEN
-                    +Attempts.forty_five_difference
-                ELSE
-                    -Attempts.forty_five_difference
-            END),
-            Attempts.forty_five_timestamp DESC
-        LIMIT 1;
-        ",
-        is_personal,
-        is_worst,
-        context.payload.broadcaster_user_id.as_str(),
-        user_id.0
-    ).fetch_one(&db_conn).await;
-
-    let query = match result {
-        Ok(query) => query,
+    if let Some(period) = period {
+        let now: i64 = web_time::SystemTime::now()
+            .duration_since(web_time::SystemTime::UNIX_EPOCH)?
+            .as_millis()
+            .try_into()?;
+
+        let Some((name, scope)) = period
+            .scope(&context.config, &db_conn, broadcaster_user_id, now)
+            .await?
+        else {
+            let key = match period {
+                Period::Stream => MessageKey::NotLive,
+                _ => MessageKey::NoSeason,
+            };
+
+            return Ok(Some(context.config.template(key).render(&Vars::new())));
+        };
+
+        let vars = Vars::new()
+            .with(Variable::Period, &name)
+            .with(Variable::Game, &game.name);
+
+        let chatter_user_id = is_personal.then_some(user_id.0.as_str());
+        let record = match &scope {
+            Scope::Between(from, to) => {
+                period_record(
+                    &db_conn,
+                    broadcaster_user_id,
+                    &game.name,
+                    *from,
+                    *to,
+                    chatter_user_id,
+                    is_worst,
+                )
+                .await
+            }
+            Scope::Stream(stream_id) => {
+                stream_record(
+                    &db_conn,
+                    broadcaster_user_id,
+                    &game.name,
+                    stream_id,
+                    chatter_user_id,
+                    is_worst,
+                )
+                .await
+            }
+        };
+        let record = db::optional(record)?;
+
+        let Some(record) = record else {
+            return Ok(Some(
+                context
+                    .config
+                    .template(MessageKey::NoAttemptsInPeriod)
+                    .render(&vars),
+            ));
+        };
+
+        let Some(user) = http_client
+            .get_user_from_id(&record.chatter_user_id, &token)
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        let key = match (is_personal, is_worst) {
+            (false, false) => MessageKey::PeriodBest,
+            (false, true) => MessageKey::PeriodWorst,
+            (true, false) => MessageKey::PeriodPersonalBest,
+            (true, true) => MessageKey::PeriodPersonalWorst,
+        };
+
+        let template = context.config.template(key);
+
+        let mut vars = vars
+            .with(Variable::User, user.display_name.as_str())
+            .with(
+                Variable::Value,
+                rules.format_value(record.forty_five_value, locale),
+            )
+            .with(
+                Variable::Difference,
+                rules.format_value(record.forty_five_difference, locale),
+            )
+            .with(Variable::Target, rules.format_value(rules.target, locale));
+
+        if template.uses(Variable::Rank) {
+            let rank = match &scope {
+                Scope::Between(from, to) => {
+                    period_rank(
+                        &db_conn,
+                        broadcaster_user_id,
+                        &game.name,
+                        *from,
+                        *to,
+                        &record.chatter_user_id,
+                        is_worst,
+                    )
+                    .await?
+                }
+                Scope::Stream(stream_id) => {
+                    stream_rank(
+                        &db_conn,
+                        broadcaster_user_id,
+                        &game.name,
+                        stream_id,
+                        &record.chatter_user_id,
+                        is_worst,
+                    )
+                    .await?
+                }
+            };
+
+            vars = vars.with(Variable::Rank, rank);
+        }
+
+        return Ok(Some(template.render(&vars)));
+    }
+
+    let epoch = current_epoch(&db_conn, broadcaster_user_id, &game.name).await?;
+
+    let result = if is_personal {
+        personal_record(
+            &db_conn,
+            broadcaster_user_id,
+            &game.name,
+            epoch,
+            &user_id.0,
+            is_worst,
+        )
+        .await
+    } else {
+        epoch_record(&db_conn, broadcaster_user_id, &game.name, epoch, is_worst).await
+    };
+
+    let record = match result {
+        Ok(record) => record,
         Err(error) => match error {
             sqlx_d1::Error::RowNotFound => {
                 let query = sqlx_d1::query!(
@@ -130,27 +363,40 @@ async fn best_worst_impl(
                       Attempts
                     WHERE
                       broadcaster_user_id = ?1
+                      AND game = ?3
                       AND chatter_user_id = ?2
+                      AND voided_by IS NULL
                     LIMIT 1;
                     ",
-                    context.payload.broadcaster_user_id.as_str(),
-                    user_id.0
+                    broadcaster_user_id,
+                    user_id.0,
+                    game.name
                 )
                 .fetch_one(&db_conn)
                 .await;
 
+                let vars = Vars::new()
+                    .with(Variable::Game, &game.name)
+                    .with(Variable::Command, game.command(context.config.prefix()));
+
                 if query.is_ok() {
-                    return Ok(Some(format!(
-                        "User {} has done a !45 in this channel, but a perfect 45 has been achieved and such the values has been wiped.",
-                        user_id.1
-                    )));
+                    let vars = vars.with(Variable::User, &user_id.1);
+                    return Ok(Some(
+                        context
+                            .config
+                            .template(MessageKey::AttemptsWiped)
+                            .render(&vars),
+                    ));
                 }
 
                 if let Some(login) = &chatter_user_name {
-                    return Ok(Some(format!(
-                        "User {} hasn't done a !45 in this channel.",
-                        login
-                    )));
+                    let vars = vars.with(Variable::User, login);
+                    return Ok(Some(
+                        context
+                            .config
+                            .template(MessageKey::NoAttempts)
+                            .render(&vars),
+                    ));
                 }
 
                 return Ok(None);
@@ -160,27 +406,47 @@ async fn best_worst_impl(
     };
 
     if let Some(user) = http_client
-        .get_user_from_id(&query.chatter_user_id, &token)
+        .get_user_from_id(&record.chatter_user_id, &token)
         .await?
     {
-        let resp = format!(
-            "{} 45 by {}: {:.3}",
-            if !is_personal {
-                if !is_worst {
-                    "Current best"
-                } else {
-                    "Current worst"
-                }
-            } else if !is_worst {
-                "Personal best"
-            } else {
-                "Personal worst"
-            },
-            user.display_name.as_str(),
-            query.forty_five_value
-        );
-
-        return Ok(Some(resp));
+        let key = match (is_personal, is_worst) {
+            (false, false) => MessageKey::Best,
+            (false, true) => MessageKey::Worst,
+            (true, false) => MessageKey::PersonalBest,
+            (true, true) => MessageKey::PersonalWorst,
+        };
+
+        let template = context.config.template(key);
+
+        let mut vars = Vars::new()
+            .with(Variable::User, user.display_name.as_str())
+            .with(
+                Variable::Value,
+                rules.format_value(record.forty_five_value, locale),
+            )
+            .with(
+                Variable::Difference,
+                rules.format_value(record.forty_five_difference, locale),
+            )
+            .with(Variable::Epoch, epoch)
+            .with(Variable::Game, &game.name)
+            .with(Variable::Target, rules.format_value(rules.target, locale));
+
+        if template.uses(Variable::Rank) {
+            let rank = personal_rank(
+                &db_conn,
+                broadcaster_user_id,
+                &game.name,
+                epoch,
+                &record.chatter_user_id,
+                is_worst,
+            )
+            .await?;
+
+            vars = vars.with(Variable::Rank, rank);
+        }
+
+        return Ok(Some(template.render(&vars)));
     }
 
     Ok(None)
@@ -0,0 +1,360 @@
+use serde_json::Value;
+
+use crate::{
+    commands::{CallableV2, Context},
+    prelude::*,
+};
+
+#[derive(argh::FromArgs, Debug)]
+#[argh(subcommand, name = "config")]
+pub struct Config {
+    #[argh(subcommand)]
+    pub sub: ConfigSubcommands,
+}
+
+#[derive(argh::FromArgs, Debug)]
+#[argh(subcommand)]
+pub enum ConfigSubcommands {
+    Get(Get),
+    Set(Set),
+    Reset(Reset),
+}
+
+#[derive(argh::FromArgs, Debug)]
+#[argh(subcommand, name = "get")]
+pub struct Get {
+    #[argh(positional)]
+    pub key: String,
+}
+
+#[derive(argh::FromArgs, Debug)]
+#[argh(subcommand, name = "set")]
+pub struct Set {
+    #[argh(positional)]
+    pub key: String,
+    #[argh(positional, greedy)]
+    pub value: Vec<String>,
+}
+
+#[derive(argh::FromArgs, Debug)]
+#[argh(subcommand, name = "reset")]
+pub struct Reset {
+    #[argh(positional)]
+    pub key: String,
+}
+
+/// The setting that decides whether moderators can use `!45 config`, which only the broadcaster
+/// can change.
+const MODS_CAN_CONFIGURE_KEY: &str = "mods_can_configure";
+
+impl CallableV2<ChannelChatMessageV1Payload> for Config {
+    async fn call(
+        self,
+        context: crate::commands::Context<ChannelChatMessageV1Payload>,
+    ) -> BotResult<Option<String>> {
+        let is_broadcaster = context.is_broadcaster();
+
+        if !context.can_configure() {
+            // Only the broadcaster, and moderators if allowed to, can change
+            // the configuration.
+            return Ok(None);
+        }
+
+        match self.sub {
+            ConfigSubcommands::Get(Get { key }) => {
+                let key_vars = Vars::new().with(Variable::Key, &key);
+
+                let mut shown = serde_json::to_value(&context.config)?;
+                redact(&mut shown);
+
+                let msg = match get_path(&shown, &key) {
+                    Some(value) => context
+                        .config
+                        .template(MessageKey::ConfigValue)
+                        .render(&key_vars.with(Variable::Value, display_value(value))),
+                    None => context
+                        .config
+                        .template(MessageKey::ConfigUnknownKey)
+                        .render(&key_vars),
+                };
+
+                Ok(Some(msg))
+            }
+            ConfigSubcommands::Set(Set { key, value }) => {
+                if key == MODS_CAN_CONFIGURE_KEY && !is_broadcaster {
+                    return Ok(None);
+                }
+
+                let value = value.join(" ");
+
+                let current = match stored(&context).await {
+                    Ok(current) => current,
+                    Err(error) => {
+                        let result = Err(ConfigUpdateError::Invalid(error.to_string()));
+                        return respond(&context, &key, &value, MessageKey::ConfigSet, result)
+                            .await;
+                    }
+                };
+
+                // Values are read as JSON so that numbers, booleans and lists
+                // can be set, anything that isn't JSON is taken as text.
+                let candidates = [
+                    serde_json::from_str(&value).ok(),
+                    Some(Value::String(value.clone())),
+                ];
+
+                let mut result = Err(ConfigUpdateError::UnknownKey);
+
+                for candidate in candidates.into_iter().flatten() {
+                    let mut updated = current.clone();
+
+                    if !set_path(&mut updated, &key, Some(candidate)) {
+                        break;
+                    }
+
+                    result = apply(updated, &key, true);
+
+                    if result.is_ok() {
+                        break;
+                    }
+                }
+
+                respond(&context, &key, &value, MessageKey::ConfigSet, result).await
+            }
+            ConfigSubcommands::Reset(Reset { key }) => {
+                if key == MODS_CAN_CONFIGURE_KEY && !is_broadcaster {
+                    return Ok(None);
+                }
+
+                let current = match stored(&context).await {
+                    Ok(current) => current,
+                    Err(error) => {
+                        let result = Err(ConfigUpdateError::Invalid(error.to_string()));
+                        return respond(&context, &key, "", MessageKey::ConfigReset, result).await;
+                    }
+                };
+
+                let defaults = serde_json::to_value(CommandConfig::default())?;
+                let default = get_path(&defaults, &key).cloned();
+
+                // Keys that don't exist by default are entries of maps, such
+                // as aliases, which are removed instead.
+                let result = if get_path(&current, &key).is_none() && default.is_none() {
+                    Err(ConfigUpdateError::UnknownKey)
+                } else {
+                    let mut updated = current.clone();
+
+                    if set_path(&mut updated, &key, default) {
+                        apply(updated, &key, false)
+                    } else {
+                        Err(ConfigUpdateError::UnknownKey)
+                    }
+                };
+
+                respond(&context, &key, "", MessageKey::ConfigReset, result).await
+            }
+        }
+    }
+}
+
+/// The stored configuration that changes are made to. Unlike the loaded configuration, settings
+/// that were ignored for being invalid are still there, so a change can't silently delete them, and
+/// the change is refused until they're fixed.
+async fn stored(context: &Context<ChannelChatMessageV1Payload>) -> BotResult<Value> {
+    let config = CommandConfig::read(&context.env, &context.payload.broadcaster_user_id).await?;
+
+    Ok(serde_json::to_value(config.unwrap_or_default())?)
+}
+
+enum ConfigUpdateError {
+    UnknownKey,
+    Invalid(String),
+}
+
+/// Turns the updated JSON back into a configuration, checking that it has the right types and
+/// passes validation. When `check_key` is set, the key also has to survive the round trip, as
+/// fields that don't exist would be silently dropped otherwise.
+fn apply(updated: Value, key: &str, check_key: bool) -> Result<CommandConfig, ConfigUpdateError> {
+    let config: CommandConfig =
+        serde_json::from_value(updated).map_err(|e| ConfigUpdateError::Invalid(e.to_string()))?;
+
+    if check_key {
+        let round_trip =
+            serde_json::to_value(&config).map_err(|e| ConfigUpdateError::Invalid(e.to_string()))?;
+
+        if get_path(&round_trip, key).is_none() {
+            return Err(ConfigUpdateError::UnknownKey);
+        }
+    }
+
+    config
+        .validate()
+        .map_err(|e| ConfigUpdateError::Invalid(e.to_string()))?;
+
+    Ok(config)
+}
+
+async fn respond(
+    context: &Context<ChannelChatMessageV1Payload>,
+    key: &str,
+    value: &str,
+    success: MessageKey,
+    result: Result<CommandConfig, ConfigUpdateError>,
+) -> BotResult<Option<String>> {
+    let vars = Vars::new().with(Variable::Key, key);
+
+    let msg = match result {
+        Ok(config) => {
+            config
+                .save(&context.env, &context.payload.broadcaster_user_id)
+                .await?;
+
+            let mut saved = serde_json::to_value(&config)?;
+            redact(&mut saved);
+            let value = get_path(&saved, key).map(display_value).unwrap_or_default();
+
+            config
+                .template(success)
+                .render(&vars.with(Variable::Value, value))
+        }
+        Err(ConfigUpdateError::UnknownKey) => context
+            .config
+            .template(MessageKey::ConfigUnknownKey)
+            .render(&vars),
+        Err(ConfigUpdateError::Invalid(error)) => {
+            context.config.template(MessageKey::ConfigInvalid).render(
+                &vars
+                    .with(Variable::Value, value)
+                    .with(Variable::Error, error),
+            )
+        }
+    };
+
+    Ok(Some(msg))
+}
+
+/// Hides the URLs of webhooks, since anyone who has one can send to it.
+fn redact(config: &mut Value) {
+    let Some(Value::Array(webhooks)) = config.get_mut("webhooks") else {
+        return;
+    };
+
+    for webhook in webhooks {
+        if let Some(url) = webhook.get_mut("url") {
+            *url = Value::String("***".to_owned());
+        }
+    }
+}
+
+/// Looks up a dotted key such as `forty_five.step`.
+fn get_path<'a>(value: &'a Value, key: &str) -> Option<&'a Value> {
+    key.split('.')
+        .try_fold(value, |value, segment| value.get(segment))
+}
+
+/// Sets or, with `None`, removes a dotted key. Returns false if the parent of the key isn't an
+/// object.
+fn set_path(value: &mut Value, key: &str, new: Option<Value>) -> bool {
+    let (parent, last) = match key.rsplit_once('.') {
+        Some((parent, last)) => (
+            parent
+                .split('.')
+                .try_fold(value, |value, segment| value.get_mut(segment)),
+            last,
+        ),
+        None => (Some(value), key),
+    };
+
+    let Some(Value::Object(map)) = parent else {
+        return false;
+    };
+
+    match new {
+        Some(new) => {
+            map.insert(last.to_owned(), new);
+        }
+        None => {
+            map.remove(last);
+        }
+    }
+
+    true
+}
+
+fn display_value(value: &Value) -> String {
+    match value {
+        Value::String(value) => value.clone(),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn gets_and_sets_dotted_keys() {
+        let mut config = json!({ "forty_five": { "step": "0.005" } });
+
+        assert_eq!(get_path(&config, "forty_five.step"), Some(&json!("0.005")));
+        assert_eq!(get_path(&config, "forty_five.max"), None);
+
+        assert!(set_path(&mut config, "forty_five.max", Some(json!("90"))));
+        assert_eq!(get_path(&config, "forty_five.max"), Some(&json!("90")));
+
+        assert!(set_path(&mut config, "forty_five.max", None));
+        assert_eq!(get_path(&config, "forty_five.max"), None);
+
+        assert!(!set_path(&mut config, "forty_five.step.x", Some(json!(1))));
+        assert!(!set_path(&mut config, "nothing.here", Some(json!(1))));
+    }
+
+    #[test]
+    fn applies_valid_changes() {
+        let mut config = serde_json::to_value(CommandConfig::default()).unwrap();
+        set_path(&mut config, "prefix", Some(json!("?")));
+
+        let applied = apply(config, "prefix", true).ok().unwrap();
+
+        assert_eq!(applied.prefix, Some('?'));
+    }
+
+    #[test]
+    fn refuses_unknown_keys_and_invalid_values() {
+        let config = serde_json::to_value(CommandConfig::default()).unwrap();
+
+        let mut unknown = config.clone();
+        set_path(&mut unknown, "no_such_key", Some(json!(true)));
+
+        assert!(matches!(
+            apply(unknown, "no_such_key", true),
+            Err(ConfigUpdateError::UnknownKey)
+        ));
+
+        let mut wrong_type = config.clone();
+        set_path(&mut wrong_type, "live_only", Some(json!("yes")));
+
+        assert!(matches!(
+            apply(wrong_type, "live_only", true),
+            Err(ConfigUpdateError::Invalid(_))
+        ));
+
+        let mut invalid = config;
+        set_path(&mut invalid, "prefix", Some(json!("a")));
+
+        assert!(matches!(
+            apply(invalid, "prefix", true),
+            Err(ConfigUpdateError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn redacts_webhook_urls() {
+        let mut config = json!({ "webhooks": [{ "url": "https://example.com/secret" }] });
+        redact(&mut config);
+
+        assert_eq!(config["webhooks"][0]["url"], "***");
+    }
+}
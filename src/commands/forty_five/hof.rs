@@ -19,58 +19,63 @@ impl CallableV2<ChannelChatMessageV1Payload> for HallOfFame {
         let database = context.env.d1(FORTYFIVE_DATA)?;
         let db_conn = sqlx_d1::D1Connection::new(database);
 
-        let query = sqlx_d1::query!(
-            "
-            SELECT
-              chatter_user_id
-            FROM Attempts  
-            WHERE 
-              forty_five_value = 45.000
-              AND broadcaster_user_id = ?1
-              AND (?2 = 0 OR epoch = ?3)
-            ORDER BY
-              epoch DESC
-            LIMIT 1
-            ",
-            &context.payload.broadcaster_user_id.as_str(),
-            self.epoch.is_some(),
-            self.epoch
+        let locale = context.config.locale();
+
+        let vars = Vars::new().with(Variable::Game, &context.game.name).with(
+            Variable::Target,
+            context
+                .game
+                .rules
+                .format_value(context.game.rules.target, locale),
+        );
+
+        let epoch = self.epoch.map(i64::try_from).transpose()?;
+
+        let query = crate::db::hall_of_fame(
+            &db_conn,
+            context.payload.broadcaster_user_id.as_str(),
+            &context.game.name,
+            epoch,
+            1,
+            0,
         )
-        .fetch_one(&db_conn)
-        .await;
+        .await
+        .map(|entries| entries.into_iter().next());
 
         match query {
-            Ok(query) => {
+            Ok(Some(query)) => {
                 if let Some(user) = http_client
                     .get_user_from_id(&query.chatter_user_id, &credentials)
                     .await?
                 {
-                    let msg = match self.epoch {
-                        Some(epoch) => {
-                            format!("Perfect 45.000 #{} by: {}", epoch, user.display_name)
-                        }
-                        None => format!("Latest perfect 45.000 by: {}", user.display_name),
+                    let key = match self.epoch {
+                        Some(_) => MessageKey::HallOfFame,
+                        None => MessageKey::LatestHallOfFame,
                     };
 
-                    return Ok(Some(msg));
+                    let vars = vars
+                        .with(Variable::User, user.display_name.as_str())
+                        .with(Variable::Epoch, query.epoch);
+
+                    return Ok(Some(context.config.template(key).render(&vars)));
                 }
 
                 Ok(None)
             }
-            Err(err) => {
-                return match err {
-                    sqlx_d1::Error::RowNotFound => {
-                        let msg = match self.epoch {
-                            Some(value) => {
-                                format!("No perfect 45.000's found with epoch of {}", value)
-                            }
-                            None => "No perfect 45.000's in this channel.".to_owned(),
-                        };
-                        Ok(Some(msg))
-                    }
-                    _ => Ok(None),
+            Ok(None) => {
+                let msg = match self.epoch {
+                    Some(value) => context
+                        .config
+                        .template(MessageKey::NoHallOfFameForEpoch)
+                        .render(&vars.with(Variable::Epoch, value)),
+                    None => context
+                        .config
+                        .template(MessageKey::NoHallOfFame)
+                        .render(&vars),
                 };
+                Ok(Some(msg))
             }
+            Err(_) => Ok(None),
         }
     }
 }
@@ -0,0 +1,184 @@
+use sqlx_d1::D1Connection;
+
+use crate::{
+    commands::{CallableV2, Context},
+    db,
+    prelude::*,
+    webhooks::{self, WebhookPayload},
+};
+
+/// Ends the current epoch without a perfect hit, for when a bug or an exploit spoiled it.
+#[derive(argh::FromArgs, Debug)]
+#[argh(subcommand, name = "reset")]
+pub struct Reset {}
+
+/// Voids an attempt, by its ID or as the latest attempt of a chatter, e.g. `!45 void @user last`.
+/// Voided attempts are kept but don't count for records, the hall of fame or stats.
+#[derive(argh::FromArgs, Debug)]
+#[argh(subcommand, name = "void")]
+pub struct Void {
+    #[argh(positional)]
+    pub target: String,
+    /// Has to be `last` when the target is a chatter.
+    #[argh(positional)]
+    pub last: Option<String>,
+}
+
+impl CallableV2<ChannelChatMessageV1Payload> for Reset {
+    async fn call(
+        self,
+        context: Context<ChannelChatMessageV1Payload>,
+    ) -> BotResult<Option<String>> {
+        if !context.is_mod() {
+            return Ok(None);
+        }
+
+        let db_conn = D1Connection::new(context.env.d1(FORTYFIVE_DATA)?);
+
+        let timestamp: i64 = web_time::SystemTime::now()
+            .duration_since(web_time::SystemTime::UNIX_EPOCH)?
+            .as_millis()
+            .try_into()?;
+
+        let epoch = db::reset_epoch(
+            &db_conn,
+            context.payload.broadcaster_user_id.as_str(),
+            &context.game.name,
+            context.payload.chatter_user_id.as_str(),
+            timestamp,
+        )
+        .await?;
+
+        if !context.config.webhooks.is_empty() {
+            let payloads = [WebhookPayload {
+                event: WebhookEvent::EpochRollover,
+                broadcaster_user_id: context.payload.broadcaster_user_id.to_string(),
+                game: context.game.name.clone(),
+                epoch,
+                user_id: context.payload.chatter_user_id.to_string(),
+                user_name: context.payload.chatter_user_name().to_string(),
+                value: String::new(),
+                difference: String::new(),
+                timestamp,
+            }];
+
+            let env = context.env.clone();
+            let config = context.config.clone();
+
+            context.deferred.push(async move {
+                if let Err(e) = webhooks::dispatch(&env, &config, &payloads).await {
+                    console_error!("Error queueing webhooks: {:?}", e);
+                }
+            });
+        }
+
+        let vars = Vars::new()
+            .with(Variable::Epoch, epoch)
+            .with(Variable::Game, &context.game.name);
+
+        Ok(Some(
+            context
+                .config
+                .template(MessageKey::EpochReset)
+                .render(&vars),
+        ))
+    }
+}
+
+impl CallableV2<ChannelChatMessageV1Payload> for Void {
+    async fn call(
+        self,
+        context: Context<ChannelChatMessageV1Payload>,
+    ) -> BotResult<Option<String>> {
+        if !context.is_mod() {
+            return Ok(None);
+        }
+
+        let http_client = HelixClient::with_client(FetchClient::default());
+        let credentials = get_credentials(&context.env).await?;
+        let db_conn = D1Connection::new(context.env.d1(FORTYFIVE_DATA)?);
+
+        let broadcaster_user_id = context.payload.broadcaster_user_id.as_str();
+        let moderator_user_id = context.payload.chatter_user_id.as_str();
+
+        let voided = match (self.target.parse::<i64>(), self.last.as_deref()) {
+            (Ok(attempt_id), None) => {
+                db::void_attempt(&db_conn, broadcaster_user_id, attempt_id, moderator_user_id)
+                    .await?
+            }
+            (Err(_), Some("last")) => {
+                let chatter = self.target.replace("@", "");
+
+                let Some(user) = http_client
+                    .get_user_from_login(&chatter.to_lowercase(), &credentials)
+                    .await?
+                else {
+                    let vars = Vars::new().with(Variable::User, &chatter);
+                    return Ok(Some(
+                        context
+                            .config
+                            .template(MessageKey::UserNotFound)
+                            .render(&vars),
+                    ));
+                };
+
+                db::void_last_attempt(
+                    &db_conn,
+                    broadcaster_user_id,
+                    &context.game.name,
+                    user.id.as_str(),
+                    moderator_user_id,
+                )
+                .await?
+            }
+            _ => {
+                let vars = Vars::new().with(
+                    Variable::Command,
+                    context.game.command(context.config.prefix()),
+                );
+
+                return Ok(Some(
+                    context.config.template(MessageKey::VoidUsage).render(&vars),
+                ));
+            }
+        };
+
+        let Some(voided) = voided else {
+            return Ok(Some(
+                context
+                    .config
+                    .template(MessageKey::NothingToVoid)
+                    .render(&Vars::new()),
+            ));
+        };
+
+        let user_name = http_client
+            .get_user_from_id(&voided.chatter_user_id, &credentials)
+            .await?
+            .map(|user| user.display_name.take())
+            .unwrap_or(voided.chatter_user_id);
+
+        // Attempts of games that were removed since are shown with the
+        // precision of !45.
+        let rules = context
+            .config
+            .game(&voided.game)
+            .map(|game| game.rules)
+            .unwrap_or_default();
+
+        let vars = Vars::new()
+            .with(Variable::User, user_name)
+            .with(
+                Variable::Value,
+                rules.format_value(voided.forty_five_value, context.config.locale()),
+            )
+            .with(Variable::Game, &voided.game);
+
+        Ok(Some(
+            context
+                .config
+                .template(MessageKey::AttemptVoided)
+                .render(&vars),
+        ))
+    }
+}
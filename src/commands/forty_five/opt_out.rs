@@ -0,0 +1,64 @@
+use sqlx_d1::D1Connection;
+
+use crate::{
+    commands::{CallableV2, Context},
+    db,
+    prelude::*,
+};
+
+/// Stops recording the chatter in every channel. Their earlier attempts are kept, the admin
+/// endpoint `DELETE /admin/users/:user_id` removes those.
+#[derive(argh::FromArgs, Debug)]
+#[argh(subcommand, name = "optout")]
+pub struct OptOut {}
+
+#[derive(argh::FromArgs, Debug)]
+#[argh(subcommand, name = "optin")]
+pub struct OptIn {}
+
+impl CallableV2<ChannelChatMessageV1Payload> for OptOut {
+    async fn call(
+        self,
+        context: Context<ChannelChatMessageV1Payload>,
+    ) -> BotResult<Option<String>> {
+        let db_conn = D1Connection::new(context.env.d1(FORTYFIVE_DATA)?);
+
+        let timestamp: i64 = web_time::SystemTime::now()
+            .duration_since(web_time::SystemTime::UNIX_EPOCH)?
+            .as_millis()
+            .try_into()?;
+
+        db::opt_out(
+            &db_conn,
+            context.payload.chatter_user_id.as_str(),
+            timestamp,
+        )
+        .await?;
+
+        Ok(Some(respond(&context, MessageKey::OptedOut)))
+    }
+}
+
+impl CallableV2<ChannelChatMessageV1Payload> for OptIn {
+    async fn call(
+        self,
+        context: Context<ChannelChatMessageV1Payload>,
+    ) -> BotResult<Option<String>> {
+        let db_conn = D1Connection::new(context.env.d1(FORTYFIVE_DATA)?);
+
+        db::opt_in(&db_conn, context.payload.chatter_user_id.as_str()).await?;
+
+        Ok(Some(respond(&context, MessageKey::OptedIn)))
+    }
+}
+
+fn respond(context: &Context<ChannelChatMessageV1Payload>, key: MessageKey) -> String {
+    let vars = Vars::new()
+        .with(Variable::User, &context.payload.chatter_user_name)
+        .with(
+            Variable::Command,
+            context.game.command(context.config.prefix()),
+        );
+
+    context.config.template(key).render(&vars)
+}
@@ -0,0 +1,203 @@
+use sqlx_d1::D1Connection;
+
+use crate::{
+    commands::{CallableV2, Context},
+    db,
+    prelude::*,
+    time::{DAY_MILLIS, iso_date, parse_date},
+};
+
+/// `!45 season` shows the current season, the subcommands manage seasons.
+#[derive(argh::FromArgs, Debug)]
+#[argh(subcommand, name = "season")]
+pub struct Season {
+    #[argh(subcommand)]
+    pub sub: Option<SeasonSubcommands>,
+}
+
+#[derive(argh::FromArgs, Debug)]
+#[argh(subcommand)]
+pub enum SeasonSubcommands {
+    Add(Add),
+    Delete(Delete),
+}
+
+/// `!45 season add 2025-06-01 2025-08-31 Summer 2025`, both days are part of the season and are
+/// in UTC.
+#[derive(argh::FromArgs, Debug)]
+#[argh(subcommand, name = "add")]
+pub struct Add {
+    #[argh(positional)]
+    pub first_day: String,
+    #[argh(positional)]
+    pub last_day: String,
+    #[argh(positional, greedy)]
+    pub name: Vec<String>,
+}
+
+#[derive(argh::FromArgs, Debug)]
+#[argh(subcommand, name = "delete")]
+pub struct Delete {
+    #[argh(positional, greedy)]
+    pub name: Vec<String>,
+}
+
+impl CallableV2<ChannelChatMessageV1Payload> for Season {
+    async fn call(
+        self,
+        context: Context<ChannelChatMessageV1Payload>,
+    ) -> BotResult<Option<String>> {
+        let db_conn = D1Connection::new(context.env.d1(FORTYFIVE_DATA)?);
+
+        let Some(sub) = self.sub else {
+            return summary(&context, &db_conn).await;
+        };
+
+        if !context.can_configure() {
+            // Seasons are managed by whoever can change the configuration.
+            return Ok(None);
+        }
+
+        let broadcaster_user_id = context.payload.broadcaster_user_id.as_str();
+
+        match sub {
+            SeasonSubcommands::Add(add) => {
+                let name = add.name.join(" ");
+
+                let span = parse_date(&add.first_day)
+                    .zip(parse_date(&add.last_day))
+                    .map(|(first, last)| (first * DAY_MILLIS, (last + 1) * DAY_MILLIS))
+                    .filter(|(starts_at, ends_at)| starts_at < ends_at && !name.is_empty());
+
+                let Some((starts_at, ends_at)) = span else {
+                    let vars = Vars::new().with(
+                        Variable::Command,
+                        context.game.command(context.config.prefix()),
+                    );
+
+                    return Ok(Some(
+                        context
+                            .config
+                            .template(MessageKey::SeasonUsage)
+                            .render(&vars),
+                    ));
+                };
+
+                if let Some(season) =
+                    db::overlapping_season(&db_conn, broadcaster_user_id, starts_at, ends_at)
+                        .await?
+                {
+                    let vars = Vars::new().with(Variable::Period, &season.name);
+
+                    return Ok(Some(
+                        context
+                            .config
+                            .template(MessageKey::SeasonExists)
+                            .render(&vars),
+                    ));
+                }
+
+                let added =
+                    db::add_season(&db_conn, broadcaster_user_id, &name, starts_at, ends_at)
+                        .await?;
+
+                let key = if added {
+                    MessageKey::SeasonAdded
+                } else {
+                    MessageKey::SeasonExists
+                };
+
+                let vars = Vars::new().with(Variable::Period, &name);
+
+                Ok(Some(context.config.template(key).render(&vars)))
+            }
+            SeasonSubcommands::Delete(delete) => {
+                let name = delete.name.join(" ");
+
+                let key = if db::delete_season(&db_conn, broadcaster_user_id, &name).await? {
+                    MessageKey::SeasonDeleted
+                } else {
+                    MessageKey::SeasonNotFound
+                };
+
+                let vars = Vars::new().with(Variable::Period, &name);
+
+                Ok(Some(context.config.template(key).render(&vars)))
+            }
+        }
+    }
+}
+
+/// The current season and who leads the game in it.
+async fn summary(
+    context: &Context<ChannelChatMessageV1Payload>,
+    db_conn: &D1Connection,
+) -> BotResult<Option<String>> {
+    let broadcaster_user_id = context.payload.broadcaster_user_id.as_str();
+
+    let now: i64 = web_time::SystemTime::now()
+        .duration_since(web_time::SystemTime::UNIX_EPOCH)?
+        .as_millis()
+        .try_into()?;
+
+    let Some(season) = db::current_season(db_conn, broadcaster_user_id, now).await? else {
+        return Ok(Some(
+            context
+                .config
+                .template(MessageKey::NoSeason)
+                .render(&Vars::new()),
+        ));
+    };
+
+    let game = &context.game;
+
+    let vars = Vars::new()
+        .with(Variable::Period, &season.name)
+        .with(Variable::Date, iso_date(season.ends_at - 1))
+        .with(Variable::Game, &game.name);
+
+    let leader = db::optional(
+        db::period_record(
+            db_conn,
+            broadcaster_user_id,
+            &game.name,
+            season.starts_at,
+            season.ends_at,
+            None,
+            false,
+        )
+        .await,
+    )?;
+
+    let Some(leader) = leader else {
+        return Ok(Some(
+            context
+                .config
+                .template(MessageKey::SeasonSummaryEmpty)
+                .render(&vars),
+        ));
+    };
+
+    let http_client = HelixClient::with_client(FetchClient::default());
+    let credentials = get_credentials(&context.env).await?;
+
+    let Some(user) = http_client
+        .get_user_from_id(&leader.chatter_user_id, &credentials)
+        .await?
+    else {
+        return Ok(None);
+    };
+
+    let vars = vars.with(Variable::User, user.display_name.as_str()).with(
+        Variable::Value,
+        game.rules
+            .format_value(leader.forty_five_value, context.config.locale()),
+    );
+
+    Ok(Some(
+        context
+            .config
+            .template(MessageKey::SeasonSummary)
+            .render(&vars),
+    ))
+}
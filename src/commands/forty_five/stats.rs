@@ -0,0 +1,70 @@
+use sqlx_d1::D1Connection;
+
+use crate::{
+    commands::{CallableV2, Context, Invocation},
+    db,
+    prelude::*,
+};
+
+/// How often a chatter played the game and hit the target, e.g. `!45 stats @user`.
+#[derive(argh::FromArgs, Debug)]
+#[argh(subcommand, name = "stats")]
+pub struct Stats {
+    #[argh(positional)]
+    pub chatter: Option<String>,
+}
+
+impl<P: Invocation> CallableV2<P> for Stats {
+    async fn call(self, context: Context<P>) -> BotResult<Option<String>> {
+        let db_conn = D1Connection::new(context.env.d1(FORTYFIVE_DATA)?);
+
+        let (user_id, user_name) = if let Some(login) = &self.chatter {
+            let token = get_credentials(&context.env).await?;
+            let http_client = HelixClient::with_client(FetchClient::default());
+
+            let user = http_client
+                .get_user_from_login(&login.replace("@", "").to_lowercase(), &token)
+                .await?;
+
+            match user {
+                Some(user) => (
+                    user.id.as_str().to_owned(),
+                    user.display_name.as_str().to_owned(),
+                ),
+                None => {
+                    let vars = Vars::new().with(Variable::User, login);
+                    return Ok(Some(
+                        context
+                            .config
+                            .template(MessageKey::UserNotFound)
+                            .render(&vars),
+                    ));
+                }
+            }
+        } else {
+            (
+                context.payload.chatter_user_id().as_str().to_owned(),
+                context.payload.chatter_user_name().as_str().to_owned(),
+            )
+        };
+
+        let stats = db::stats(
+            &db_conn,
+            context.payload.broadcaster_user_id().as_str(),
+            &context.game.name,
+            None,
+            Some(&user_id),
+        )
+        .await?;
+
+        let vars = Vars::new()
+            .with(Variable::User, &user_name)
+            .with(Variable::Game, &context.game.name)
+            .with(Variable::Attempts, stats.attempts)
+            .with(Variable::Perfects, stats.perfects);
+
+        Ok(Some(
+            context.config.template(MessageKey::Stats).render(&vars),
+        ))
+    }
+}
@@ -58,13 +58,7 @@ async fn timeout_impl(
     let credentials = get_credentials(&context.env).await?;
     let timeouts = context.env.kv(TIMEOUTS_KV)?;
 
-    let is_mod = context
-        .payload
-        .badges
-        .iter()
-        .any(|x| x.set_id.as_str() == "moderator" || x.set_id.as_str() == "broadcaster");
-
-    if !is_mod {
+    if !context.is_mod() {
         // The user is not a moderator and such the messages should be ignored.
         return Ok(None);
     }
@@ -75,8 +69,15 @@ async fn timeout_impl(
         .get_user_from_login(&chatter.to_lowercase(), &credentials)
         .await?;
 
+    let vars = Vars::new().with(Variable::User, &chatter);
+
     let Some(user) = user else {
-        return Ok(Some(format!("User {} not found.", &chatter)));
+        return Ok(Some(
+            context
+                .config
+                .template(MessageKey::UserNotFound)
+                .render(&vars),
+        ));
     };
 
     match action {
@@ -97,24 +98,26 @@ async fn timeout_impl(
                 .execute()
                 .await?;
 
-            Ok(Some(format!(
-                "Timed out {} from !45's for {} seconds.",
-                chatter, secs
-            )))
+            Ok(Some(
+                context
+                    .config
+                    .template(MessageKey::TimedOut)
+                    .render(&vars.with(Variable::Secs, secs)),
+            ))
         }
         TimeoutAction::Untimeout => {
             let timeout_key = timeout_key(&context.payload.broadcaster_user_id, Some(&user.id));
 
             let timed_out_user: Option<TimeoutData> = timeouts.get(&timeout_key).json().await?;
 
-            let msg = if timed_out_user.is_some() {
+            let key = if timed_out_user.is_some() {
                 timeouts.delete(&timeout_key).await?;
-                format!("Removed !45 timeout for {}.", chatter)
+                MessageKey::Untimedout
             } else {
-                format!("{} is not currently timed out.", chatter)
+                MessageKey::NotTimedOut
             };
 
-            Ok(Some(msg))
+            Ok(Some(context.config.template(key).render(&vars)))
         }
     }
 }
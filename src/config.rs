@@ -1,4 +1,21 @@
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::time::Duration;
+
+use chrono_tz::Tz;
+use rust_decimal::prelude::*;
 use serde::{Deserialize, Serialize};
+use twitch_api::types::UserId;
+use web_time::Instant;
+use worker::{Env, Url, console_error};
+
+use crate::error::{BotResult, ConfigError};
+use crate::messages::{Locale, MessageKey};
+use crate::template::Template;
+
+mod migrations;
+
+pub use migrations::migrate;
 
 /// User ID of the bot for it to be able to ignore it's own messages.
 pub const BOT_USER_ID: &str = "1179987305";
@@ -18,16 +35,991 @@ pub const CONFIG_KV: &str = "BroadcasterConfiguration";
 /// A D1 database for storing !45 data.
 pub const FORTYFIVE_DATA: &str = "DB";
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+/// The name of the built-in game, configured by [`CommandConfig::forty_five`].
+pub const DEFAULT_GAME: &str = "45";
+
+/// The character commands start with unless a channel configures its own.
+pub const DEFAULT_PREFIX: char = '!';
+
+/// The `!45` subcommands whose responses can be whispered, see [`CommandConfig::whispered`]. Only
+/// commands that look things up are, so that whispering never hides what happened to chat.
+pub const WHISPERABLE_COMMANDS: &[&str] =
+    &["best", "worst", "pb", "pw", "hof", "achievements", "stats"];
+
+/// The version of [`CommandConfig`]'s shape. Bump this and add a migration to
+/// `config/migrations.rs` whenever a change would make older configuration read differently.
+pub const CONFIG_VERSION: u64 = 1;
+
+/// How long configuration read from KV is reused for within an isolate. Writes made through the
+/// bot replace the cached value right away, so this only delays changes made elsewhere.
+const CONFIG_CACHE_TTL: Duration = Duration::from_secs(30);
+
+/// How long the prefix of a channel is remembered for within an isolate, see [`known_prefix`].
+/// Longer than [`CONFIG_CACHE_TTL`] since prefixes rarely change, which only delays a new prefix
+/// changed elsewhere by as much.
+const PREFIX_CACHE_TTL: Duration = Duration::from_secs(10 * 60);
+
+thread_local! {
+    static CONFIG_CACHE: RefCell<HashMap<String, (Instant, CommandConfig)>> =
+        RefCell::new(HashMap::new());
+    static PREFIX_CACHE: RefCell<HashMap<String, (Instant, char)>> =
+        RefCell::new(HashMap::new());
+}
+
+/// The prefix of a channel if its configuration was loaded recently, so that chat messages
+/// starting with other punctuation can be skipped without reading it again.
+pub fn known_prefix(broadcaster_user_id: &UserId) -> Option<char> {
+    PREFIX_CACHE.with_borrow_mut(|cache| match cache.get(broadcaster_user_id.as_str()) {
+        Some((cached_at, prefix)) if cached_at.elapsed() < PREFIX_CACHE_TTL => Some(*prefix),
+        Some(_) => {
+            cache.remove(broadcaster_user_id.as_str());
+            None
+        }
+        None => None,
+    })
+}
+
+fn cached_config(broadcaster_user_id: &UserId) -> Option<CommandConfig> {
+    CONFIG_CACHE.with_borrow_mut(|cache| match cache.get(broadcaster_user_id.as_str()) {
+        Some((cached_at, config)) if cached_at.elapsed() < CONFIG_CACHE_TTL => Some(config.clone()),
+        Some(_) => {
+            cache.remove(broadcaster_user_id.as_str());
+            None
+        }
+        None => None,
+    })
+}
+
+fn cache_config(broadcaster_user_id: &UserId, config: &CommandConfig) {
+    CONFIG_CACHE.with_borrow_mut(|cache| {
+        cache.insert(
+            broadcaster_user_id.as_str().to_owned(),
+            (Instant::now(), config.clone()),
+        );
+    });
+
+    PREFIX_CACHE.with_borrow_mut(|cache| {
+        cache.insert(
+            broadcaster_user_id.as_str().to_owned(),
+            (Instant::now(), config.prefix()),
+        );
+    });
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct CommandConfig {
+    /// The version of the shape this configuration was written in, see [`CONFIG_VERSION`].
+    pub version: u64,
     pub forty_five: FortyFiveConfig,
+    /// Additional games the channel has besides !45.
+    pub games: Vec<GameConfig>,
+    /// The character commands start with, [`DEFAULT_PREFIX`] if not set. Only ASCII punctuation
+    /// is allowed so that regular chat messages can be skipped without reading the configuration.
+    pub prefix: Option<char>,
+    /// Shortcuts for commands, keyed by the alias without the prefix. The value is the command it
+    /// stands for, also without the prefix, e.g. `45b` to `45 best` or `strafe` to `45`.
+    pub aliases: BTreeMap<String, String>,
+    /// Overrides for the bot's responses, see [`MessageKey`] for the variables each of them can
+    /// use.
+    pub messages: BTreeMap<MessageKey, String>,
+    /// Language tag of the bundled messages to use, e.g. `de`. English is used when not set, and
+    /// for messages a language doesn't have.
+    pub locale: Option<String>,
+    /// Name of the timezone days, weeks and months start in for `!45 best today` and friends, e.g.
+    /// `Europe/Berlin`. UTC is used when not set.
+    pub timezone: Option<String>,
+    /// Whether moderators can use `!45 config`, the broadcaster always can.
+    pub mods_can_configure: bool,
+    /// Whether games can only be played while the channel is live. Needs the stream.online and
+    /// stream.offline subscriptions from `POST /admin/subscriptions/:broadcaster_user_id`.
+    pub live_only: bool,
+    /// Channel point rewards that play a game, keyed by the reward ID. The value is the name of the
+    /// game, e.g. `45`. See [`crate::redemptions`].
+    pub rewards: BTreeMap<String, String>,
+    /// What to do with commands sent in other channels during a Shared Chat session.
+    pub shared_chat: SharedChatPolicy,
+    /// Subcommands whose responses are whispered to the chatter instead of sent to chat, e.g.
+    /// `pb`. Needs the bot's token from `/dashboard/login?whispers`, see [`crate::whispers`].
+    pub whispered: BTreeSet<String>,
+    /// Where to send perfect hits and new records, see [`crate::webhooks`].
+    pub webhooks: Vec<WebhookConfig>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+impl Default for CommandConfig {
+    fn default() -> Self {
+        Self {
+            version: CONFIG_VERSION,
+            forty_five: FortyFiveConfig::default(),
+            games: Vec::new(),
+            prefix: None,
+            aliases: BTreeMap::new(),
+            messages: BTreeMap::new(),
+            locale: None,
+            timezone: None,
+            mods_can_configure: false,
+            live_only: false,
+            rewards: BTreeMap::new(),
+            shared_chat: SharedChatPolicy::default(),
+            whispered: BTreeSet::new(),
+            webhooks: Vec::new(),
+        }
+    }
+}
+
+impl CommandConfig {
+    /// Reads the configuration of a broadcaster from [`CONFIG_KV`]. Missing or invalid values fall
+    /// back to the defaults so that a bad configuration never stops the bot from responding, the
+    /// reason is logged and can be looked up through the admin API.
+    pub async fn load(env: &Env, broadcaster_user_id: &UserId) -> CommandConfig {
+        if let Some(config) = cached_config(broadcaster_user_id) {
+            return config;
+        }
+
+        let mut config = match Self::read(env, broadcaster_user_id).await {
+            Ok(config) => config.unwrap_or_default(),
+            Err(e) => {
+                console_error!(
+                    "Failed reading configuration for {}, using defaults: {}",
+                    broadcaster_user_id,
+                    e
+                );
+
+                CommandConfig::default()
+            }
+        };
+
+        if let Err(e) = config.forty_five.validate() {
+            console_error!(
+                "Invalid !45 configuration for {}, using defaults: {}",
+                broadcaster_user_id,
+                e
+            );
+
+            config.forty_five = FortyFiveConfig {
+                perfect_45_message: config.forty_five.perfect_45_message.take(),
+                ..Default::default()
+            };
+        }
+
+        config.games.retain(|game| match game.validate() {
+            Ok(()) => true,
+            Err(e) => {
+                console_error!(
+                    "Invalid game configuration for {}, ignoring it: {}",
+                    broadcaster_user_id,
+                    e
+                );
+                false
+            }
+        });
+
+        config.webhooks.retain(|webhook| match webhook.validate() {
+            Ok(()) => true,
+            Err(e) => {
+                console_error!(
+                    "Invalid webhook for {}, ignoring it: {}",
+                    broadcaster_user_id,
+                    e
+                );
+                false
+            }
+        });
+
+        if let Some(prefix) = config.prefix
+            && !prefix.is_ascii_punctuation()
+        {
+            console_error!(
+                "Invalid prefix {:?} for {}, using the default",
+                prefix,
+                broadcaster_user_id
+            );
+
+            config.prefix = None;
+        }
+
+        let aliases = std::mem::take(&mut config.aliases);
+        config.aliases = aliases
+            .into_iter()
+            .filter(
+                |(alias, command)| match config.validate_alias(alias, command) {
+                    Ok(()) => true,
+                    Err(e) => {
+                        console_error!(
+                            "Invalid alias for {}, ignoring it: {}",
+                            broadcaster_user_id,
+                            e
+                        );
+                        false
+                    }
+                },
+            )
+            .collect();
+
+        if let Some(locale) = &config.locale
+            && Locale::from_tag(locale).is_none()
+        {
+            console_error!(
+                "Unknown locale {:?} for {}, using the default",
+                locale,
+                broadcaster_user_id
+            );
+
+            config.locale = None;
+        }
+
+        if let Some(timezone) = &config.timezone
+            && Tz::from_str(timezone).is_err()
+        {
+            console_error!(
+                "Unknown timezone {:?} for {}, using UTC",
+                timezone,
+                broadcaster_user_id
+            );
+
+            config.timezone = None;
+        }
+
+        let rewards = std::mem::take(&mut config.rewards);
+        config.rewards = rewards
+            .into_iter()
+            .filter(|(reward, game)| {
+                if config.game(game).is_some() {
+                    return true;
+                }
+
+                console_error!(
+                    "Reward {} of {} plays unknown game {:?}, ignoring it",
+                    reward,
+                    broadcaster_user_id,
+                    game
+                );
+                false
+            })
+            .collect();
+
+        config.whispered.retain(|command| {
+            if WHISPERABLE_COMMANDS.contains(&command.as_str()) {
+                return true;
+            }
+
+            console_error!(
+                "{:?} of {} can't be whispered, ignoring it",
+                command,
+                broadcaster_user_id
+            );
+            false
+        });
+
+        config.messages.retain(
+            |key, message| match Template::parse(message, key.variables()) {
+                Ok(_) => true,
+                Err(e) => {
+                    console_error!(
+                        "Invalid {:?} message for {}, ignoring it: {}",
+                        key,
+                        broadcaster_user_id,
+                        e
+                    );
+                    false
+                }
+            },
+        );
+
+        cache_config(broadcaster_user_id, &config);
+
+        config
+    }
+
+    /// Reads the stored configuration of a broadcaster, migrating it from older versions. Unlike
+    /// [`CommandConfig::load`] this doesn't hide errors or fix invalid values.
+    pub async fn read(env: &Env, broadcaster_user_id: &UserId) -> BotResult<Option<CommandConfig>> {
+        let raw = Self::read_raw(env, broadcaster_user_id).await?;
+
+        let Some(raw) = raw else {
+            return Ok(None);
+        };
+
+        let config = serde_json::from_value(migrations::migrate(raw)?)?;
+
+        Ok(Some(config))
+    }
+
+    /// Reads the stored configuration of a broadcaster as-is.
+    pub async fn read_raw(
+        env: &Env,
+        broadcaster_user_id: &UserId,
+    ) -> BotResult<Option<serde_json::Value>> {
+        let raw = env
+            .kv(CONFIG_KV)?
+            .get(broadcaster_user_id.as_str())
+            .text()
+            .await?;
+
+        match raw {
+            Some(raw) => Ok(Some(serde_json::from_str(&raw)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// The template for a response, either the channel's own or the default one.
+    pub fn template(&self, key: MessageKey) -> Template {
+        self.messages
+            .get(&key)
+            .and_then(|message| Template::parse(message, key.variables()).ok())
+            .unwrap_or_else(|| key.default_template(self.locale()))
+    }
+
+    pub fn locale(&self) -> Locale {
+        self.locale
+            .as_deref()
+            .and_then(Locale::from_tag)
+            .unwrap_or_default()
+    }
+
+    pub fn timezone(&self) -> Tz {
+        self.timezone
+            .as_deref()
+            .and_then(|timezone| Tz::from_str(timezone).ok())
+            .unwrap_or(Tz::UTC)
+    }
+
+    pub fn prefix(&self) -> char {
+        self.prefix.unwrap_or(DEFAULT_PREFIX)
+    }
+
+    /// Strips the prefix off a chat message and expands aliases, returning the command with the
+    /// game name first, e.g. `["45", "best"]`. Returns `None` if the message isn't a command.
+    pub fn resolve_command<'a>(&'a self, args: &[&'a str]) -> Option<Vec<&'a str>> {
+        let (first, rest) = args.split_first()?;
+        let command = first.strip_prefix(self.prefix())?;
+
+        let resolved = match self.aliases.get(command) {
+            Some(alias) => alias
+                .split_whitespace()
+                .chain(rest.iter().copied())
+                .collect(),
+            None => std::iter::once(command)
+                .chain(rest.iter().copied())
+                .collect(),
+        };
+
+        Some(resolved)
+    }
+
+    /// Looks up a game by its name, [`DEFAULT_GAME`] being !45 itself.
+    pub fn game(&self, name: &str) -> Option<GameConfig> {
+        if name == DEFAULT_GAME {
+            return Some(GameConfig {
+                name: DEFAULT_GAME.to_owned(),
+                rules: self.forty_five.clone(),
+            });
+        }
+
+        self.games.iter().find(|game| game.name == name).cloned()
+    }
+
+    /// Every game of the channel, starting with the built-in one.
+    pub fn all_games(&self) -> Vec<GameConfig> {
+        let mut games = Vec::with_capacity(self.games.len() + 1);
+
+        games.extend(self.game(DEFAULT_GAME));
+        games.extend(self.games.iter().cloned());
+
+        games
+    }
+
+    /// Validates and writes the configuration of a broadcaster to [`CONFIG_KV`], and replaces the
+    /// cached configuration of this isolate.
+    pub async fn save(&self, env: &Env, broadcaster_user_id: &UserId) -> BotResult<()> {
+        self.validate()?;
+
+        env.kv(CONFIG_KV)?
+            .put(broadcaster_user_id.as_str(), serde_json::to_string(self)?)?
+            .execute()
+            .await?;
+
+        cache_config(broadcaster_user_id, self);
+
+        Ok(())
+    }
+
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.version != CONFIG_VERSION {
+            return Err(ConfigError::UnsupportedVersion(self.version));
+        }
+
+        self.forty_five.validate()?;
+
+        for (i, game) in self.games.iter().enumerate() {
+            game.validate()?;
+
+            if self.games[..i].iter().any(|other| other.name == game.name) {
+                return Err(ConfigError::DuplicateGame(game.name.clone()));
+            }
+        }
+
+        if let Some(prefix) = self.prefix
+            && !prefix.is_ascii_punctuation()
+        {
+            return Err(ConfigError::InvalidPrefix(prefix));
+        }
+
+        for (alias, command) in &self.aliases {
+            self.validate_alias(alias, command)?;
+        }
+
+        if let Some(locale) = &self.locale
+            && Locale::from_tag(locale).is_none()
+        {
+            return Err(ConfigError::UnknownLocale(locale.clone()));
+        }
+
+        if let Some(timezone) = &self.timezone
+            && Tz::from_str(timezone).is_err()
+        {
+            return Err(ConfigError::UnknownTimezone(timezone.clone()));
+        }
+
+        for game in self.rewards.values() {
+            if self.game(game).is_none() {
+                return Err(ConfigError::RewardWithoutGame(game.clone()));
+            }
+        }
+
+        for command in &self.whispered {
+            if !WHISPERABLE_COMMANDS.contains(&command.as_str()) {
+                return Err(ConfigError::NotWhisperable(command.clone()));
+            }
+        }
+
+        for (key, message) in &self.messages {
+            Template::parse(message, key.variables()).map_err(|e| ConfigError::Message(*key, e))?;
+        }
+
+        for webhook in &self.webhooks {
+            webhook.validate()?;
+        }
+
+        Ok(())
+    }
+
+    /// Aliases can't shadow games, and must point to one so that they don't expand into other
+    /// aliases.
+    fn validate_alias(&self, alias: &str, command: &str) -> Result<(), ConfigError> {
+        if alias.is_empty() || alias.chars().any(char::is_whitespace) || self.game(alias).is_some()
+        {
+            return Err(ConfigError::InvalidAlias(alias.to_owned()));
+        }
+
+        match command.split_whitespace().next() {
+            Some(game) if self.game(game).is_some() => Ok(()),
+            _ => Err(ConfigError::AliasWithoutGame(alias.to_owned())),
+        }
+    }
+}
+
+/// A game played the same way as !45, but with its own command, rules and leaderboards.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameConfig {
+    /// The name of the game, which is also the command without the prefix, e.g. `22.5` for `!22.5`.
+    /// Attempts are stored under this name so renaming a game starts it from scratch.
+    pub name: String,
+    #[serde(flatten)]
+    pub rules: FortyFiveConfig,
+}
+
+impl GameConfig {
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.name.is_empty()
+            || self.name == DEFAULT_GAME
+            || self.name.starts_with(|c: char| c.is_ascii_punctuation())
+            || self.name.chars().any(char::is_whitespace)
+        {
+            return Err(ConfigError::InvalidGameName(self.name.clone()));
+        }
+
+        self.rules
+            .validate()
+            .map_err(|e| ConfigError::Game(self.name.clone(), Box::new(e)))
+    }
+
+    /// The chat command that starts the game.
+    pub fn command(&self, prefix: char) -> String {
+        format!("{}{}", prefix, self.name)
+    }
+}
+
+/// The most bonus rolls a single cheer or subscription can grant, each of them is a chat message.
+pub const MAX_BONUS_ROLLS: u32 = 5;
+
+/// Rolls granted to supporters on top of the ones they play in chat. Bonus rolls are recorded with
+/// their source so stats can tell them apart, see [`crate::db::AttemptSource`].
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BonusConfig {
+    /// How many bits a cheer needs for [`BonusConfig::cheer_rolls`], cheers don't grant rolls if
+    /// not set.
+    pub cheer_bits: Option<u32>,
+    pub cheer_rolls: u32,
+    /// Rolls for subscribing or resubscribing. Gifted subscriptions don't grant rolls.
+    pub sub_rolls: u32,
+    /// Whether bonus rolls are played even while the supporter is timed out from the game.
+    pub bypass_timeouts: bool,
+}
+
+impl BonusConfig {
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.cheer_bits == Some(0) {
+            return Err(ConfigError::NoCheerBits);
+        }
+
+        if self.cheer_rolls > MAX_BONUS_ROLLS || self.sub_rolls > MAX_BONUS_ROLLS {
+            return Err(ConfigError::TooManyBonusRolls);
+        }
+
+        Ok(())
+    }
+
+    /// Whether the channel has to be subscribed to cheers and subscriptions.
+    pub fn is_enabled(&self) -> bool {
+        (self.cheer_bits.is_some() && self.cheer_rolls > 0) || self.sub_rolls > 0
+    }
+
+    /// The rolls a cheer of `bits` grants.
+    pub fn rolls_for_cheer(&self, bits: i64) -> u32 {
+        match self.cheer_bits {
+            Some(threshold) if bits >= i64::from(threshold) => self.cheer_rolls,
+            _ => 0,
+        }
+    }
+}
+
+/// A URL to send events of the channel's games to.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookConfig {
+    pub url: String,
+    #[serde(default)]
+    pub format: WebhookFormat,
+    /// The events to send, all of them if empty.
+    #[serde(default)]
+    pub events: Vec<WebhookEvent>,
+}
+
+impl WebhookConfig {
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        match Url::parse(&self.url) {
+            Ok(url) if url.scheme() == "https" => Ok(()),
+            _ => Err(ConfigError::InvalidWebhookUrl(self.url.clone())),
+        }
+    }
+
+    pub fn wants(&self, event: WebhookEvent) -> bool {
+        self.events.is_empty() || self.events.contains(&event)
+    }
+}
+
+/// How commands sent in another channel of a Shared Chat session are handled. Commands sent in
+/// this channel are always handled here, and each command is only answered once even when the bot
+/// is in several channels of the session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SharedChatPolicy {
+    /// Only the channel the command was sent in handles it.
+    #[default]
+    Ignore,
+    /// Played with the settings and on the leaderboards of the channel the command was sent in,
+    /// and answered in that channel's chat, as if the bot was in that channel.
+    Origin,
+    /// Played with the settings and on the leaderboards of the channel the command was sent in,
+    /// and answered in this channel's chat.
+    Source,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookFormat {
+    /// The event as JSON, signed so that it can be verified.
+    #[default]
+    Json,
+    /// A message for a Discord webhook, from the `webhook_*` messages.
+    Discord,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookEvent {
+    Perfect,
+    NewBest,
+    NewWorst,
+    /// A new epoch started after a perfect hit.
+    EpochRollover,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 #[non_exhaustive]
 pub struct FortyFiveConfig {
     /// The message that's emitted when a chatter gets 45.000. Such messages would be like this:
-    /// `{{ chatter_user_name }} has achieved perfect 45!`, `{{ chatter_user_name }}` would be
-    /// dynamically replaced by the actual chatter emitted by Twitch when sending the event.
+    /// `{{ user }} has achieved perfect 45!`, `{{ user }}` would be dynamically replaced by the
+    /// actual chatter emitted by Twitch when sending the event. Takes precedence over the
+    /// channel's [`MessageKey::Perfect`] message, and can use the same variables.
     pub perfect_45_message: Option<String>,
+    /// The lowest value that can be generated.
+    pub min: Decimal,
+    /// The highest value that can be generated.
+    pub max: Decimal,
+    /// The increments between generated values, this also decides how many decimal places are
+    /// shown when displaying a value.
+    pub step: Decimal,
+    /// The value chatters are aiming for. Hitting it exactly is a perfect 45 and starts a new
+    /// epoch.
+    pub target: Decimal,
+    /// Extra rolls for cheering and subscribing, see [`crate::bonus`].
+    pub bonus: BonusConfig,
+}
+
+impl Default for FortyFiveConfig {
+    fn default() -> Self {
+        Self {
+            perfect_45_message: None,
+            min: dec!(0),
+            max: dec!(90),
+            step: dec!(0.005),
+            target: dec!(45),
+            bonus: BonusConfig::default(),
+        }
+    }
+}
+
+impl FortyFiveConfig {
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.step <= Decimal::ZERO {
+            return Err(ConfigError::NonPositiveStep);
+        }
+
+        if self.min >= self.max {
+            return Err(ConfigError::EmptyRange);
+        }
+
+        if !((self.max - self.min) % self.step).is_zero() {
+            return Err(ConfigError::RangeNotMultipleOfStep);
+        }
+
+        if !self.is_on_grid(self.target) {
+            return Err(ConfigError::TargetOffGrid);
+        }
+
+        if let Some(message) = &self.perfect_45_message {
+            Template::parse(message, MessageKey::Perfect.variables())
+                .map_err(|e| ConfigError::Message(MessageKey::Perfect, e))?;
+        }
+
+        self.bonus.validate()?;
+
+        Ok(())
+    }
+
+    /// Whether the game can generate a value, i.e. it's between the minimum and maximum and a
+    /// whole number of steps from the minimum.
+    pub fn is_on_grid(&self, value: Decimal) -> bool {
+        value >= self.min && value <= self.max && ((value - self.min) % self.step).is_zero()
+    }
+
+    /// The amount of steps between the minimum and maximum value.
+    pub fn steps(&self) -> Decimal {
+        ((self.max - self.min) / self.step).round()
+    }
+
+    /// The value that's `steps` steps away from the minimum.
+    pub fn value_at(&self, steps: Decimal) -> Decimal {
+        self.min + steps * self.step
+    }
+
+    /// The template for perfect hits of this game, falling back to the channel's.
+    pub fn perfect_template(&self, config: &CommandConfig) -> Template {
+        self.perfect_45_message
+            .as_deref()
+            .and_then(|message| Template::parse(message, MessageKey::Perfect.variables()).ok())
+            .unwrap_or_else(|| config.template(MessageKey::Perfect))
+    }
+
+    /// How far a value is from the target. Lower is better.
+    pub fn difference(&self, value: Decimal) -> Decimal {
+        (value - self.target).abs()
+    }
+
+    /// The amount of decimal places to display values with.
+    pub fn precision(&self) -> usize {
+        self.step
+            .normalize()
+            .scale()
+            .max(self.min.normalize().scale()) as usize
+    }
+
+    /// Formats a value with the precision of the step, e.g. `45.000` for a step of `0.005`, and
+    /// the decimal separator of the locale.
+    pub fn format_value(&self, value: impl std::fmt::Display, locale: Locale) -> String {
+        locale.localize_number(format!("{:.*}", self.precision(), value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::TemplateError;
+
+    fn game(name: &str) -> GameConfig {
+        GameConfig {
+            name: name.to_owned(),
+            rules: FortyFiveConfig {
+                min: dec!(0),
+                max: dec!(45),
+                step: dec!(0.5),
+                target: dec!(22.5),
+                ..Default::default()
+            },
+        }
+    }
+
+    #[test]
+    fn defaults_are_valid() {
+        CommandConfig::default().validate().unwrap();
+    }
+
+    #[test]
+    fn rejects_invalid_rules() {
+        let rules = |min, max, step, target| FortyFiveConfig {
+            min,
+            max,
+            step,
+            target,
+            ..Default::default()
+        };
+
+        assert!(matches!(
+            rules(dec!(0), dec!(90), dec!(0), dec!(45)).validate(),
+            Err(ConfigError::NonPositiveStep)
+        ));
+        assert!(matches!(
+            rules(dec!(90), dec!(90), dec!(1), dec!(90)).validate(),
+            Err(ConfigError::EmptyRange)
+        ));
+        assert!(matches!(
+            rules(dec!(0), dec!(90), dec!(0.7), dec!(45.5)).validate(),
+            Err(ConfigError::RangeNotMultipleOfStep)
+        ));
+        assert!(matches!(
+            rules(dec!(0), dec!(90), dec!(1), dec!(45.5)).validate(),
+            Err(ConfigError::TargetOffGrid)
+        ));
+        assert!(matches!(
+            rules(dec!(0), dec!(90), dec!(1), dec!(91)).validate(),
+            Err(ConfigError::TargetOffGrid)
+        ));
+
+        let message = FortyFiveConfig {
+            perfect_45_message: Some("{{ rank }}".to_owned()),
+            ..Default::default()
+        };
+
+        assert!(matches!(
+            message.validate(),
+            Err(ConfigError::Message(MessageKey::Perfect, _))
+        ));
+    }
+
+    #[test]
+    fn rejects_invalid_games() {
+        for name in ["", DEFAULT_GAME, "!45", "two words"] {
+            assert!(
+                matches!(game(name).validate(), Err(ConfigError::InvalidGameName(_))),
+                "{name:?}"
+            );
+        }
+
+        game("22.5").validate().unwrap();
+
+        let config = CommandConfig {
+            games: vec![game("22.5"), game("22.5")],
+            ..Default::default()
+        };
+
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigError::DuplicateGame(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_invalid_settings() {
+        let invalid = |config: CommandConfig| config.validate().unwrap_err();
+
+        assert!(matches!(
+            invalid(CommandConfig {
+                prefix: Some('a'),
+                ..Default::default()
+            }),
+            ConfigError::InvalidPrefix('a')
+        ));
+        assert!(matches!(
+            invalid(CommandConfig {
+                locale: Some("pt".to_owned()),
+                ..Default::default()
+            }),
+            ConfigError::UnknownLocale(_)
+        ));
+        assert!(matches!(
+            invalid(CommandConfig {
+                timezone: Some("Europe/Nowhere".to_owned()),
+                ..Default::default()
+            }),
+            ConfigError::UnknownTimezone(_)
+        ));
+        assert!(matches!(
+            invalid(CommandConfig {
+                rewards: BTreeMap::from([("reward".to_owned(), "22.5".to_owned())]),
+                ..Default::default()
+            }),
+            ConfigError::RewardWithoutGame(_)
+        ));
+        assert!(matches!(
+            invalid(CommandConfig {
+                whispered: BTreeSet::from(["gen".to_owned()]),
+                ..Default::default()
+            }),
+            ConfigError::NotWhisperable(_)
+        ));
+        assert!(matches!(
+            invalid(CommandConfig {
+                messages: BTreeMap::from([(MessageKey::Roll, "{{ user".to_owned())]),
+                ..Default::default()
+            }),
+            ConfigError::Message(MessageKey::Roll, TemplateError::Unclosed)
+        ));
+        assert!(matches!(
+            invalid(CommandConfig {
+                webhooks: vec![WebhookConfig {
+                    url: "http://example.com".to_owned(),
+                    format: WebhookFormat::default(),
+                    events: Vec::new(),
+                }],
+                ..Default::default()
+            }),
+            ConfigError::InvalidWebhookUrl(_)
+        ));
+        assert!(matches!(
+            invalid(CommandConfig {
+                version: CONFIG_VERSION + 1,
+                ..Default::default()
+            }),
+            ConfigError::UnsupportedVersion(_)
+        ));
+    }
+
+    #[test]
+    fn validates_aliases() {
+        let config = CommandConfig {
+            games: vec![game("22.5")],
+            ..Default::default()
+        };
+
+        config.validate_alias("45b", "45 best").unwrap();
+        config.validate_alias("half", "22.5").unwrap();
+
+        assert!(matches!(
+            config.validate_alias("22.5", "45"),
+            Err(ConfigError::InvalidAlias(_))
+        ));
+        assert!(matches!(
+            config.validate_alias("a b", "45"),
+            Err(ConfigError::InvalidAlias(_))
+        ));
+        assert!(matches!(
+            config.validate_alias("x", "x best"),
+            Err(ConfigError::AliasWithoutGame(_))
+        ));
+        assert!(matches!(
+            config.validate_alias("y", ""),
+            Err(ConfigError::AliasWithoutGame(_))
+        ));
+    }
+
+    #[test]
+    fn resolves_commands() {
+        let config = CommandConfig {
+            prefix: Some('?'),
+            aliases: BTreeMap::from([("45b".to_owned(), "45 best today".to_owned())]),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            config.resolve_command(&["?45", "pb"]),
+            Some(vec!["45", "pb"])
+        );
+        assert_eq!(
+            config.resolve_command(&["?45b", "@someone"]),
+            Some(vec!["45", "best", "today", "@someone"])
+        );
+        assert_eq!(config.resolve_command(&["!45"]), None);
+        assert_eq!(config.resolve_command(&[]), None);
+    }
+
+    #[test]
+    fn grants_cheer_rolls_from_the_threshold() {
+        let bonus = BonusConfig {
+            cheer_bits: Some(100),
+            cheer_rolls: 2,
+            ..Default::default()
+        };
+
+        assert_eq!(bonus.rolls_for_cheer(99), 0);
+        assert_eq!(bonus.rolls_for_cheer(100), 2);
+        assert_eq!(bonus.rolls_for_cheer(10_000), 2);
+        assert!(bonus.is_enabled());
+
+        let disabled = BonusConfig {
+            cheer_rolls: 2,
+            ..Default::default()
+        };
+
+        assert_eq!(disabled.rolls_for_cheer(10_000), 0);
+        assert!(!disabled.is_enabled());
+    }
+
+    #[test]
+    fn limits_bonus_rolls() {
+        let bonus = |cheer_bits, cheer_rolls, sub_rolls| BonusConfig {
+            cheer_bits,
+            cheer_rolls,
+            sub_rolls,
+            bypass_timeouts: false,
+        };
+
+        bonus(Some(1), MAX_BONUS_ROLLS, MAX_BONUS_ROLLS)
+            .validate()
+            .unwrap();
+
+        assert!(matches!(
+            bonus(Some(0), 1, 0).validate(),
+            Err(ConfigError::NoCheerBits)
+        ));
+        assert!(matches!(
+            bonus(None, MAX_BONUS_ROLLS + 1, 0).validate(),
+            Err(ConfigError::TooManyBonusRolls)
+        ));
+        assert!(matches!(
+            bonus(None, 0, MAX_BONUS_ROLLS + 1).validate(),
+            Err(ConfigError::TooManyBonusRolls)
+        ));
+    }
+
+    #[test]
+    fn formats_values_with_the_precision_of_the_step() {
+        let rules = FortyFiveConfig::default();
+
+        assert_eq!(rules.format_value(dec!(45), Locale::En), "45.000");
+        assert_eq!(rules.format_value(dec!(12.5), Locale::De), "12,500");
+        assert_eq!(game("22.5").rules.format_value(dec!(3), Locale::En), "3.0");
+    }
 }
@@ -0,0 +1,165 @@
+//! Migrations for configuration written by older versions of the bot. Each migration takes the
+//! JSON of one version and returns the JSON of the next one, so stored configuration keeps
+//! working without having to be rewritten in KV.
+
+use serde_json::Value;
+
+use super::CONFIG_VERSION;
+use crate::error::ConfigError;
+
+/// Brings configuration of any supported version up to [`CONFIG_VERSION`]. Configuration without
+/// a version is from before versions existed, which is version 0.
+pub fn migrate(mut config: Value) -> Result<Value, ConfigError> {
+    if !config.is_object() {
+        return Err(ConfigError::NotAnObject);
+    }
+
+    let mut version = match config.get("version") {
+        Some(version) => version.as_u64().ok_or(ConfigError::InvalidVersion)?,
+        None => 0,
+    };
+
+    if version > CONFIG_VERSION {
+        return Err(ConfigError::UnsupportedVersion(version));
+    }
+
+    while version < CONFIG_VERSION {
+        config = match version {
+            0 => v0_to_v1(config),
+            _ => unreachable!("Every version below CONFIG_VERSION has a migration"),
+        };
+
+        version += 1;
+        config["version"] = version.into();
+    }
+
+    Ok(config)
+}
+
+/// Version 0 wrote the chatter as `{{ chatter_user_name }}` in the perfect messages of !45 and the
+/// other games, and in the channel's message overrides. Templates call it `{{ user }}` now.
+fn v0_to_v1(mut config: Value) -> Value {
+    if let Some(Value::String(message)) = config.pointer_mut("/forty_five/perfect_45_message") {
+        *message = rename_variable(message, "chatter_user_name", "user");
+    }
+
+    if let Some(Value::Array(games)) = config.get_mut("games") {
+        for game in games {
+            if let Some(Value::String(message)) = game.get_mut("perfect_45_message") {
+                *message = rename_variable(message, "chatter_user_name", "user");
+            }
+        }
+    }
+
+    if let Some(Value::Object(messages)) = config.get_mut("messages") {
+        for message in messages.values_mut() {
+            if let Value::String(message) = message {
+                *message = rename_variable(message, "chatter_user_name", "user");
+            }
+        }
+    }
+
+    config
+}
+
+/// Renames a variable in a template, however it's spaced, e.g. `{{from}}` and `{{ from }}`.
+fn rename_variable(template: &str, from: &str, to: &str) -> String {
+    let mut renamed = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find("{{") {
+        let after = &rest[start + 2..];
+
+        let Some(end) = after.find("}}") else {
+            break;
+        };
+
+        renamed.push_str(&rest[..start]);
+
+        if after[..end].trim() == from {
+            renamed.push_str("{{ ");
+            renamed.push_str(to);
+            renamed.push_str(" }}");
+        } else {
+            renamed.push_str(&rest[start..start + 2 + end + 2]);
+        }
+
+        rest = &after[end + 2..];
+    }
+
+    renamed.push_str(rest);
+    renamed
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::config::CommandConfig;
+
+    #[test]
+    fn v0_renames_the_chatter_everywhere() {
+        let v0 = json!({
+            "forty_five": { "perfect_45_message": "{{ chatter_user_name }} hit it!" },
+            "games": [
+                { "name": "22.5", "perfect_45_message": "GG {{chatter_user_name}}" },
+                { "name": "90" }
+            ],
+            "messages": { "perfect": "{{ chatter_user_name }} got {{ value }}" }
+        });
+
+        let v1 = migrate(v0).unwrap();
+
+        assert_eq!(v1["version"], 1);
+        assert_eq!(v1["forty_five"]["perfect_45_message"], "{{ user }} hit it!");
+        assert_eq!(v1["games"][0]["perfect_45_message"], "GG {{ user }}");
+        assert!(v1["games"][1].get("perfect_45_message").is_none());
+        assert_eq!(v1["messages"]["perfect"], "{{ user }} got {{ value }}");
+
+        let config: CommandConfig = serde_json::from_value(v1).unwrap();
+        config.validate().unwrap();
+    }
+
+    #[test]
+    fn v0_keeps_other_variables_and_unclosed_braces() {
+        assert_eq!(
+            rename_variable(
+                "{{ value }} {{ chatter_user_name }} {{",
+                "chatter_user_name",
+                "user"
+            ),
+            "{{ value }} {{ user }} {{"
+        );
+    }
+
+    #[test]
+    fn v1_round_trips() {
+        let mut config = CommandConfig::default();
+        config.forty_five.perfect_45_message = Some("{{ user }} hit it!".to_owned());
+        config.prefix = Some('?');
+        config.timezone = Some("Europe/Berlin".to_owned());
+
+        let saved = serde_json::to_value(&config).unwrap();
+        let migrated = migrate(saved.clone()).unwrap();
+
+        assert_eq!(migrated, saved);
+
+        let loaded: CommandConfig = serde_json::from_value(migrated).unwrap();
+
+        assert_eq!(serde_json::to_value(&loaded).unwrap(), saved);
+    }
+
+    #[test]
+    fn rejects_unsupported_versions() {
+        assert!(matches!(
+            migrate(json!({ "version": CONFIG_VERSION + 1 })),
+            Err(ConfigError::UnsupportedVersion(_))
+        ));
+        assert!(matches!(
+            migrate(json!({ "version": "1" })),
+            Err(ConfigError::InvalidVersion)
+        ));
+        assert!(matches!(migrate(json!([])), Err(ConfigError::NotAnObject)));
+    }
+}
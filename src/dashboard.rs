@@ -0,0 +1,969 @@
+//! A small dashboard for broadcasters and their moderators, served as plain HTML from the worker.
+//! Users log in with Twitch and can look at and configure the channels they manage. Everything
+//! shown comes from [`crate::db`], the same queries the chat commands use.
+
+use std::str::FromStr;
+
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use sqlx_d1::D1Connection;
+use twitch_api::helix::moderation::GetModeratedChannelsRequest;
+use twitch_oauth2::{AccessToken, UserToken};
+use worker::{
+    Context, Fetch, FormEntry, Headers, Method, Request, RequestInit, Response, ResponseBody,
+    Result, RouteContext, Url, wasm_bindgen::JsValue,
+};
+
+use crate::commands::forty_five::timeouts::timeout_key;
+use crate::db;
+use crate::export;
+use crate::import;
+use crate::overlay;
+use crate::prelude::*;
+use crate::webhooks;
+
+use html::{escape, page};
+use session::{Channel, SESSION_COOKIE, SESSION_SECS, STATE_COOKIE, Session, cookie, set_cookie};
+
+pub(crate) mod html;
+pub(crate) mod session;
+
+/// The scope needed to list the channels a user moderates.
+const MODERATED_CHANNELS_SCOPE: &str = "user:read:moderated_channels";
+
+/// The scope broadcasters grant for their channel point redemptions to be fulfilled and refunded,
+/// see [`crate::redemptions`].
+const REDEMPTIONS_SCOPE: &str = "channel:manage:redemptions";
+
+/// The scopes broadcasters grant for cheers and subscriptions to be delivered, see
+/// [`crate::bonus`].
+const SUPPORTERS_SCOPES: &str = "bits:read channel:read:subscriptions";
+
+/// The scopes the bot's account grants for whispers to be sent and received, see
+/// [`crate::whispers`].
+const WHISPERS_SCOPES: &str = "user:read:whispers user:manage:whispers";
+
+/// How many entries of the leaderboard and hall of fame are shown.
+const LIST_LENGTH: i64 = 10;
+
+/// `GET /dashboard`: The channels the user can manage, or a link to log in.
+pub async fn index(req: Request, ctx: RouteContext<Context>) -> Result<Response> {
+    handle(index_impl(req, ctx.env).await)
+}
+
+/// `GET /dashboard/login`: Sends the user to Twitch to log in. With `?redemptions`, broadcasters
+/// also allow the bot to manage their channel point redemptions, and with `?supporters` to see
+/// their cheers and subscriptions. The bot's own account logs in with `?whispers`.
+pub async fn login(req: Request, ctx: RouteContext<Context>) -> Result<Response> {
+    handle(login_impl(req, ctx.env).await)
+}
+
+/// `GET /dashboard/callback`: Where Twitch sends the user back to after logging in.
+pub async fn callback(req: Request, ctx: RouteContext<Context>) -> Result<Response> {
+    handle(callback_impl(req, ctx.env).await)
+}
+
+/// `POST /dashboard/logout`
+pub async fn logout(req: Request, ctx: RouteContext<Context>) -> Result<Response> {
+    handle(logout_impl(req, ctx.env).await)
+}
+
+/// `GET /dashboard/channels/:broadcaster_user_id`: Leaderboards, hall of fame, timeouts and
+/// settings of every game of a channel.
+pub async fn channel(req: Request, ctx: RouteContext<Context>) -> Result<Response> {
+    let broadcaster_user_id = ctx.param("broadcaster_user_id").cloned();
+
+    match broadcaster_user_id {
+        Some(broadcaster_user_id) => handle(channel_impl(req, ctx.env, broadcaster_user_id).await),
+        None => page(400, "Not found", "<p>No channel given.</p>"),
+    }
+}
+
+/// `POST /dashboard/channels/:broadcaster_user_id/config`: Changes the rules of one game of a
+/// channel.
+pub async fn update_config(req: Request, ctx: RouteContext<Context>) -> Result<Response> {
+    let broadcaster_user_id = ctx.param("broadcaster_user_id").cloned();
+
+    match broadcaster_user_id {
+        Some(broadcaster_user_id) => {
+            handle(update_config_impl(req, ctx.env, broadcaster_user_id).await)
+        }
+        None => page(400, "Not found", "<p>No channel given.</p>"),
+    }
+}
+
+/// `POST /dashboard/channels/:broadcaster_user_id/overlay`: Creates or replaces the overlay URL of
+/// a channel.
+pub async fn rotate_overlay_secret(req: Request, ctx: RouteContext<Context>) -> Result<Response> {
+    let broadcaster_user_id = ctx.param("broadcaster_user_id").cloned();
+
+    match broadcaster_user_id {
+        Some(broadcaster_user_id) => {
+            handle(rotate_overlay_secret_impl(req, ctx.env, broadcaster_user_id).await)
+        }
+        None => page(400, "Not found", "<p>No channel given.</p>"),
+    }
+}
+
+/// `GET /dashboard/channels/:broadcaster_user_id/export?format=csv`: Downloads every attempt of a
+/// channel, as `csv` or `ndjson`.
+pub async fn export(req: Request, ctx: RouteContext<Context>) -> Result<Response> {
+    let broadcaster_user_id = ctx.param("broadcaster_user_id").cloned();
+
+    match broadcaster_user_id {
+        Some(broadcaster_user_id) => handle(export_impl(req, ctx.env, broadcaster_user_id).await),
+        None => page(400, "Not found", "<p>No channel given.</p>"),
+    }
+}
+
+/// `POST /dashboard/channels/:broadcaster_user_id/import`: Imports attempts from another bot,
+/// uploaded as a CSV or NDJSON file.
+pub async fn import(req: Request, ctx: RouteContext<Context>) -> Result<Response> {
+    let broadcaster_user_id = ctx.param("broadcaster_user_id").cloned();
+
+    match broadcaster_user_id {
+        Some(broadcaster_user_id) => handle(import_impl(req, ctx.env, broadcaster_user_id).await),
+        None => page(400, "Not found", "<p>No channel given.</p>"),
+    }
+}
+
+/// Shows a generic error page for errors, the details are only logged.
+fn handle(result: BotResult<Response>) -> Result<Response> {
+    result.or_else(|e| {
+        console_error!("Error serving the dashboard: {:?}", e);
+        page(
+            500,
+            "Something went wrong",
+            "<p>Please try again later.</p>",
+        )
+    })
+}
+
+fn redirect(location: &str, cookies: &[String]) -> BotResult<Response> {
+    let mut builder = Response::builder()
+        .with_status(303)
+        .with_header("Location", location)?;
+
+    for cookie in cookies {
+        builder = builder.with_header("Set-Cookie", cookie)?;
+    }
+
+    Ok(builder.body(ResponseBody::Empty))
+}
+
+/// The URL Twitch redirects to after logging in, which has to be registered for the application.
+fn redirect_uri(req: &Request) -> BotResult<String> {
+    Ok(format!(
+        "{}/dashboard/callback",
+        req.url()?.origin().ascii_serialization()
+    ))
+}
+
+async fn index_impl(req: Request, env: Env) -> BotResult<Response> {
+    let Some(session) = Session::from_request(&req, &env).await? else {
+        return Ok(page(
+            200,
+            "Dashboard",
+            r#"<p><a href="/dashboard/login">Log in with Twitch</a> to manage your channel.</p>"#,
+        )?);
+    };
+
+    let mut body = format!(
+        "<p>Logged in as {}.</p>\n<ul>\n",
+        escape(&session.user_login)
+    );
+
+    for channel in &session.channels {
+        body.push_str(&format!(
+            "<li><a href=\"/dashboard/channels/{}\">{}</a></li>\n",
+            escape(channel.id.as_str()),
+            escape(&channel.login)
+        ));
+    }
+
+    body.push_str("</ul>\n");
+
+    if session.channels_expired {
+        body.push_str(
+            "<p><a href=\"/dashboard/login\">Log in again</a> to manage the channels you moderate.</p>\n",
+        );
+    }
+
+    body.push_str(&format!(
+        "<form method=\"post\" action=\"/dashboard/logout\">\
+         <input type=\"hidden\" name=\"csrf_token\" value=\"{}\">\
+         <button>Log out</button></form>",
+        escape(&session.csrf_token)
+    ));
+
+    Ok(page(200, "Dashboard", &body)?)
+}
+
+async fn login_impl(req: Request, env: Env) -> BotResult<Response> {
+    let client_id = env.secret("TW_CLIENT_ID")?.to_string();
+    let state = session::random_token()?;
+
+    let mut scope = MODERATED_CHANNELS_SCOPE.to_owned();
+
+    for (key, _) in req.url()?.query_pairs() {
+        match &*key {
+            "redemptions" => scope = format!("{scope} {REDEMPTIONS_SCOPE}"),
+            "supporters" => scope = format!("{scope} {SUPPORTERS_SCOPES}"),
+            "whispers" => scope = format!("{scope} {WHISPERS_SCOPES}"),
+            _ => (),
+        }
+    }
+
+    let url = Url::parse_with_params(
+        "https://id.twitch.tv/oauth2/authorize",
+        &[
+            ("response_type", "code"),
+            ("client_id", &client_id),
+            ("redirect_uri", &redirect_uri(&req)?),
+            ("scope", &scope),
+            ("state", &state),
+        ],
+    )
+    .expect("URL is valid");
+
+    redirect(url.as_str(), &[set_cookie(STATE_COOKIE, Some(&state), 600)])
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    refresh_token: Option<String>,
+    #[serde(default)]
+    scope: Vec<String>,
+}
+
+async fn callback_impl(req: Request, env: Env) -> BotResult<Response> {
+    let url = req.url()?;
+    let param = |name: &str| {
+        url.query_pairs()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.into_owned())
+    };
+
+    let expected_state = cookie(&req, STATE_COOKIE)?;
+
+    let (Some(code), Some(state), Some(expected_state)) =
+        (param("code"), param("state"), expected_state)
+    else {
+        // Either the user declined or the login didn't start here.
+        return Ok(page(
+            400,
+            "Login failed",
+            r#"<p>Logging in didn't work. <a href="/dashboard/login">Try again</a>.</p>"#,
+        )?);
+    };
+
+    if !constant_time_eq::constant_time_eq(state.as_bytes(), expected_state.as_bytes()) {
+        return Ok(page(
+            400,
+            "Login failed",
+            r#"<p>Logging in didn't work. <a href="/dashboard/login">Try again</a>.</p>"#,
+        )?);
+    }
+
+    let client_id = env.secret("TW_CLIENT_ID")?.to_string();
+    let client_secret = env.secret("TW_CLIENT_SECRET")?.to_string();
+    let redirect_uri = redirect_uri(&req)?;
+
+    // The token endpoint takes the same encoding as a query string, so let
+    // Url do the escaping.
+    let form = Url::parse_with_params(
+        "https://id.twitch.tv/oauth2/token",
+        &[
+            ("client_id", client_id.as_str()),
+            ("client_secret", client_secret.as_str()),
+            ("code", code.as_str()),
+            ("grant_type", "authorization_code"),
+            ("redirect_uri", redirect_uri.as_str()),
+        ],
+    )
+    .expect("URL is valid");
+
+    let headers = Headers::new();
+    headers.set("Content-Type", "application/x-www-form-urlencoded")?;
+
+    let request_init = RequestInit {
+        method: Method::Post,
+        headers,
+        body: Some(JsValue::from_str(form.query().unwrap_or_default())),
+        ..Default::default()
+    };
+
+    let request = Request::new_with_init("https://id.twitch.tv/oauth2/token", &request_init)?;
+    let mut response = Fetch::Request(request).send().await?;
+
+    if response.status_code() != 200 {
+        return Ok(page(
+            400,
+            "Login failed",
+            r#"<p>Twitch didn't accept the login. <a href="/dashboard/login">Try again</a>.</p>"#,
+        )?);
+    }
+
+    let token: TokenResponse = response.json().await?;
+    let granted = |wanted: &str| {
+        wanted
+            .split_whitespace()
+            .all(|wanted| token.scope.iter().any(|scope| scope == wanted))
+    };
+    let redemptions_token = match &token.refresh_token {
+        Some(refresh_token) if granted(REDEMPTIONS_SCOPE) => {
+            Some((token.access_token.clone(), refresh_token.clone()))
+        }
+        _ => None,
+    };
+    let whispers_token = match &token.refresh_token {
+        Some(refresh_token) if granted(WHISPERS_SCOPES) => {
+            Some((token.access_token.clone(), refresh_token.clone()))
+        }
+        _ => None,
+    };
+
+    let http_client = HelixClient::with_client(FetchClient::default());
+    let token = UserToken::from_existing(
+        &http_client,
+        AccessToken::new(token.access_token),
+        None,
+        None,
+    )
+    .await?;
+
+    if let Some((access_token, refresh_token)) = redemptions_token {
+        crate::redemptions::store_token(
+            &env,
+            token.user_id.as_str(),
+            &access_token,
+            &refresh_token,
+        )
+        .await?;
+    }
+
+    // Only the bot's own account can whisper as the bot.
+    if let Some((access_token, refresh_token)) = whispers_token
+        && token.user_id.as_str() == BOT_USER_ID
+    {
+        crate::whispers::store_token(&env, &access_token, &refresh_token).await?;
+    }
+
+    // Broadcasters can always manage their own channel.
+    let mut channels = vec![Channel {
+        id: token.user_id.clone(),
+        login: token.login.to_string(),
+    }];
+
+    let mut request = GetModeratedChannelsRequest::user_id(&token.user_id);
+    request.first = Some(100);
+
+    let mut response = Some(http_client.req_get(request, &token).await?);
+
+    while let Some(page) = response {
+        channels.extend(page.data.iter().map(|channel| Channel {
+            id: channel.broadcaster_id.clone(),
+            login: channel.broadcaster_login.to_string(),
+        }));
+
+        response = page.get_next(&http_client, &token).await?;
+    }
+
+    let session = Session::create(
+        &env,
+        token.user_id.clone(),
+        token.login.to_string(),
+        channels,
+    )
+    .await?;
+
+    redirect(
+        "/dashboard",
+        &[
+            set_cookie(STATE_COOKIE, None, 0),
+            set_cookie(SESSION_COOKIE, Some(&session.id), SESSION_SECS),
+        ],
+    )
+}
+
+async fn logout_impl(mut req: Request, env: Env) -> BotResult<Response> {
+    if let Some(session) = Session::from_request(&req, &env).await? {
+        let form = req.form_data().await?;
+
+        if let Some(FormEntry::Field(token)) = form.get("csrf_token")
+            && session.is_valid_csrf_token(&token)
+        {
+            session.delete(&env).await?;
+        }
+    }
+
+    redirect("/dashboard", &[set_cookie(SESSION_COOKIE, None, 0)])
+}
+
+/// The session and channel of a request, or the response to send when the user isn't logged in or
+/// can't manage the channel.
+async fn authorize(
+    req: &Request,
+    env: &Env,
+    broadcaster_user_id: &str,
+) -> BotResult<std::result::Result<(Session, Channel), Response>> {
+    let Some(session) = Session::from_request(req, env).await? else {
+        return Ok(Err(redirect("/dashboard/login", &[])?));
+    };
+
+    let Some(channel) = session.channel(broadcaster_user_id).cloned() else {
+        let body = if session.channels_expired {
+            "<p>Only the broadcaster and their moderators can manage this channel. \
+             <a href=\"/dashboard/login\">Log in again</a> if you moderate it.</p>"
+        } else {
+            "<p>Only the broadcaster and their moderators can manage this channel.</p>"
+        };
+
+        return Ok(Err(page(403, "Not allowed", body)?));
+    };
+
+    Ok(Ok((session, channel)))
+}
+
+async fn channel_impl(req: Request, env: Env, broadcaster_user_id: String) -> BotResult<Response> {
+    let (session, channel) = match authorize(&req, &env, &broadcaster_user_id).await? {
+        Ok(authorized) => authorized,
+        Err(response) => return Ok(response),
+    };
+
+    let config = CommandConfig::load(&env, &channel.id).await;
+    let db_conn = D1Connection::new(env.d1(FORTYFIVE_DATA)?);
+    let locale = Locale::default();
+
+    let mut sections = Vec::new();
+    let mut user_ids = Vec::new();
+
+    for game in config.all_games() {
+        let epoch = db::current_epoch(&db_conn, channel.id.as_str(), &game.name).await?;
+        let leaderboard = db::leaderboard(
+            &db_conn,
+            channel.id.as_str(),
+            &game.name,
+            epoch,
+            false,
+            LIST_LENGTH,
+            0,
+        )
+        .await?;
+        let hall_of_fame = db::hall_of_fame(
+            &db_conn,
+            channel.id.as_str(),
+            &game.name,
+            None,
+            LIST_LENGTH,
+            0,
+        )
+        .await?;
+
+        user_ids.extend(leaderboard.iter().map(|r| r.chatter_user_id.clone()));
+        user_ids.extend(hall_of_fame.iter().map(|e| e.chatter_user_id.clone()));
+
+        sections.push((game, epoch, leaderboard, hall_of_fame));
+    }
+
+    let timeouts = env
+        .kv(TIMEOUTS_KV)?
+        .list()
+        .prefix(
+            timeout_key(&channel.id, None)
+                .trim_end_matches('*')
+                .to_owned(),
+        )
+        .execute()
+        .await?;
+
+    let timed_out = timeouts
+        .keys
+        .iter()
+        .filter_map(|key| {
+            let (_, chatter) = key.name.rsplit_once("chatter=")?;
+            Some((chatter.to_owned(), key.expiration))
+        })
+        .collect::<Vec<_>>();
+
+    user_ids.extend(timed_out.iter().map(|(id, _)| id.clone()));
+    user_ids.sort();
+    user_ids.dedup();
+
+    let http_client = HelixClient::with_client(FetchClient::default());
+    let credentials = get_credentials(&env).await?;
+    let names = get_display_names(
+        &http_client,
+        &user_ids
+            .iter()
+            .map(|id| UserIdRef::from_str(id))
+            .collect::<Vec<_>>(),
+        &credentials,
+    )
+    .await?;
+
+    let name = |id: &str| escape(names.get(id).map(String::as_str).unwrap_or(id));
+
+    let mut body = String::from(r#"<p><a href="/dashboard">All channels</a></p>"#);
+
+    if req.url()?.query_pairs().any(|(key, _)| key == "saved") {
+        body.push_str("<p>Saved.</p>\n");
+    }
+
+    for (game, epoch, leaderboard, hall_of_fame) in &sections {
+        let rules = &game.rules;
+
+        body.push_str(&format!(
+            "<h2>{}</h2>\n<p>Epoch {epoch}, aiming for {}.</p>\n",
+            escape(&game.command(config.prefix())),
+            rules.format_value(rules.target, locale)
+        ));
+
+        body.push_str("<h3>Leaderboard</h3>\n");
+
+        if leaderboard.is_empty() {
+            body.push_str("<p>No attempts in this epoch yet.</p>\n");
+        } else {
+            body.push_str(
+                "<table>\n<tr><th>#</th><th>Chatter</th><th>Value</th><th>Difference</th></tr>\n",
+            );
+
+            for (position, record) in leaderboard.iter().enumerate() {
+                body.push_str(&format!(
+                    "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+                    position + 1,
+                    name(&record.chatter_user_id),
+                    rules.format_value(record.forty_five_value, locale),
+                    rules.format_value(record.forty_five_difference, locale)
+                ));
+            }
+
+            body.push_str("</table>\n");
+        }
+
+        body.push_str("<h3>Hall of fame</h3>\n");
+
+        if hall_of_fame.is_empty() {
+            body.push_str("<p>Nobody hit the target yet.</p>\n");
+        } else {
+            body.push_str("<table>\n<tr><th>Epoch</th><th>Chatter</th></tr>\n");
+
+            for entry in hall_of_fame {
+                body.push_str(&format!(
+                    "<tr><td>{}</td><td>{}</td></tr>\n",
+                    entry.epoch,
+                    name(&entry.chatter_user_id)
+                ));
+            }
+
+            body.push_str("</table>\n");
+        }
+
+        body.push_str(&config_form(&session, &channel, game));
+    }
+
+    body.push_str("<h2>Overlay</h2>\n");
+
+    let overlay_action = match overlay::secret(&env, channel.id.as_str()).await? {
+        Some(secret) => {
+            let url = Url::parse_with_params(
+                &format!(
+                    "{}/overlay/{}",
+                    req.url()?.origin().ascii_serialization(),
+                    channel.id.as_str()
+                ),
+                &[("secret", &secret)],
+            )
+            .expect("URL is valid");
+
+            body.push_str(&format!(
+                "<p>Add this URL as a browser source in OBS, and keep it to yourself:</p>\n\
+                 <p><input value=\"{}\" size=\"80\" readonly></p>\n",
+                escape(url.as_str())
+            ));
+
+            "Replace the overlay URL"
+        }
+        None => "Create an overlay URL",
+    };
+
+    body.push_str(&format!(
+        "<form method=\"post\" action=\"/dashboard/channels/{}/overlay\">\
+         <input type=\"hidden\" name=\"csrf_token\" value=\"{}\">\
+         <button>{overlay_action}</button></form>\n",
+        escape(channel.id.as_str()),
+        escape(&session.csrf_token)
+    ));
+
+    body.push_str(&format!(
+        "<h2>Export</h2>\n\
+         <p>Download every attempt as <a href=\"/dashboard/channels/{id}/export?format=csv\">CSV</a> \
+         or <a href=\"/dashboard/channels/{id}/export?format=ndjson\">NDJSON</a>.</p>\n",
+        id = escape(channel.id.as_str())
+    ));
+
+    body.push_str(&import_form(&session, &channel, &config.all_games()));
+
+    if session.user_id == channel.id {
+        body.push_str(&format!(
+            "<h2>Channel points</h2>\n\
+             <p>{} reward(s) play games, set them with <code>!45 config set rewards</code>.</p>\n\
+             <p><a href=\"/dashboard/login?redemptions\">Allow the bot to fulfill and refund \
+             redemptions</a> of rewards it created.</p>\n\
+             <h2>Supporters</h2>\n\
+             <p>Cheers and subscriptions grant the bonus rolls set with \
+             <code>!45 config set forty_five.bonus</code>.</p>\n\
+             <p><a href=\"/dashboard/login?supporters\">Allow the bot to see cheers and \
+             subscriptions</a>.</p>\n",
+            config.rewards.len()
+        ));
+    }
+
+    body.push_str(&format!(
+        "<h2>Webhooks</h2>\n\
+         <p>{} webhook(s) configured, set them with <code>!45 config set webhooks</code>.</p>\n",
+        config.webhooks.len(),
+    ));
+
+    // Anyone with the secret can sign deliveries, so only the broadcaster
+    // gets to see it.
+    if session.user_id == channel.id {
+        body.push_str(&format!(
+            "<p>JSON deliveries are signed with <code>{}</code>.</p>\n",
+            escape(&webhooks::signing_secret(&env, channel.id.as_str())?)
+        ));
+    }
+
+    body.push_str("<h2>Timeouts</h2>\n");
+
+    if timed_out.is_empty() {
+        body.push_str("<p>Nobody is timed out.</p>\n");
+    } else {
+        let now = web_time::SystemTime::now()
+            .duration_since(web_time::SystemTime::UNIX_EPOCH)?
+            .as_secs();
+
+        body.push_str("<table>\n<tr><th>Chatter</th><th>Seconds left</th></tr>\n");
+
+        for (id, expiration) in &timed_out {
+            let left = expiration
+                .map(|expiration| expiration.saturating_sub(now).to_string())
+                .unwrap_or_default();
+
+            body.push_str(&format!("<tr><td>{}</td><td>{left}</td></tr>\n", name(id)));
+        }
+
+        body.push_str("</table>\n");
+    }
+
+    Ok(page(200, &channel.login, &body)?)
+}
+
+fn config_form(session: &Session, channel: &Channel, game: &GameConfig) -> String {
+    let rules = &game.rules;
+
+    format!(
+        "<h3>Settings</h3>\n\
+         <form method=\"post\" action=\"/dashboard/channels/{channel_id}/config\">\n\
+         <input type=\"hidden\" name=\"csrf_token\" value=\"{csrf_token}\">\n\
+         <input type=\"hidden\" name=\"game\" value=\"{game}\">\n\
+         <label>Min <input name=\"min\" value=\"{min}\" required></label>\n\
+         <label>Max <input name=\"max\" value=\"{max}\" required></label>\n\
+         <label>Step <input name=\"step\" value=\"{step}\" required></label>\n\
+         <label>Target <input name=\"target\" value=\"{target}\" required></label>\n\
+         <label>Message for hitting the target <input name=\"perfect_45_message\" value=\"{perfect}\" size=\"60\"></label>\n\
+         <button>Save</button>\n\
+         </form>\n",
+        channel_id = escape(channel.id.as_str()),
+        csrf_token = escape(&session.csrf_token),
+        game = escape(&game.name),
+        min = rules.min,
+        max = rules.max,
+        step = rules.step,
+        target = rules.target,
+        perfect = escape(rules.perfect_45_message.as_deref().unwrap_or_default()),
+    )
+}
+
+fn import_form(session: &Session, channel: &Channel, games: &[GameConfig]) -> String {
+    let options = games
+        .iter()
+        .map(|game| format!("<option>{}</option>", escape(&game.name)))
+        .collect::<String>();
+
+    format!(
+        "<h2>Import</h2>\n\
+         <p>Attempts from other bots can be imported as CSV with a header, or as NDJSON, with a \
+         <code>login</code> or <code>user_id</code>, a <code>value</code> and a \
+         <code>timestamp</code> for every attempt. Attempts that are already stored are skipped.</p>\n\
+         <form method=\"post\" action=\"/dashboard/channels/{channel_id}/import\" enctype=\"multipart/form-data\">\n\
+         <input type=\"hidden\" name=\"csrf_token\" value=\"{csrf_token}\">\n\
+         <label>Game <select name=\"game\">{options}</select></label>\n\
+         <label>Format <select name=\"format\"><option value=\"csv\">CSV</option><option value=\"ndjson\">NDJSON</option></select></label>\n\
+         <label>File <input type=\"file\" name=\"file\" required></label>\n\
+         <label><input type=\"checkbox\" name=\"dry_run\" checked> Only check, don't import</label>\n\
+         <button>Import</button>\n\
+         </form>\n",
+        channel_id = escape(channel.id.as_str()),
+        csrf_token = escape(&session.csrf_token),
+    )
+}
+
+async fn update_config_impl(
+    mut req: Request,
+    env: Env,
+    broadcaster_user_id: String,
+) -> BotResult<Response> {
+    let (session, channel) = match authorize(&req, &env, &broadcaster_user_id).await? {
+        Ok(authorized) => authorized,
+        Err(response) => return Ok(response),
+    };
+
+    let form = req.form_data().await?;
+    let field = |name: &str| match form.get(name) {
+        Some(FormEntry::Field(value)) => Some(value),
+        _ => None,
+    };
+
+    let invalid = |message: &str| {
+        page(
+            400,
+            "Invalid settings",
+            &format!(
+                "<p class=\"error\">{}</p>\n<p><a href=\"/dashboard/channels/{}\">Back</a></p>",
+                escape(message),
+                escape(channel.id.as_str())
+            ),
+        )
+    };
+
+    if !field("csrf_token").is_some_and(|token| session.is_valid_csrf_token(&token)) {
+        return Ok(page(
+            403,
+            "Not allowed",
+            "<p>The form has expired, reload the page and try again.</p>",
+        )?);
+    }
+
+    let decimal = |name: &str| {
+        field(name)
+            .and_then(|value| Decimal::from_str(value.trim()).ok())
+            .ok_or_else(|| format!("{name} must be a number"))
+    };
+
+    let values = (|| {
+        Ok::<_, String>((
+            decimal("min")?,
+            decimal("max")?,
+            decimal("step")?,
+            decimal("target")?,
+        ))
+    })();
+
+    let (min, max, step, target) = match values {
+        Ok(values) => values,
+        Err(message) => return Ok(invalid(&message)?),
+    };
+
+    let perfect_45_message = field("perfect_45_message")
+        .map(|message| message.trim().to_owned())
+        .filter(|message| !message.is_empty());
+
+    // Changes are made to the stored configuration rather than the loaded
+    // one, which leaves out invalid settings that would be lost on save.
+    let mut config = match CommandConfig::read(&env, &channel.id).await {
+        Ok(config) => config.unwrap_or_default(),
+        Err(e) => {
+            return Ok(invalid(&format!("The stored settings can't be read: {e}"))?);
+        }
+    };
+    let game = field("game").unwrap_or_default();
+
+    let rules = if game == DEFAULT_GAME {
+        &mut config.forty_five
+    } else {
+        match config.games.iter_mut().find(|g| g.name == game) {
+            Some(game) => &mut game.rules,
+            None => return Ok(invalid("This game doesn't exist anymore.")?),
+        }
+    };
+
+    rules.min = min;
+    rules.max = max;
+    rules.step = step;
+    rules.target = target;
+    rules.perfect_45_message = perfect_45_message;
+
+    match config.save(&env, &channel.id).await {
+        Ok(()) => redirect(
+            &format!("/dashboard/channels/{}?saved", channel.id.as_str()),
+            &[],
+        ),
+        Err(BotError::ConfigError(e)) => Ok(invalid(&e.to_string())?),
+        Err(e) => Err(e),
+    }
+}
+
+async fn rotate_overlay_secret_impl(
+    mut req: Request,
+    env: Env,
+    broadcaster_user_id: String,
+) -> BotResult<Response> {
+    let (session, channel) = match authorize(&req, &env, &broadcaster_user_id).await? {
+        Ok(authorized) => authorized,
+        Err(response) => return Ok(response),
+    };
+
+    let form = req.form_data().await?;
+
+    let Some(FormEntry::Field(token)) = form.get("csrf_token") else {
+        return Ok(page(
+            403,
+            "Not allowed",
+            "<p>The form has expired, reload the page and try again.</p>",
+        )?);
+    };
+
+    if !session.is_valid_csrf_token(&token) {
+        return Ok(page(
+            403,
+            "Not allowed",
+            "<p>The form has expired, reload the page and try again.</p>",
+        )?);
+    }
+
+    overlay::set_secret(&env, channel.id.as_str(), &session::random_token()?).await?;
+
+    redirect(&format!("/dashboard/channels/{}", channel.id.as_str()), &[])
+}
+
+async fn export_impl(req: Request, env: Env, broadcaster_user_id: String) -> BotResult<Response> {
+    let (_, channel) = match authorize(&req, &env, &broadcaster_user_id).await? {
+        Ok(authorized) => authorized,
+        Err(response) => return Ok(response),
+    };
+
+    let format = req
+        .url()?
+        .query_pairs()
+        .find(|(key, _)| key == "format")
+        .map(|(_, value)| value.into_owned())
+        .unwrap_or_else(|| "csv".to_owned());
+
+    let Some(format) = export::Format::from_name(&format) else {
+        return Ok(page(
+            400,
+            "Unknown format",
+            "<p>Exports can be downloaded as csv or ndjson.</p>",
+        )?);
+    };
+
+    export::response(env, &channel.id, &channel.login, format).await
+}
+
+async fn import_impl(
+    mut req: Request,
+    env: Env,
+    broadcaster_user_id: String,
+) -> BotResult<Response> {
+    let (session, channel) = match authorize(&req, &env, &broadcaster_user_id).await? {
+        Ok(authorized) => authorized,
+        Err(response) => return Ok(response),
+    };
+
+    let form = req.form_data().await?;
+    let field = |name: &str| match form.get(name) {
+        Some(FormEntry::Field(value)) => Some(value),
+        _ => None,
+    };
+
+    if !field("csrf_token").is_some_and(|token| session.is_valid_csrf_token(&token)) {
+        return Ok(page(
+            403,
+            "Not allowed",
+            "<p>The form has expired, reload the page and try again.</p>",
+        )?);
+    }
+
+    let back = format!(
+        "<p><a href=\"/dashboard/channels/{}\">Back</a></p>",
+        escape(channel.id.as_str())
+    );
+
+    let invalid = |message: &str| {
+        page(
+            400,
+            "Invalid import",
+            &format!("<p class=\"error\">{}</p>\n{back}", escape(message)),
+        )
+    };
+
+    let Some(format) = field("format").and_then(|format| export::Format::from_name(&format)) else {
+        return Ok(invalid("Imports have to be csv or ndjson.")?);
+    };
+
+    let Some(FormEntry::File(file)) = form.get("file") else {
+        return Ok(invalid("No file was uploaded.")?);
+    };
+
+    let config = CommandConfig::load(&env, &channel.id).await;
+    let game = field("game").unwrap_or_else(|| DEFAULT_GAME.to_owned());
+
+    let Some(game) = config.game(&game) else {
+        return Ok(invalid("This game doesn't exist anymore.")?);
+    };
+
+    let dry_run = field("dry_run").is_some();
+    let input = file.text().await?;
+
+    let report = import::run(&env, channel.id.as_str(), &game, format, &input, dry_run).await?;
+
+    let mut body = format!(
+        "<p>{} of {} attempt(s) of {} {}, {} perfect. {} were already stored and {} were invalid.</p>\n\
+         <p>Epoch {} {} epoch {}, and {} stored attempt(s) {} a later epoch.</p>\n",
+        report.imported,
+        report.rows,
+        escape(&report.game),
+        if dry_run {
+            "would be imported"
+        } else {
+            "were imported"
+        },
+        report.perfects,
+        report.duplicates,
+        report.invalid,
+        report.epoch_before,
+        if dry_run { "would become" } else { "became" },
+        report.epoch_after,
+        report.renumbered,
+        if dry_run { "would move to" } else { "moved to" },
+    );
+
+    if !report.unknown_users.is_empty() {
+        body.push_str(&format!(
+            "<p>These users don't exist on Twitch: {}</p>\n",
+            escape(&report.unknown_users.join(", "))
+        ));
+    }
+
+    if !report.errors.is_empty() {
+        body.push_str("<table>\n<tr><th>Line</th><th>Problem</th></tr>\n");
+
+        for error in &report.errors {
+            body.push_str(&format!(
+                "<tr><td>{}</td><td>{}</td></tr>\n",
+                error.line,
+                escape(&error.reason)
+            ));
+        }
+
+        body.push_str("</table>\n");
+    }
+
+    body.push_str(&back);
+
+    let title = if dry_run { "Import check" } else { "Import" };
+
+    Ok(page(200, title, &body)?)
+}
@@ -0,0 +1,48 @@
+use worker::{Response, Result};
+
+/// Escapes text for use in HTML, both in elements and in quoted attributes.
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+/// Wraps the body of a page, `title` is escaped but `body` has to be HTML already.
+pub fn page(status: u16, title: &str, body: &str) -> Result<Response> {
+    let title = escape(title);
+
+    let html = format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>{title} · next45</title>
+<style>
+body {{ font-family: system-ui, sans-serif; max-width: 60rem; margin: 2rem auto; padding: 0 1rem; }}
+table {{ border-collapse: collapse; margin-bottom: 1rem; }}
+th, td {{ text-align: left; padding: 0.25rem 0.75rem; border-bottom: 1px solid #ddd; }}
+label {{ display: block; margin: 0.5rem 0; }}
+.error {{ color: #b00020; }}
+</style>
+</head>
+<body>
+<h1>{title}</h1>
+{body}
+</body>
+</html>"#
+    );
+
+    Ok(Response::from_html(html)?.with_status(status))
+}
@@ -0,0 +1,190 @@
+use serde::{Deserialize, Serialize};
+use sqlx_d1::D1Connection;
+use twitch_api::types::UserId;
+use worker::{Env, Request};
+
+use crate::prelude::*;
+
+/// Name of the cookie holding the session ID.
+pub const SESSION_COOKIE: &str = "next45_session";
+
+/// Name of the cookie holding the OAuth state while logging in.
+pub const STATE_COOKIE: &str = "next45_oauth_state";
+
+/// How long a login lasts.
+pub const SESSION_SECS: i64 = 7 * 24 * 60 * 60;
+
+/// How long the channels a user moderates are trusted after logging in. Broadcasters can remove
+/// moderators at any time, so sessions only keep the user's own channel after this.
+pub const MODERATED_CHANNELS_SECS: i64 = 15 * 60;
+
+/// A channel the logged in user can manage.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Channel {
+    pub id: UserId,
+    pub login: String,
+}
+
+#[derive(Debug)]
+pub struct Session {
+    pub id: String,
+    pub user_id: UserId,
+    pub user_login: String,
+    /// The user's own channel and the channels they moderate, only the former once
+    /// [`MODERATED_CHANNELS_SECS`] have passed.
+    pub channels: Vec<Channel>,
+    /// Whether the channels the user moderates were dropped for being looked up too long ago.
+    pub channels_expired: bool,
+    /// Sent with every form and compared on submit.
+    pub csrf_token: String,
+}
+
+impl Session {
+    /// Stores a new session for the user, removing expired ones along the way.
+    pub async fn create(
+        env: &Env,
+        user_id: UserId,
+        user_login: String,
+        channels: Vec<Channel>,
+    ) -> BotResult<Self> {
+        let db_conn = D1Connection::new(env.d1(FORTYFIVE_DATA)?);
+        let now = now_millis()?;
+
+        let session = Self {
+            id: random_token()?,
+            user_id,
+            user_login,
+            channels,
+            channels_expired: false,
+            csrf_token: random_token()?,
+        };
+
+        let channels = serde_json::to_string(&session.channels)?;
+        let expires_at = now + SESSION_SECS * 1000;
+        let channels_expire_at = now + MODERATED_CHANNELS_SECS * 1000;
+
+        sqlx_d1::query!("DELETE FROM DashboardSessions WHERE expires_at <= ?1;", now)
+            .execute(&db_conn)
+            .await?;
+
+        sqlx_d1::query!(
+            "
+            INSERT INTO DashboardSessions (id, user_id, user_login, channels, csrf_token, expires_at, channels_expire_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7);
+            ",
+            session.id,
+            session.user_id.as_str(),
+            session.user_login,
+            channels,
+            session.csrf_token,
+            expires_at,
+            channels_expire_at
+        )
+        .execute(&db_conn)
+        .await?;
+
+        Ok(session)
+    }
+
+    /// The session of the request's cookie, if it exists and hasn't expired.
+    pub async fn from_request(req: &Request, env: &Env) -> BotResult<Option<Self>> {
+        let Some(id) = cookie(req, SESSION_COOKIE)? else {
+            return Ok(None);
+        };
+
+        let db_conn = D1Connection::new(env.d1(FORTYFIVE_DATA)?);
+        let now = now_millis()?;
+
+        let row = sqlx_d1::query!(
+            "
+            SELECT user_id, user_login, channels, csrf_token, channels_expire_at
+            FROM DashboardSessions
+            WHERE id = ?1 AND expires_at > ?2;
+            ",
+            id,
+            now
+        )
+        .fetch_optional(&db_conn)
+        .await?;
+
+        let Some(row) = row else {
+            return Ok(None);
+        };
+
+        let user_id = UserId::new(row.user_id);
+        let mut channels: Vec<Channel> = serde_json::from_str(&row.channels)?;
+        let channels_expired = row.channels_expire_at <= now;
+
+        if channels_expired {
+            channels.retain(|channel| channel.id == user_id);
+        }
+
+        Ok(Some(Self {
+            id,
+            user_id,
+            user_login: row.user_login,
+            channels,
+            channels_expired,
+            csrf_token: row.csrf_token,
+        }))
+    }
+
+    pub async fn delete(&self, env: &Env) -> BotResult<()> {
+        let db_conn = D1Connection::new(env.d1(FORTYFIVE_DATA)?);
+
+        sqlx_d1::query!("DELETE FROM DashboardSessions WHERE id = ?1;", self.id)
+            .execute(&db_conn)
+            .await?;
+
+        Ok(())
+    }
+
+    /// The channel with the given ID, if the user can manage it.
+    pub fn channel(&self, broadcaster_user_id: &str) -> Option<&Channel> {
+        self.channels
+            .iter()
+            .find(|channel| channel.id.as_str() == broadcaster_user_id)
+    }
+
+    pub fn is_valid_csrf_token(&self, token: &str) -> bool {
+        constant_time_eq::constant_time_eq(token.as_bytes(), self.csrf_token.as_bytes())
+    }
+}
+
+/// Reads a cookie from the request.
+pub fn cookie(req: &Request, name: &str) -> worker::Result<Option<String>> {
+    let Some(header) = req.headers().get("Cookie")? else {
+        return Ok(None);
+    };
+
+    Ok(header.split(';').find_map(|pair| {
+        let (key, value) = pair.trim().split_once('=')?;
+        (key == name).then(|| value.to_owned())
+    }))
+}
+
+/// A `Set-Cookie` value for the dashboard, `None` clears the cookie.
+pub fn set_cookie(name: &str, value: Option<&str>, max_age: i64) -> String {
+    let (value, max_age) = match value {
+        Some(value) => (value, max_age),
+        None => ("", 0),
+    };
+
+    format!("{name}={value}; Path=/dashboard; Max-Age={max_age}; HttpOnly; Secure; SameSite=Lax")
+}
+
+/// 32 random bytes, hex encoded.
+pub fn random_token() -> BotResult<String> {
+    let mut buf = [0u8; 32];
+    getrandom::fill(&mut buf)
+        .map_err(|e| BotError::WorkerError(worker::Error::RustError(e.to_string())))?;
+
+    Ok(hex::encode(buf))
+}
+
+fn now_millis() -> BotResult<i64> {
+    Ok(web_time::SystemTime::now()
+        .duration_since(web_time::SystemTime::UNIX_EPOCH)?
+        .as_millis()
+        .try_into()?)
+}
@@ -0,0 +1,1644 @@
+//! Queries against the !45 data in D1, shared by the chat commands and the web endpoints so the
+//! two always agree.
+
+use serde::{Deserialize, Serialize};
+use sqlx_d1::D1Connection;
+use worker::{D1Database, D1PreparedStatement, wasm_bindgen::JsValue};
+
+/// A single attempt looked up from the database.
+pub struct Record {
+    pub chatter_user_id: String,
+    pub forty_five_value: f64,
+    pub forty_five_difference: f64,
+}
+
+/// The epoch new attempts of a game in a channel are recorded in, which is the number of perfect
+/// hits plus the number of `!45 reset`s.
+pub async fn current_epoch(
+    db_conn: &D1Connection,
+    broadcaster_user_id: &str,
+    game: &str,
+) -> Result<i64, sqlx_d1::Error> {
+    let query = sqlx_d1::query!(
+        "
+        SELECT
+          (
+            SELECT COUNT(*)
+            FROM Attempts
+            WHERE forty_five_difference = 0 AND broadcaster_user_id = ?1 AND game = ?2
+          ) + (
+            SELECT COUNT(*)
+            FROM EpochResets
+            WHERE broadcaster_user_id = ?1 AND game = ?2
+          ) AS epoch;
+        ",
+        broadcaster_user_id,
+        game
+    )
+    .fetch_one(db_conn)
+    .await?;
+
+    Ok(query.epoch)
+}
+
+/// The best or worst attempt of a chatter in an epoch.
+pub async fn personal_record(
+    db_conn: &D1Connection,
+    broadcaster_user_id: &str,
+    game: &str,
+    epoch: i64,
+    chatter_user_id: &str,
+    is_worst: bool,
+) -> Result<Record, sqlx_d1::Error> {
+    sqlx_d1::query!(
+        "
+        SELECT chatter_user_id, forty_five_value, forty_five_difference
+        FROM Attempts
+        WHERE
+            Attempts.broadcaster_user_id = ?2
+            AND Attempts.game = ?3
+            AND Attempts.chatter_user_id = ?5
+            AND Attempts.epoch = ?4
+            AND Attempts.voided_by IS NULL
+        ORDER BY
+            (CASE
+                WHEN ?1 = 0 THEN
+                    +Attempts.forty_five_difference
+                ELSE
+                    -Attempts.forty_five_difference
+            END),
+            Attempts.forty_five_timestamp DESC
+        LIMIT 1;
+        ",
+        is_worst,
+        broadcaster_user_id,
+        game,
+        epoch,
+        chatter_user_id
+    )
+    .fetch_one(db_conn)
+    .await
+    .map(|row| Record {
+        chatter_user_id: row.chatter_user_id,
+        forty_five_value: row.forty_five_value,
+        forty_five_difference: row.forty_five_difference,
+    })
+}
+
+/// The best or worst attempt of an epoch. These are kept in EpochRecords
+/// so this doesn't have to go through the attempts of the epoch.
+pub async fn epoch_record(
+    db_conn: &D1Connection,
+    broadcaster_user_id: &str,
+    game: &str,
+    epoch: i64,
+    is_worst: bool,
+) -> Result<Record, sqlx_d1::Error> {
+    sqlx_d1::query!(
+        "
+        SELECT Attempts.chatter_user_id, Attempts.forty_five_value, Attempts.forty_five_difference
+        FROM EpochRecords
+        INNER JOIN Attempts ON Attempts.id = (CASE
+            WHEN ?1 = 0 THEN
+                EpochRecords.best_attempt_id
+            ELSE
+                EpochRecords.worst_attempt_id
+        END)
+        WHERE
+            EpochRecords.broadcaster_user_id = ?2
+            AND EpochRecords.game = ?3
+            AND EpochRecords.epoch = ?4
+        LIMIT 1;
+        ",
+        is_worst,
+        broadcaster_user_id,
+        game,
+        epoch
+    )
+    .fetch_one(db_conn)
+    .await
+    .map(|row| Record {
+        chatter_user_id: row.chatter_user_id,
+        forty_five_value: row.forty_five_value,
+        forty_five_difference: row.forty_five_difference,
+    })
+}
+
+/// Where a chatter's personal best (or worst) places among everyone's in
+/// an epoch, starting from 1.
+pub async fn personal_rank(
+    db_conn: &D1Connection,
+    broadcaster_user_id: &str,
+    game: &str,
+    epoch: i64,
+    chatter_user_id: &str,
+    is_worst: bool,
+) -> Result<i64, sqlx_d1::Error> {
+    let query = sqlx_d1::query!(
+        "
+        WITH Personal AS (
+            SELECT
+                chatter_user_id,
+                (CASE
+                    WHEN ?1 = 0 THEN
+                        MIN(forty_five_difference)
+                    ELSE
+                        -MAX(forty_five_difference)
+                END) AS score
+            FROM Attempts
+            WHERE broadcaster_user_id = ?2 AND game = ?3 AND epoch = ?4 AND voided_by IS NULL
+            GROUP BY chatter_user_id
+        )
+        SELECT COUNT(*) + 1 AS rank
+        FROM Personal
+        WHERE score < (SELECT score FROM Personal WHERE chatter_user_id = ?5);
+        ",
+        is_worst,
+        broadcaster_user_id,
+        game,
+        epoch,
+        chatter_user_id
+    )
+    .fetch_one(db_conn)
+    .await?;
+
+    Ok(query.rank)
+}
+
+/// Everyone's personal best (or worst) in an epoch, best first.
+pub async fn leaderboard(
+    db_conn: &D1Connection,
+    broadcaster_user_id: &str,
+    game: &str,
+    epoch: i64,
+    is_worst: bool,
+    limit: i64,
+    offset: i64,
+) -> Result<Vec<Record>, sqlx_d1::Error> {
+    let rows = sqlx_d1::query!(
+        "
+        WITH Ranked AS (
+            SELECT
+                chatter_user_id,
+                forty_five_value,
+                forty_five_difference,
+                ROW_NUMBER() OVER (
+                    PARTITION BY chatter_user_id
+                    ORDER BY
+                        (CASE
+                            WHEN ?1 = 0 THEN
+                                +forty_five_difference
+                            ELSE
+                                -forty_five_difference
+                        END),
+                        forty_five_timestamp DESC
+                ) AS position
+            FROM Attempts
+            WHERE broadcaster_user_id = ?2 AND game = ?3 AND epoch = ?4 AND voided_by IS NULL
+        )
+        SELECT chatter_user_id, forty_five_value, forty_five_difference
+        FROM Ranked
+        WHERE position = 1
+        ORDER BY
+            (CASE
+                WHEN ?1 = 0 THEN
+                    +forty_five_difference
+                ELSE
+                    -forty_five_difference
+            END),
+            chatter_user_id
+        LIMIT ?5 OFFSET ?6;
+        ",
+        is_worst,
+        broadcaster_user_id,
+        game,
+        epoch,
+        limit,
+        offset
+    )
+    .fetch_all(db_conn)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| Record {
+            chatter_user_id: row.chatter_user_id,
+            forty_five_value: row.forty_five_value,
+            forty_five_difference: row.forty_five_difference,
+        })
+        .collect())
+}
+
+/// A perfect hit, which ended its epoch.
+pub struct HallOfFameEntry {
+    pub chatter_user_id: String,
+    pub epoch: i64,
+    /// Milliseconds since the Unix epoch.
+    pub forty_five_timestamp: i64,
+}
+
+/// Perfect hits of a game in a channel, latest first. With `epoch` set, only the perfect hit that
+/// ended that epoch.
+pub async fn hall_of_fame(
+    db_conn: &D1Connection,
+    broadcaster_user_id: &str,
+    game: &str,
+    epoch: Option<i64>,
+    limit: i64,
+    offset: i64,
+) -> Result<Vec<HallOfFameEntry>, sqlx_d1::Error> {
+    let rows = sqlx_d1::query!(
+        "
+        SELECT
+          chatter_user_id,
+          epoch,
+          forty_five_timestamp
+        FROM Attempts
+        WHERE
+          forty_five_difference = 0
+          AND broadcaster_user_id = ?1
+          AND game = ?4
+          AND (?2 = 0 OR epoch = ?3)
+          AND voided_by IS NULL
+        ORDER BY
+          epoch DESC
+        LIMIT ?5 OFFSET ?6
+        ",
+        broadcaster_user_id,
+        epoch.is_some(),
+        epoch,
+        game,
+        limit,
+        offset
+    )
+    .fetch_all(db_conn)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| HallOfFameEntry {
+            chatter_user_id: row.chatter_user_id,
+            epoch: row.epoch,
+            forty_five_timestamp: row.forty_five_timestamp,
+        })
+        .collect())
+}
+
+/// Counts of attempts, optionally narrowed down to an epoch or a chatter.
+pub struct Stats {
+    pub attempts: i64,
+    pub chatters: i64,
+    pub perfects: i64,
+    /// Attempts granted for cheering or subscribing, see [`crate::bonus`].
+    pub bonus_attempts: i64,
+}
+
+pub async fn stats(
+    db_conn: &D1Connection,
+    broadcaster_user_id: &str,
+    game: &str,
+    epoch: Option<i64>,
+    chatter_user_id: Option<&str>,
+) -> Result<Stats, sqlx_d1::Error> {
+    let query = sqlx_d1::query!(
+        "
+        SELECT
+          COUNT(*) AS attempts,
+          COUNT(DISTINCT chatter_user_id) AS chatters,
+          COALESCE(SUM(forty_five_difference = 0), 0) AS perfects,
+          COALESCE(SUM(source IN ('cheer', 'subscription')), 0) AS bonus_attempts
+        FROM Attempts
+        WHERE
+          broadcaster_user_id = ?1
+          AND game = ?2
+          AND (?3 = 0 OR epoch = ?4)
+          AND (?5 = 0 OR chatter_user_id = ?6)
+          AND voided_by IS NULL;
+        ",
+        broadcaster_user_id,
+        game,
+        epoch.is_some(),
+        epoch,
+        chatter_user_id.is_some(),
+        chatter_user_id
+    )
+    .fetch_one(db_conn)
+    .await?;
+
+    Ok(Stats {
+        attempts: query.attempts,
+        chatters: query.chatters,
+        perfects: query.perfects,
+        bonus_attempts: query.bonus_attempts,
+    })
+}
+
+/// Turns a missing row into `None`, for lookups where nothing being there isn't an error.
+pub fn optional<T>(result: Result<T, sqlx_d1::Error>) -> Result<Option<T>, sqlx_d1::Error> {
+    match result {
+        Ok(row) => Ok(Some(row)),
+        Err(sqlx_d1::Error::RowNotFound) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// The best and worst difference of the current epoch, `None` before its first attempt.
+pub async fn current_extremes(
+    db_conn: &D1Connection,
+    broadcaster_user_id: &str,
+    game: &str,
+) -> Result<Option<(f64, f64)>, sqlx_d1::Error> {
+    let query = sqlx_d1::query!(
+        "
+        SELECT best_difference, worst_difference
+        FROM EpochRecords
+        WHERE
+            broadcaster_user_id = ?1
+            AND game = ?2
+            AND epoch = (
+                SELECT COUNT(*)
+                FROM Attempts
+                WHERE forty_five_difference = 0 AND broadcaster_user_id = ?1 AND game = ?2
+            ) + (
+                SELECT COUNT(*)
+                FROM EpochResets
+                WHERE broadcaster_user_id = ?1 AND game = ?2
+            );
+        ",
+        broadcaster_user_id,
+        game
+    )
+    .fetch_optional(db_conn)
+    .await?;
+
+    Ok(query.map(|row| (row.best_difference, row.worst_difference)))
+}
+
+/// What an attempt was played through, stored in Attempts.source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttemptSource {
+    /// `!45` in chat.
+    Chat,
+    /// A channel point redemption, see [`crate::redemptions`].
+    ChannelPoints,
+    /// A bonus roll for cheering, see [`crate::bonus`].
+    Cheer,
+    /// A bonus roll for subscribing or resubscribing.
+    Subscription,
+    /// Imported from another bot, see [`crate::import`].
+    Import,
+}
+
+impl AttemptSource {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Chat => "chat",
+            Self::ChannelPoints => "channel_points",
+            Self::Cheer => "cheer",
+            Self::Subscription => "subscription",
+            Self::Import => "import",
+        }
+    }
+}
+
+/// An attempt as stored.
+pub struct Attempt {
+    pub id: i64,
+    pub epoch: i64,
+    pub game: String,
+    pub chatter_user_id: String,
+    pub forty_five_value: f64,
+    pub forty_five_difference: f64,
+    /// Milliseconds since the Unix epoch.
+    pub forty_five_timestamp: i64,
+}
+
+/// Attempts of a channel with an ID greater than `after`, oldest first, leaving out voided ones.
+/// Paging by ID rather than an offset keeps every page equally fast.
+pub async fn attempts_after(
+    db_conn: &D1Connection,
+    broadcaster_user_id: &str,
+    after: i64,
+    limit: i64,
+) -> Result<Vec<Attempt>, sqlx_d1::Error> {
+    let rows = sqlx_d1::query!(
+        "
+        SELECT
+          id,
+          epoch,
+          game,
+          chatter_user_id,
+          forty_five_value,
+          forty_five_difference,
+          forty_five_timestamp
+        FROM Attempts
+        WHERE broadcaster_user_id = ?1 AND id > ?2 AND voided_by IS NULL
+        ORDER BY id
+        LIMIT ?3;
+        ",
+        broadcaster_user_id,
+        after,
+        limit
+    )
+    .fetch_all(db_conn)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| Attempt {
+            id: row.id,
+            epoch: row.epoch,
+            game: row.game,
+            chatter_user_id: row.chatter_user_id,
+            forty_five_value: row.forty_five_value,
+            forty_five_difference: row.forty_five_difference,
+            forty_five_timestamp: row.forty_five_timestamp,
+        })
+        .collect())
+}
+
+/// Existing attempts of a game between two timestamps, as `(chatter_user_id, timestamp)`, to tell
+/// imported attempts that are already there apart.
+pub async fn attempt_keys_between(
+    db_conn: &D1Connection,
+    broadcaster_user_id: &str,
+    game: &str,
+    from: i64,
+    to: i64,
+) -> Result<Vec<(String, i64)>, sqlx_d1::Error> {
+    let rows = sqlx_d1::query!(
+        "
+        SELECT chatter_user_id, forty_five_timestamp
+        FROM Attempts
+        WHERE
+          broadcaster_user_id = ?1
+          AND game = ?2
+          AND forty_five_timestamp BETWEEN ?3 AND ?4;
+        ",
+        broadcaster_user_id,
+        game,
+        from,
+        to
+    )
+    .fetch_all(db_conn)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| (row.chatter_user_id, row.forty_five_timestamp))
+        .collect())
+}
+
+/// An attempt that didn't go through `!45`, such as an imported one.
+pub struct NewAttempt {
+    pub chatter_user_id: String,
+    pub forty_five_value: f64,
+    pub forty_five_difference: f64,
+    pub forty_five_timestamp: i64,
+}
+
+/// D1 allows at most 100 bound parameters per query, and every attempt takes 7.
+const INSERT_BATCH: usize = 14;
+
+// Epochs are the perfect hits before an attempt plus the resets up to it,
+// ordered by time since imported attempts can be older than stored ones.
+const RENUMBER_ATTEMPTS: &str = "
+    WITH Numbered AS (
+        SELECT
+            id,
+            COALESCE(SUM(forty_five_difference = 0) OVER (
+                ORDER BY forty_five_timestamp, id
+                ROWS BETWEEN UNBOUNDED PRECEDING AND 1 PRECEDING
+            ), 0) + (
+                SELECT COUNT(*)
+                FROM EpochResets
+                WHERE
+                    EpochResets.broadcaster_user_id = ?1
+                    AND EpochResets.game = ?2
+                    AND EpochResets.reset_at <= Attempts.forty_five_timestamp
+            ) AS epoch
+        FROM Attempts
+        WHERE broadcaster_user_id = ?1 AND game = ?2
+    )
+    UPDATE Attempts
+    SET epoch = Numbered.epoch
+    FROM Numbered
+    WHERE Attempts.id = Numbered.id AND Attempts.epoch != Numbered.epoch;
+    ";
+
+// The epoch a reset ended is the one of attempts right before it, counted
+// the same way.
+const RENUMBER_EPOCH_RESETS: &str = "
+    UPDATE EpochResets
+    SET epoch = (
+        SELECT COUNT(*)
+        FROM Attempts
+        WHERE
+            Attempts.broadcaster_user_id = ?1
+            AND Attempts.game = ?2
+            AND Attempts.forty_five_difference = 0
+            AND Attempts.forty_five_timestamp < EpochResets.reset_at
+    ) + (
+        SELECT COUNT(*)
+        FROM EpochResets Earlier
+        WHERE
+            Earlier.broadcaster_user_id = ?1
+            AND Earlier.game = ?2
+            AND (
+                Earlier.reset_at < EpochResets.reset_at
+                OR (Earlier.reset_at = EpochResets.reset_at AND Earlier.id < EpochResets.id)
+            )
+    )
+    WHERE broadcaster_user_id = ?1 AND game = ?2;
+    ";
+
+/// Inserts attempts of a game in a channel that didn't go through `!45`, renumbers the epochs of
+/// the game by time and rebuilds its EpochRecords. Everything is one batch, so a failed import
+/// leaves the data as it was.
+pub async fn import_attempts(
+    db: &D1Database,
+    broadcaster_user_id: &str,
+    game: &str,
+    attempts: &[NewAttempt],
+) -> worker::Result<()> {
+    let mut statements = Vec::new();
+
+    for chunk in attempts.chunks(INSERT_BATCH) {
+        // Inserted with a placeholder epoch, renumbered right after.
+        let values = vec!["(0, ?, ?, ?, ?, ?, ?, ?)"; chunk.len()].join(", ");
+        let sql = format!(
+            "
+            INSERT INTO Attempts (epoch, broadcaster_user_id, game, chatter_user_id, forty_five_value, forty_five_difference, forty_five_timestamp, source)
+            VALUES {values};
+            "
+        );
+
+        let mut params = Vec::with_capacity(chunk.len() * 7);
+
+        for attempt in chunk {
+            params.extend([
+                broadcaster_user_id.into(),
+                game.into(),
+                attempt.chatter_user_id.as_str().into(),
+                JsValue::from_f64(attempt.forty_five_value),
+                JsValue::from_f64(attempt.forty_five_difference),
+                JsValue::from_f64(attempt.forty_five_timestamp as f64),
+                AttemptSource::Import.name().into(),
+            ]);
+        }
+
+        statements.push(db.prepare(&sql).bind(&params)?);
+    }
+
+    for sql in [RENUMBER_ATTEMPTS, RENUMBER_EPOCH_RESETS] {
+        statements.push(
+            db.prepare(sql)
+                .bind(&[broadcaster_user_id.into(), game.into()])?,
+        );
+    }
+
+    statements.extend(rebuild_epoch_records_statements(
+        db,
+        broadcaster_user_id,
+        game,
+    )?);
+
+    batch(db, statements).await?;
+
+    Ok(())
+}
+
+/// How many attempts of a game in a channel are later than a timestamp, which are the ones whose
+/// epoch goes up when a perfect hit from that time is imported.
+pub async fn count_attempts_after(
+    db_conn: &D1Connection,
+    broadcaster_user_id: &str,
+    game: &str,
+    timestamp: i64,
+) -> Result<i64, sqlx_d1::Error> {
+    let query = sqlx_d1::query!(
+        "
+        SELECT COUNT(*) AS attempts
+        FROM Attempts
+        WHERE broadcaster_user_id = ?1 AND game = ?2 AND forty_five_timestamp > ?3;
+        ",
+        broadcaster_user_id,
+        game,
+        timestamp
+    )
+    .fetch_one(db_conn)
+    .await?;
+
+    Ok(query.attempts)
+}
+
+const DELETE_EPOCH_RECORDS: &str =
+    "DELETE FROM EpochRecords WHERE broadcaster_user_id = ?1 AND game = ?2;";
+
+// Ties are won by the latest attempt, same as the trigger keeping
+// EpochRecords up to date.
+const INSERT_EPOCH_RECORDS: &str = "
+    INSERT INTO EpochRecords
+    SELECT
+        a.broadcaster_user_id,
+        a.game,
+        a.epoch,
+        (
+            SELECT b.id FROM Attempts b
+            WHERE b.broadcaster_user_id = a.broadcaster_user_id AND b.game = a.game AND b.epoch = a.epoch AND b.voided_by IS NULL
+            ORDER BY b.forty_five_difference ASC, b.forty_five_timestamp DESC, b.id DESC
+            LIMIT 1
+        ),
+        MIN(a.forty_five_difference),
+        (
+            SELECT w.id FROM Attempts w
+            WHERE w.broadcaster_user_id = a.broadcaster_user_id AND w.game = a.game AND w.epoch = a.epoch AND w.voided_by IS NULL
+            ORDER BY w.forty_five_difference DESC, w.forty_five_timestamp DESC, w.id DESC
+            LIMIT 1
+        ),
+        MAX(a.forty_five_difference)
+    FROM Attempts a
+    WHERE a.broadcaster_user_id = ?1 AND a.game = ?2 AND a.voided_by IS NULL
+    GROUP BY a.broadcaster_user_id, a.game, a.epoch;
+    ";
+
+/// Rebuilds the EpochRecords of a game in a channel from its attempts that aren't voided, for when
+/// attempts are changed or removed rather than inserted.
+pub async fn rebuild_epoch_records(
+    db_conn: &D1Connection,
+    broadcaster_user_id: &str,
+    game: &str,
+) -> Result<(), sqlx_d1::Error> {
+    for sql in [DELETE_EPOCH_RECORDS, INSERT_EPOCH_RECORDS] {
+        sqlx_d1::query(sql)
+            .bind(broadcaster_user_id)
+            .bind(game)
+            .execute(db_conn)
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// The statements of [`rebuild_epoch_records`], for running them in a [`batch`].
+fn rebuild_epoch_records_statements(
+    db: &D1Database,
+    broadcaster_user_id: &str,
+    game: &str,
+) -> worker::Result<Vec<D1PreparedStatement>> {
+    [DELETE_EPOCH_RECORDS, INSERT_EPOCH_RECORDS]
+        .into_iter()
+        .map(|sql| {
+            db.prepare(sql)
+                .bind(&[broadcaster_user_id.into(), game.into()])
+        })
+        .collect()
+}
+
+/// Runs statements as one D1 batch, which either applies all of them or none. Returns how many
+/// rows each statement changed.
+async fn batch(
+    db: &D1Database,
+    statements: Vec<D1PreparedStatement>,
+) -> worker::Result<Vec<usize>> {
+    db.batch(statements)
+        .await?
+        .iter()
+        .map(|result| Ok(result.meta()?.and_then(|meta| meta.changes).unwrap_or(0)))
+        .collect()
+}
+
+/// Whether a chatter opted out of being recorded.
+pub async fn is_opted_out(
+    db_conn: &D1Connection,
+    chatter_user_id: &str,
+) -> Result<bool, sqlx_d1::Error> {
+    let row = optional(
+        sqlx_d1::query!(
+            "SELECT chatter_user_id FROM OptOuts WHERE chatter_user_id = ?1;",
+            chatter_user_id
+        )
+        .fetch_one(db_conn)
+        .await,
+    )?;
+
+    Ok(row.is_some())
+}
+
+pub async fn opt_out(
+    db_conn: &D1Connection,
+    chatter_user_id: &str,
+    timestamp: i64,
+) -> Result<(), sqlx_d1::Error> {
+    sqlx_d1::query!(
+        "INSERT INTO OptOuts (chatter_user_id, opted_out_at) VALUES (?1, ?2) ON CONFLICT DO NOTHING;",
+        chatter_user_id,
+        timestamp
+    )
+    .execute(db_conn)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn opt_in(db_conn: &D1Connection, chatter_user_id: &str) -> Result<(), sqlx_d1::Error> {
+    sqlx_d1::query!(
+        "DELETE FROM OptOuts WHERE chatter_user_id = ?1;",
+        chatter_user_id
+    )
+    .execute(db_conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Who perfect hits of purged users are attributed to. Twitch has no user with this ID, so it
+/// shows up the same way as users that deleted their account.
+pub const DELETED_USER_ID: &str = "0";
+
+/// What [`purge_user`] removed.
+#[derive(Debug, Serialize)]
+pub struct Purge {
+    pub deleted: usize,
+    /// Perfect hits that were kept without the user, since epochs are counted from them.
+    pub anonymized: usize,
+    pub channels: usize,
+}
+
+/// A game a purged user played in.
+#[derive(Deserialize)]
+struct PlayedGame {
+    broadcaster_user_id: String,
+    game: String,
+}
+
+/// Removes every attempt of a user in every channel. Perfect hits are attributed to
+/// [`DELETED_USER_ID`] instead, so that the epochs of the channels stay the same, and the records
+/// of the affected games are rebuilt without the user. Achievements and dashboard sessions of the
+/// user are removed as well, and voids and resets the user made as a moderator are attributed to
+/// [`DELETED_USER_ID`]. The removal is one batch, so a failed purge leaves the data as it was, and
+/// records still pointing at a removed attempt afterwards are rebuilt in a second one.
+pub async fn purge_user(db: &D1Database, chatter_user_id: &str) -> worker::Result<Purge> {
+    let games = db
+        .prepare(
+            "SELECT DISTINCT broadcaster_user_id, game FROM Attempts WHERE chatter_user_id = ?1;",
+        )
+        .bind(&[chatter_user_id.into()])?
+        .all()
+        .await?
+        .results::<PlayedGame>()?;
+
+    let mut statements = vec![
+        db.prepare(
+            "
+            UPDATE Attempts
+            SET chatter_user_id = ?2
+            WHERE chatter_user_id = ?1 AND forty_five_difference = 0;
+            ",
+        )
+        .bind(&[chatter_user_id.into(), DELETED_USER_ID.into()])?,
+        db.prepare("DELETE FROM Attempts WHERE chatter_user_id = ?1;")
+            .bind(&[chatter_user_id.into()])?,
+        db.prepare("DELETE FROM Achievements WHERE chatter_user_id = ?1;")
+            .bind(&[chatter_user_id.into()])?,
+        db.prepare("UPDATE Attempts SET voided_by = ?2 WHERE voided_by = ?1;")
+            .bind(&[chatter_user_id.into(), DELETED_USER_ID.into()])?,
+        db.prepare("UPDATE EpochResets SET moderator_user_id = ?2 WHERE moderator_user_id = ?1;")
+            .bind(&[chatter_user_id.into(), DELETED_USER_ID.into()])?,
+    ];
+
+    for game in &games {
+        statements.extend(rebuild_epoch_records_statements(
+            db,
+            &game.broadcaster_user_id,
+            &game.game,
+        )?);
+    }
+
+    statements.push(
+        db.prepare("DELETE FROM DashboardSessions WHERE user_id = ?1;")
+            .bind(&[chatter_user_id.into()])?,
+    );
+
+    let changes = batch(db, statements).await?;
+
+    // An attempt made between reading the games and the batch can be in a game that wasn't read,
+    // which leaves records pointing at the deleted attempt.
+    let stale_games = db
+        .prepare(
+            "
+            SELECT DISTINCT r.broadcaster_user_id, r.game
+            FROM EpochRecords r
+            WHERE NOT EXISTS (SELECT 1 FROM Attempts WHERE id = r.best_attempt_id)
+            OR NOT EXISTS (SELECT 1 FROM Attempts WHERE id = r.worst_attempt_id);
+            ",
+        )
+        .all()
+        .await?
+        .results::<PlayedGame>()?;
+
+    if !stale_games.is_empty() {
+        let mut statements = Vec::new();
+        for game in &stale_games {
+            statements.extend(rebuild_epoch_records_statements(
+                db,
+                &game.broadcaster_user_id,
+                &game.game,
+            )?);
+        }
+        batch(db, statements).await?;
+    }
+
+    let mut channels = games
+        .iter()
+        .chain(&stale_games)
+        .map(|game| game.broadcaster_user_id.as_str())
+        .collect::<Vec<_>>();
+    channels.sort_unstable();
+    channels.dedup();
+
+    Ok(Purge {
+        deleted: changes[1],
+        anonymized: changes[0],
+        channels: channels.len(),
+    })
+}
+
+/// An attempt voided by [`void_attempt`] or [`void_last_attempt`].
+pub struct Voided {
+    pub id: i64,
+    pub game: String,
+    pub chatter_user_id: String,
+    pub forty_five_value: f64,
+}
+
+/// Voids an attempt of a channel by its ID, takes back the achievements it unlocked and rebuilds
+/// the records of its game. `None` if the channel has no such attempt, or it's already voided.
+pub async fn void_attempt(
+    db_conn: &D1Connection,
+    broadcaster_user_id: &str,
+    attempt_id: i64,
+    moderator_user_id: &str,
+) -> Result<Option<Voided>, sqlx_d1::Error> {
+    let row = sqlx_d1::query!(
+        "
+        UPDATE Attempts
+        SET voided_by = ?3
+        WHERE id = ?2 AND broadcaster_user_id = ?1 AND voided_by IS NULL
+        RETURNING id, game, chatter_user_id, forty_five_value;
+        ",
+        broadcaster_user_id,
+        attempt_id,
+        moderator_user_id
+    )
+    .fetch_optional(db_conn)
+    .await?;
+
+    let Some(row) = row else {
+        return Ok(None);
+    };
+
+    revoke_achievements(
+        db_conn,
+        broadcaster_user_id,
+        &row.game,
+        &row.chatter_user_id,
+        row.id,
+    )
+    .await?;
+    rebuild_epoch_records(db_conn, broadcaster_user_id, &row.game).await?;
+
+    Ok(Some(Voided {
+        id: row.id,
+        game: row.game,
+        chatter_user_id: row.chatter_user_id,
+        forty_five_value: row.forty_five_value,
+    }))
+}
+
+/// Voids the latest attempt of a chatter in a game that isn't voided yet, like [`void_attempt`].
+pub async fn void_last_attempt(
+    db_conn: &D1Connection,
+    broadcaster_user_id: &str,
+    game: &str,
+    chatter_user_id: &str,
+    moderator_user_id: &str,
+) -> Result<Option<Voided>, sqlx_d1::Error> {
+    let row = sqlx_d1::query!(
+        "
+        UPDATE Attempts
+        SET voided_by = ?4
+        WHERE id = (
+            SELECT id
+            FROM Attempts
+            WHERE
+                broadcaster_user_id = ?1
+                AND game = ?2
+                AND chatter_user_id = ?3
+                AND voided_by IS NULL
+            ORDER BY forty_five_timestamp DESC, id DESC
+            LIMIT 1
+        )
+        RETURNING id, game, chatter_user_id, forty_five_value;
+        ",
+        broadcaster_user_id,
+        game,
+        chatter_user_id,
+        moderator_user_id
+    )
+    .fetch_optional(db_conn)
+    .await?;
+
+    let Some(row) = row else {
+        return Ok(None);
+    };
+
+    revoke_achievements(db_conn, broadcaster_user_id, game, chatter_user_id, row.id).await?;
+    rebuild_epoch_records(db_conn, broadcaster_user_id, game).await?;
+
+    Ok(Some(Voided {
+        id: row.id,
+        game: row.game,
+        chatter_user_id: row.chatter_user_id,
+        forty_five_value: row.forty_five_value,
+    }))
+}
+
+/// Ends the current epoch of a game without a perfect hit, returning the epoch that starts.
+pub async fn reset_epoch(
+    db_conn: &D1Connection,
+    broadcaster_user_id: &str,
+    game: &str,
+    moderator_user_id: &str,
+    timestamp: i64,
+) -> Result<i64, sqlx_d1::Error> {
+    let epoch = current_epoch(db_conn, broadcaster_user_id, game).await?;
+
+    sqlx_d1::query!(
+        "
+        INSERT INTO EpochResets (broadcaster_user_id, game, epoch, moderator_user_id, reset_at)
+        VALUES (?1, ?2, ?3, ?4, ?5);
+        ",
+        broadcaster_user_id,
+        game,
+        epoch,
+        moderator_user_id,
+        timestamp
+    )
+    .execute(db_conn)
+    .await?;
+
+    Ok(epoch + 1)
+}
+
+/// The best or worst attempt between two timestamps regardless of epochs, optionally of a single
+/// chatter. `from` is inclusive and `to` exclusive, both in milliseconds since the Unix epoch.
+pub async fn period_record(
+    db_conn: &D1Connection,
+    broadcaster_user_id: &str,
+    game: &str,
+    from: i64,
+    to: i64,
+    chatter_user_id: Option<&str>,
+    is_worst: bool,
+) -> Result<Record, sqlx_d1::Error> {
+    sqlx_d1::query!(
+        "
+        SELECT chatter_user_id, forty_five_value, forty_five_difference
+        FROM Attempts
+        WHERE
+            broadcaster_user_id = ?2
+            AND game = ?3
+            AND forty_five_timestamp >= ?4
+            AND forty_five_timestamp < ?5
+            AND (?6 = 0 OR chatter_user_id = ?7)
+            AND voided_by IS NULL
+        ORDER BY
+            (CASE
+                WHEN ?1 = 0 THEN
+                    +forty_five_difference
+                ELSE
+                    -forty_five_difference
+            END),
+            forty_five_timestamp DESC
+        LIMIT 1;
+        ",
+        is_worst,
+        broadcaster_user_id,
+        game,
+        from,
+        to,
+        chatter_user_id.is_some(),
+        chatter_user_id
+    )
+    .fetch_one(db_conn)
+    .await
+    .map(|row| Record {
+        chatter_user_id: row.chatter_user_id,
+        forty_five_value: row.forty_five_value,
+        forty_five_difference: row.forty_five_difference,
+    })
+}
+
+/// Like [`personal_rank`], but between two timestamps instead of in an epoch.
+pub async fn period_rank(
+    db_conn: &D1Connection,
+    broadcaster_user_id: &str,
+    game: &str,
+    from: i64,
+    to: i64,
+    chatter_user_id: &str,
+    is_worst: bool,
+) -> Result<i64, sqlx_d1::Error> {
+    let query = sqlx_d1::query!(
+        "
+        WITH Personal AS (
+            SELECT
+                chatter_user_id,
+                (CASE
+                    WHEN ?1 = 0 THEN
+                        MIN(forty_five_difference)
+                    ELSE
+                        -MAX(forty_five_difference)
+                END) AS score
+            FROM Attempts
+            WHERE
+                broadcaster_user_id = ?2
+                AND game = ?3
+                AND forty_five_timestamp >= ?4
+                AND forty_five_timestamp < ?5
+                AND voided_by IS NULL
+            GROUP BY chatter_user_id
+        )
+        SELECT COUNT(*) + 1 AS rank
+        FROM Personal
+        WHERE score < (SELECT score FROM Personal WHERE chatter_user_id = ?6);
+        ",
+        is_worst,
+        broadcaster_user_id,
+        game,
+        from,
+        to,
+        chatter_user_id
+    )
+    .fetch_one(db_conn)
+    .await?;
+
+    Ok(query.rank)
+}
+
+/// Like [`period_record`], but of the attempts made during a stream.
+pub async fn stream_record(
+    db_conn: &D1Connection,
+    broadcaster_user_id: &str,
+    game: &str,
+    stream_id: &str,
+    chatter_user_id: Option<&str>,
+    is_worst: bool,
+) -> Result<Record, sqlx_d1::Error> {
+    sqlx_d1::query!(
+        "
+        SELECT chatter_user_id, forty_five_value, forty_five_difference
+        FROM Attempts
+        WHERE
+            broadcaster_user_id = ?2
+            AND game = ?3
+            AND stream_id = ?4
+            AND (?5 = 0 OR chatter_user_id = ?6)
+            AND voided_by IS NULL
+        ORDER BY
+            (CASE
+                WHEN ?1 = 0 THEN
+                    +forty_five_difference
+                ELSE
+                    -forty_five_difference
+            END),
+            forty_five_timestamp DESC
+        LIMIT 1;
+        ",
+        is_worst,
+        broadcaster_user_id,
+        game,
+        stream_id,
+        chatter_user_id.is_some(),
+        chatter_user_id
+    )
+    .fetch_one(db_conn)
+    .await
+    .map(|row| Record {
+        chatter_user_id: row.chatter_user_id,
+        forty_five_value: row.forty_five_value,
+        forty_five_difference: row.forty_five_difference,
+    })
+}
+
+/// Like [`personal_rank`], but among the attempts made during a stream.
+pub async fn stream_rank(
+    db_conn: &D1Connection,
+    broadcaster_user_id: &str,
+    game: &str,
+    stream_id: &str,
+    chatter_user_id: &str,
+    is_worst: bool,
+) -> Result<i64, sqlx_d1::Error> {
+    let query = sqlx_d1::query!(
+        "
+        WITH Personal AS (
+            SELECT
+                chatter_user_id,
+                (CASE
+                    WHEN ?1 = 0 THEN
+                        MIN(forty_five_difference)
+                    ELSE
+                        -MAX(forty_five_difference)
+                END) AS score
+            FROM Attempts
+            WHERE
+                broadcaster_user_id = ?2
+                AND game = ?3
+                AND stream_id = ?4
+                AND voided_by IS NULL
+            GROUP BY chatter_user_id
+        )
+        SELECT COUNT(*) + 1 AS rank
+        FROM Personal
+        WHERE score < (SELECT score FROM Personal WHERE chatter_user_id = ?5);
+        ",
+        is_worst,
+        broadcaster_user_id,
+        game,
+        stream_id,
+        chatter_user_id
+    )
+    .fetch_one(db_conn)
+    .await?;
+
+    Ok(query.rank)
+}
+
+/// A season of a channel, covering every game from `starts_at` up to but not including `ends_at`.
+pub struct Season {
+    pub id: i64,
+    pub broadcaster_user_id: String,
+    pub name: String,
+    pub starts_at: i64,
+    pub ends_at: i64,
+}
+
+/// The season running at `now`, seasons of a channel don't overlap.
+pub async fn current_season(
+    db_conn: &D1Connection,
+    broadcaster_user_id: &str,
+    now: i64,
+) -> Result<Option<Season>, sqlx_d1::Error> {
+    let row = sqlx_d1::query!(
+        "
+        SELECT id, broadcaster_user_id, name, starts_at, ends_at
+        FROM Seasons
+        WHERE broadcaster_user_id = ?1 AND starts_at <= ?2 AND ends_at > ?2
+        LIMIT 1;
+        ",
+        broadcaster_user_id,
+        now
+    )
+    .fetch_optional(db_conn)
+    .await?;
+
+    Ok(row.map(|row| Season {
+        id: row.id,
+        broadcaster_user_id: row.broadcaster_user_id,
+        name: row.name,
+        starts_at: row.starts_at,
+        ends_at: row.ends_at,
+    }))
+}
+
+/// A season of the channel that overlaps the given span of time, if any.
+pub async fn overlapping_season(
+    db_conn: &D1Connection,
+    broadcaster_user_id: &str,
+    starts_at: i64,
+    ends_at: i64,
+) -> Result<Option<Season>, sqlx_d1::Error> {
+    let row = sqlx_d1::query!(
+        "
+        SELECT id, broadcaster_user_id, name, starts_at, ends_at
+        FROM Seasons
+        WHERE broadcaster_user_id = ?1 AND starts_at < ?3 AND ends_at > ?2
+        LIMIT 1;
+        ",
+        broadcaster_user_id,
+        starts_at,
+        ends_at
+    )
+    .fetch_optional(db_conn)
+    .await?;
+
+    Ok(row.map(|row| Season {
+        id: row.id,
+        broadcaster_user_id: row.broadcaster_user_id,
+        name: row.name,
+        starts_at: row.starts_at,
+        ends_at: row.ends_at,
+    }))
+}
+
+/// Adds a season, `false` if the channel already has one with the name.
+pub async fn add_season(
+    db_conn: &D1Connection,
+    broadcaster_user_id: &str,
+    name: &str,
+    starts_at: i64,
+    ends_at: i64,
+) -> Result<bool, sqlx_d1::Error> {
+    let row = sqlx_d1::query!(
+        "
+        INSERT INTO Seasons (broadcaster_user_id, name, starts_at, ends_at)
+        VALUES (?1, ?2, ?3, ?4)
+        ON CONFLICT DO NOTHING
+        RETURNING id;
+        ",
+        broadcaster_user_id,
+        name,
+        starts_at,
+        ends_at
+    )
+    .fetch_optional(db_conn)
+    .await?;
+
+    Ok(row.is_some())
+}
+
+/// Deletes a season by its name, `false` if the channel has none with the name.
+pub async fn delete_season(
+    db_conn: &D1Connection,
+    broadcaster_user_id: &str,
+    name: &str,
+) -> Result<bool, sqlx_d1::Error> {
+    let row = sqlx_d1::query!(
+        "DELETE FROM Seasons WHERE broadcaster_user_id = ?1 AND name = ?2 RETURNING id;",
+        broadcaster_user_id,
+        name
+    )
+    .fetch_optional(db_conn)
+    .await?;
+
+    Ok(row.is_some())
+}
+
+/// Seasons of every channel that ended before `now` and weren't closed yet.
+pub async fn seasons_to_close(
+    db_conn: &D1Connection,
+    now: i64,
+) -> Result<Vec<Season>, sqlx_d1::Error> {
+    let rows = sqlx_d1::query!(
+        "
+        SELECT id, broadcaster_user_id, name, starts_at, ends_at
+        FROM Seasons
+        WHERE closed_at IS NULL AND ends_at <= ?1
+        ORDER BY ends_at;
+        ",
+        now
+    )
+    .fetch_all(db_conn)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| Season {
+            id: row.id,
+            broadcaster_user_id: row.broadcaster_user_id,
+            name: row.name,
+            starts_at: row.starts_at,
+            ends_at: row.ends_at,
+        })
+        .collect())
+}
+
+pub async fn close_season(
+    db_conn: &D1Connection,
+    season_id: i64,
+    now: i64,
+) -> Result<(), sqlx_d1::Error> {
+    sqlx_d1::query!(
+        "UPDATE Seasons SET closed_at = ?2 WHERE id = ?1;",
+        season_id,
+        now
+    )
+    .execute(db_conn)
+    .await?;
+
+    Ok(())
+}
+
+/// What achievements are decided on after an attempt, see [`crate::achievements`].
+pub struct AchievementFacts {
+    /// Attempts of the chatter in the game, including this one.
+    pub attempts: i64,
+    /// How many of the chatter's latest `streak` attempts are within `threshold` of the target.
+    pub close_attempts: i64,
+    /// Attempts of anyone in the game since `day_start`, before this one.
+    pub earlier_today: i64,
+    /// The chatter's best difference in the epoch before this attempt.
+    pub previous_best: Option<f64>,
+    /// Names of the achievements the chatter already has in the game.
+    pub unlocked: Vec<String>,
+}
+
+pub async fn achievement_facts(
+    db_conn: &D1Connection,
+    broadcaster_user_id: &str,
+    attempt: &Attempt,
+    day_start: i64,
+    streak: i64,
+    threshold: f64,
+) -> Result<AchievementFacts, sqlx_d1::Error> {
+    let query = sqlx_d1::query!(
+        "
+        SELECT
+          (
+            SELECT COUNT(*)
+            FROM Attempts
+            WHERE broadcaster_user_id = ?1 AND game = ?2 AND chatter_user_id = ?3 AND voided_by IS NULL
+          ) AS attempts,
+          (
+            SELECT COUNT(*)
+            FROM (
+              SELECT forty_five_difference
+              FROM Attempts
+              WHERE broadcaster_user_id = ?1 AND game = ?2 AND chatter_user_id = ?3 AND voided_by IS NULL
+              ORDER BY id DESC
+              LIMIT ?7
+            )
+            WHERE forty_five_difference <= ?8
+          ) AS close_attempts,
+          (
+            SELECT COUNT(*)
+            FROM Attempts
+            WHERE
+              broadcaster_user_id = ?1
+              AND game = ?2
+              AND forty_five_timestamp >= ?6
+              AND id < ?4
+              AND voided_by IS NULL
+          ) AS earlier_today,
+          (
+            SELECT COALESCE(MIN(forty_five_difference), -1)
+            FROM Attempts
+            WHERE
+              broadcaster_user_id = ?1
+              AND game = ?2
+              AND chatter_user_id = ?3
+              AND epoch = ?5
+              AND id != ?4
+              AND voided_by IS NULL
+          ) AS previous_best,
+          (
+            SELECT COALESCE(GROUP_CONCAT(achievement), '')
+            FROM Achievements
+            WHERE broadcaster_user_id = ?1 AND game = ?2 AND chatter_user_id = ?3
+          ) AS unlocked;
+        ",
+        broadcaster_user_id,
+        attempt.game,
+        attempt.chatter_user_id,
+        attempt.id,
+        attempt.epoch,
+        day_start,
+        streak,
+        threshold
+    )
+    .fetch_one(db_conn)
+    .await?;
+
+    Ok(AchievementFacts {
+        attempts: query.attempts,
+        close_attempts: query.close_attempts,
+        earlier_today: query.earlier_today,
+        // Differences are never negative, so -1 stands for no attempts.
+        previous_best: (query.previous_best >= 0.0).then_some(query.previous_best),
+        unlocked: query
+            .unlocked
+            .split(',')
+            .filter(|name| !name.is_empty())
+            .map(str::to_owned)
+            .collect(),
+    })
+}
+
+/// Stores achievements of a chatter, returning the ones they didn't have yet.
+pub async fn unlock_achievements(
+    db_conn: &D1Connection,
+    broadcaster_user_id: &str,
+    game: &str,
+    chatter_user_id: &str,
+    achievements: &[&str],
+    attempt_id: i64,
+    timestamp: i64,
+) -> Result<Vec<String>, sqlx_d1::Error> {
+    // One statement for all of them, the names are passed as a JSON array.
+    let names = serde_json::to_string(achievements).expect("Names serialize to JSON");
+
+    let rows = sqlx_d1::query!(
+        "
+        INSERT INTO Achievements (broadcaster_user_id, game, chatter_user_id, achievement, attempt_id, unlocked_at)
+        SELECT ?1, ?2, ?3, value, ?4, ?5
+        FROM json_each(?6)
+        WHERE true
+        ON CONFLICT DO NOTHING
+        RETURNING achievement;
+        ",
+        broadcaster_user_id,
+        game,
+        chatter_user_id,
+        attempt_id,
+        timestamp,
+        names
+    )
+    .fetch_all(db_conn)
+    .await?;
+
+    Ok(rows.into_iter().map(|row| row.achievement).collect())
+}
+
+/// Removes the achievements an attempt unlocked, for when it's voided. They can be unlocked again.
+async fn revoke_achievements(
+    db_conn: &D1Connection,
+    broadcaster_user_id: &str,
+    game: &str,
+    chatter_user_id: &str,
+    attempt_id: i64,
+) -> Result<(), sqlx_d1::Error> {
+    sqlx_d1::query!(
+        "
+        DELETE FROM Achievements
+        WHERE broadcaster_user_id = ?1 AND game = ?2 AND chatter_user_id = ?3 AND attempt_id = ?4;
+        ",
+        broadcaster_user_id,
+        game,
+        chatter_user_id,
+        attempt_id
+    )
+    .execute(db_conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Achievements of a chatter in a game, in the order they were unlocked.
+pub async fn achievements(
+    db_conn: &D1Connection,
+    broadcaster_user_id: &str,
+    game: &str,
+    chatter_user_id: &str,
+) -> Result<Vec<String>, sqlx_d1::Error> {
+    let rows = sqlx_d1::query!(
+        "
+        SELECT achievement
+        FROM Achievements
+        WHERE broadcaster_user_id = ?1 AND game = ?2 AND chatter_user_id = ?3
+        ORDER BY unlocked_at, achievement;
+        ",
+        broadcaster_user_id,
+        game,
+        chatter_user_id
+    )
+    .fetch_all(db_conn)
+    .await?;
+
+    Ok(rows.into_iter().map(|row| row.achievement).collect())
+}
+
+/// A broadcast of a channel, see [`crate::event`].
+pub struct Stream {
+    pub id: String,
+    pub started_at: i64,
+}
+
+/// The stream a channel is live with, `None` while offline.
+pub async fn live_stream(
+    db_conn: &D1Connection,
+    broadcaster_user_id: &str,
+) -> Result<Option<Stream>, sqlx_d1::Error> {
+    let row = sqlx_d1::query!(
+        "
+        SELECT id, started_at
+        FROM Streams
+        WHERE broadcaster_user_id = ?1 AND ended_at IS NULL
+        ORDER BY started_at DESC
+        LIMIT 1;
+        ",
+        broadcaster_user_id
+    )
+    .fetch_optional(db_conn)
+    .await?;
+
+    Ok(row.map(|row| Stream {
+        id: row.id,
+        started_at: row.started_at,
+    }))
+}
+
+/// Records a stream going live. Twitch can deliver an event more than once, which is ignored.
+pub async fn start_stream(
+    db_conn: &D1Connection,
+    broadcaster_user_id: &str,
+    stream_id: &str,
+    started_at: i64,
+) -> Result<(), sqlx_d1::Error> {
+    sqlx_d1::query!(
+        "
+        INSERT INTO Streams (id, broadcaster_user_id, started_at)
+        VALUES (?1, ?2, ?3)
+        ON CONFLICT (id) DO NOTHING;
+        ",
+        stream_id,
+        broadcaster_user_id,
+        started_at
+    )
+    .execute(db_conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Ends every stream of a channel that's still live. stream.offline doesn't say which stream
+/// ended, and a channel is only ever live with one.
+pub async fn end_streams(
+    db_conn: &D1Connection,
+    broadcaster_user_id: &str,
+    ended_at: i64,
+) -> Result<(), sqlx_d1::Error> {
+    sqlx_d1::query!(
+        "
+        UPDATE Streams
+        SET ended_at = ?2
+        WHERE broadcaster_user_id = ?1 AND ended_at IS NULL;
+        ",
+        broadcaster_user_id,
+        ended_at
+    )
+    .execute(db_conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Marks a message as handled, false if it already was. Holds Shared Chat messages, which every
+/// channel of the session receives, and EventSub notifications, which Twitch can deliver again.
+pub async fn claim_message(
+    db_conn: &D1Connection,
+    message_id: &str,
+    now: i64,
+) -> Result<bool, sqlx_d1::Error> {
+    let row = sqlx_d1::query!(
+        "
+        INSERT INTO SharedChatMessages (message_id, claimed_at)
+        VALUES (?1, ?2)
+        ON CONFLICT (message_id) DO NOTHING
+        RETURNING message_id;
+        ",
+        message_id,
+        now
+    )
+    .fetch_optional(db_conn)
+    .await?;
+
+    Ok(row.is_some())
+}
+
+/// Forgets messages claimed before `before`.
+pub async fn prune_claimed_messages(
+    db_conn: &D1Connection,
+    before: i64,
+) -> Result<(), sqlx_d1::Error> {
+    sqlx_d1::query!(
+        "DELETE FROM SharedChatMessages WHERE claimed_at < ?1;",
+        before
+    )
+    .execute(db_conn)
+    .await?;
+
+    Ok(())
+}
+
+/// The channel a chatter played in last, for whispers that don't name one.
+pub async fn last_played_channel(
+    db_conn: &D1Connection,
+    chatter_user_id: &str,
+) -> Result<Option<String>, sqlx_d1::Error> {
+    let row = sqlx_d1::query!(
+        "
+        SELECT broadcaster_user_id
+        FROM Attempts
+        WHERE chatter_user_id = ?1 AND voided_by IS NULL
+        ORDER BY forty_five_timestamp DESC
+        LIMIT 1;
+        ",
+        chatter_user_id
+    )
+    .fetch_optional(db_conn)
+    .await?;
+
+    Ok(row.map(|row| row.broadcaster_user_id))
+}
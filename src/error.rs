@@ -1,9 +1,13 @@
+use crate::messages::MessageKey;
+
 #[derive(thiserror::Error, Debug)]
 pub enum BotError {
     #[error("Error from workers: {0}")]
     WorkerError(#[from] worker::Error),
     #[error("Failed sending a request to Twitch API")]
     HelixClientError(#[from] twitch_api::helix::ClientRequestError<worker::Error>),
+    #[error("Failed validating a Twitch token")]
+    TokenValidationError(#[from] twitch_oauth2::tokens::errors::ValidationError<worker::Error>),
     #[error("Reading/writing to KV failed")]
     KvError(#[from] worker::kv::KvError),
     #[error("Reading/writing to database failed")]
@@ -12,9 +16,69 @@ pub enum BotError {
     TimeError(#[from] web_time::SystemTimeError),
     #[error("Number too big")]
     IntError(#[from] std::num::TryFromIntError),
+    #[error("Failed (de)serializing JSON: {0}")]
+    JsonError(#[from] serde_json::Error),
+    #[error("Invalid configuration: {0}")]
+    ConfigError(#[from] ConfigError),
     #[error("Unimplemented.")]
     #[allow(dead_code)]
     Unimplemented,
 }
 
+#[derive(thiserror::Error, Debug)]
+pub enum ConfigError {
+    #[error("step must be greater than 0")]
+    NonPositiveStep,
+    #[error("min must be less than max")]
+    EmptyRange,
+    #[error("the range between min and max must be a multiple of step")]
+    RangeNotMultipleOfStep,
+    #[error("target must be between min and max, and reachable in steps from min")]
+    TargetOffGrid,
+    #[error("{0:?} can't be used as a game name")]
+    InvalidGameName(String),
+    #[error("there's more than one game named {0:?}")]
+    DuplicateGame(String),
+    #[error("{0:?} can't be used as a webhook, only https URLs can")]
+    InvalidWebhookUrl(String),
+    #[error("{0:?} can't be used as a prefix, only ASCII punctuation can")]
+    InvalidPrefix(char),
+    #[error("{0:?} can't be used as an alias")]
+    InvalidAlias(String),
+    #[error("alias {0:?} has to start with the name of a game")]
+    AliasWithoutGame(String),
+    #[error("cheer_bits must be greater than 0")]
+    NoCheerBits,
+    #[error("at most {max} bonus rolls can be granted at once", max = crate::config::MAX_BONUS_ROLLS)]
+    TooManyBonusRolls,
+    #[error("rewards have to play a game, {0:?} isn't one")]
+    RewardWithoutGame(String),
+    #[error("{0:?} can't be whispered, only these commands can: {commands}", commands = crate::config::WHISPERABLE_COMMANDS.join(", "))]
+    NotWhisperable(String),
+    #[error("there are no messages for locale {0:?}")]
+    UnknownLocale(String),
+    #[error("unknown timezone {0:?}, expected a name such as Europe/Berlin")]
+    UnknownTimezone(String),
+    #[error("version {0} isn't supported, expected version {version}", version = crate::config::CONFIG_VERSION)]
+    UnsupportedVersion(u64),
+    #[error("version has to be a positive integer")]
+    InvalidVersion,
+    #[error("configuration has to be a JSON object")]
+    NotAnObject,
+    #[error("message {0:?}: {1}")]
+    Message(MessageKey, TemplateError),
+    #[error("game {0:?}: {1}")]
+    Game(String, Box<ConfigError>),
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum TemplateError {
+    #[error("a {{{{ is missing its closing }}}}")]
+    Unclosed,
+    #[error("unknown variable {0:?}")]
+    UnknownVariable(String),
+    #[error("variable {0:?} isn't available in this message")]
+    UnavailableVariable(String),
+}
+
 pub type BotResult<T> = core::result::Result<T, BotError>;
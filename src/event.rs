@@ -11,7 +11,9 @@ pub fn verify_signature(
     constant_time_eq::constant_time_eq_32(&signature, expected)
 }
 
-pub async fn event(env: Env, ctx: Context, event: Event) -> Result<Response> {
+/// Handles a notification of EventSub, `message_id` being its `Twitch-Eventsub-Message-Id` which
+/// stays the same when Twitch delivers it again.
+pub async fn event(env: Env, ctx: Context, event: Event, message_id: String) -> Result<Response> {
     match event {
         // channel.chat.message: Notification
         Event::ChannelChatMessageV1(Payload {
@@ -20,10 +22,87 @@ pub async fn event(env: Env, ctx: Context, event: Event) -> Result<Response> {
         }) => {
             ctx.wait_until(crate::commands::parse(env, msg));
         }
-        // channel.chat.message: Payload verification
+        // stream.online: Notification
+        Event::StreamOnlineV1(Payload {
+            message: Message::Notification(msg),
+            ..
+        }) => {
+            ctx.wait_until(crate::streams::online(env, msg));
+        }
+        // stream.offline: Notification
+        Event::StreamOfflineV1(Payload {
+            message: Message::Notification(msg),
+            ..
+        }) => {
+            ctx.wait_until(crate::streams::offline(env, msg));
+        }
+        // channel.channel_points_custom_reward_redemption.add: Notification
+        Event::ChannelPointsCustomRewardRedemptionAddV1(Payload {
+            message: Message::Notification(msg),
+            ..
+        }) => {
+            ctx.wait_until(crate::redemptions::redeem(env, msg));
+        }
+        // channel.cheer: Notification
+        Event::ChannelCheerV1(Payload {
+            message: Message::Notification(msg),
+            ..
+        }) => {
+            ctx.wait_until(crate::bonus::cheer(env, message_id, msg));
+        }
+        // channel.subscribe: Notification
+        Event::ChannelSubscribeV1(Payload {
+            message: Message::Notification(msg),
+            ..
+        }) => {
+            ctx.wait_until(crate::bonus::subscription(env, message_id, msg));
+        }
+        // channel.subscription.message: Notification
+        Event::ChannelSubscriptionMessageV1(Payload {
+            message: Message::Notification(msg),
+            ..
+        }) => {
+            ctx.wait_until(crate::bonus::resubscription(env, message_id, msg));
+        }
+        // user.whisper.message: Notification
+        Event::UserWhisperMessageV1(Payload {
+            message: Message::Notification(msg),
+            ..
+        }) => {
+            ctx.wait_until(crate::whispers::answer(env, msg));
+        }
+        // Payload verification of every subscription
         Event::ChannelChatMessageV1(Payload {
             message: Message::VerificationRequest(ver),
             ..
+        })
+        | Event::StreamOnlineV1(Payload {
+            message: Message::VerificationRequest(ver),
+            ..
+        })
+        | Event::StreamOfflineV1(Payload {
+            message: Message::VerificationRequest(ver),
+            ..
+        })
+        | Event::ChannelPointsCustomRewardRedemptionAddV1(Payload {
+            message: Message::VerificationRequest(ver),
+            ..
+        })
+        | Event::ChannelCheerV1(Payload {
+            message: Message::VerificationRequest(ver),
+            ..
+        })
+        | Event::ChannelSubscribeV1(Payload {
+            message: Message::VerificationRequest(ver),
+            ..
+        })
+        | Event::ChannelSubscriptionMessageV1(Payload {
+            message: Message::VerificationRequest(ver),
+            ..
+        })
+        | Event::UserWhisperMessageV1(Payload {
+            message: Message::VerificationRequest(ver),
+            ..
         }) => {
             return Ok(Response::builder()
                 .with_header("Content-Type", "text/plain")?
@@ -0,0 +1,223 @@
+//! Exports of every attempt of a channel, streamed page by page from D1 so that large channels
+//! don't have to fit in memory.
+
+use std::collections::HashMap;
+
+use futures_util::stream::try_unfold;
+use serde::Serialize;
+use sqlx_d1::D1Connection;
+use twitch_oauth2::AppAccessToken;
+use worker::Response;
+
+use crate::db;
+use crate::prelude::*;
+use crate::time::iso_timestamp;
+
+/// How many attempts are read from D1 at a time.
+const PAGE_SIZE: i64 = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Csv,
+    /// One JSON object per line.
+    Ndjson,
+}
+
+impl Format {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "csv" => Some(Self::Csv),
+            "ndjson" | "json" => Some(Self::Ndjson),
+            _ => None,
+        }
+    }
+
+    fn content_type(self) -> &'static str {
+        match self {
+            Self::Csv => "text/csv; charset=utf-8",
+            Self::Ndjson => "application/x-ndjson",
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Ndjson => "ndjson",
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct Row<'a> {
+    id: i64,
+    game: &'a str,
+    epoch: i64,
+    user_id: &'a str,
+    user_name: &'a str,
+    value: String,
+    difference: String,
+    timestamp: String,
+}
+
+const CSV_HEADER: &str = "id,game,epoch,user_id,user_name,value,difference,timestamp\n";
+
+struct Export {
+    db_conn: D1Connection,
+    credentials: AppAccessToken,
+    broadcaster_user_id: String,
+    format: Format,
+    config: CommandConfig,
+    /// Display names of chatters seen so far, so that each is only looked up once.
+    names: HashMap<String, String>,
+    after: i64,
+    done: bool,
+}
+
+impl Export {
+    async fn next_page(mut self) -> BotResult<Option<(Vec<u8>, Self)>> {
+        if self.done {
+            return Ok(None);
+        }
+
+        let attempts = db::attempts_after(
+            &self.db_conn,
+            &self.broadcaster_user_id,
+            self.after,
+            PAGE_SIZE,
+        )
+        .await?;
+
+        let mut chunk = String::new();
+
+        if self.after == 0 && self.format == Format::Csv {
+            chunk.push_str(CSV_HEADER);
+        }
+
+        self.done = (attempts.len() as i64) < PAGE_SIZE;
+
+        let Some(last) = attempts.last() else {
+            return Ok((!chunk.is_empty()).then(|| (chunk.into_bytes(), self)));
+        };
+
+        self.after = last.id;
+
+        let mut unknown = attempts
+            .iter()
+            .map(|attempt| attempt.chatter_user_id.as_str())
+            .filter(|id| !self.names.contains_key(*id))
+            .collect::<Vec<_>>();
+        unknown.sort_unstable();
+        unknown.dedup();
+
+        if !unknown.is_empty() {
+            let http_client = HelixClient::with_client(FetchClient::default());
+            let user_ids = unknown
+                .iter()
+                .map(|id| UserIdRef::from_str(id))
+                .collect::<Vec<_>>();
+
+            let names = get_display_names(&http_client, &user_ids, &self.credentials).await?;
+            self.names.extend(names);
+        }
+
+        for attempt in &attempts {
+            // Attempts of games that were removed since are written with the
+            // precision of !45.
+            let rules = self
+                .config
+                .game(&attempt.game)
+                .map(|game| game.rules)
+                .unwrap_or_default();
+
+            let row = Row {
+                id: attempt.id,
+                game: &attempt.game,
+                epoch: attempt.epoch,
+                user_id: &attempt.chatter_user_id,
+                user_name: self
+                    .names
+                    .get(&attempt.chatter_user_id)
+                    .map(String::as_str)
+                    .unwrap_or_default(),
+                value: rules.format_value(attempt.forty_five_value, Locale::En),
+                difference: rules.format_value(attempt.forty_five_difference, Locale::En),
+                timestamp: iso_timestamp(attempt.forty_five_timestamp),
+            };
+
+            match self.format {
+                Format::Csv => {
+                    let fields = [
+                        row.id.to_string(),
+                        csv_field(row.game),
+                        row.epoch.to_string(),
+                        csv_field(row.user_id),
+                        csv_field(row.user_name),
+                        row.value,
+                        row.difference,
+                        row.timestamp,
+                    ];
+
+                    chunk.push_str(&fields.join(","));
+                }
+                Format::Ndjson => chunk.push_str(&serde_json::to_string(&row)?),
+            }
+
+            chunk.push('\n');
+        }
+
+        Ok(Some((chunk.into_bytes(), self)))
+    }
+}
+
+/// Streams every attempt of a channel as a download.
+pub async fn response(
+    env: Env,
+    broadcaster_user_id: &UserId,
+    broadcaster_login: &str,
+    format: Format,
+) -> BotResult<Response> {
+    let db_conn = D1Connection::new(env.d1(FORTYFIVE_DATA)?);
+    let credentials = get_credentials(&env).await?;
+    let config = CommandConfig::load(&env, broadcaster_user_id).await;
+
+    let export = Export {
+        db_conn,
+        credentials,
+        broadcaster_user_id: broadcaster_user_id.as_str().to_owned(),
+        format,
+        config,
+        names: HashMap::new(),
+        after: 0,
+        done: false,
+    };
+
+    let stream = try_unfold(export, |export| async move {
+        export.next_page().await.map_err(|e| {
+            console_error!("Error exporting attempts: {:?}", e);
+            worker::Error::RustError(e.to_string())
+        })
+    });
+
+    let mut response = Response::from_stream(stream)?;
+    let headers = response.headers_mut();
+    headers.set("Content-Type", format.content_type())?;
+    headers.set(
+        "Content-Disposition",
+        &format!(
+            "attachment; filename=\"{}-attempts.{}\"",
+            broadcaster_login,
+            format.extension()
+        ),
+    )?;
+
+    Ok(response)
+}
+
+/// Quotes a CSV field if it needs to be.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_owned()
+    }
+}
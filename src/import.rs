@@ -0,0 +1,513 @@
+//! Imports of attempts from other bots, such as the !45 commands channels had on Nightbot or
+//! StreamElements before. Rows are `(login or user_id, value, timestamp)` as CSV with a header or
+//! as NDJSON, and go through the same rules as `!45`: values have to be ones the game can
+//! generate, differences are computed from the target and epochs are renumbered by time, which
+//! moves stored attempts after an imported perfect hit to a later epoch.
+
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+
+use rust_decimal::prelude::*;
+use serde::Serialize;
+use serde_json::Value;
+use sqlx_d1::D1Connection;
+
+use crate::db::{self, NewAttempt};
+use crate::export::Format;
+use crate::prelude::*;
+
+/// How many row errors are reported, the rest are only counted.
+const MAX_REPORTED_ERRORS: usize = 100;
+
+/// Who an imported attempt is from.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Chatter {
+    Id(String),
+    Login(String),
+}
+
+#[derive(Debug)]
+struct Row {
+    line: usize,
+    chatter: Chatter,
+    value: Decimal,
+    /// Milliseconds since the Unix epoch.
+    timestamp: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RowError {
+    pub line: usize,
+    pub reason: String,
+}
+
+/// What an import did, or would do for dry runs.
+#[derive(Debug, Default, Serialize)]
+pub struct ImportReport {
+    pub dry_run: bool,
+    pub game: String,
+    /// Rows in the file, not counting the CSV header.
+    pub rows: usize,
+    pub imported: usize,
+    /// Rows that are already stored, by chatter and timestamp.
+    pub duplicates: usize,
+    pub perfects: usize,
+    pub invalid: usize,
+    /// Logins that don't exist on Twitch, their rows are skipped.
+    pub unknown_users: Vec<String>,
+    /// Why rows were invalid, for the first [`MAX_REPORTED_ERRORS`] of them.
+    pub errors: Vec<RowError>,
+    pub epoch_before: i64,
+    pub epoch_after: i64,
+    /// Stored attempts that move to a later epoch, since they come after an imported perfect hit.
+    pub renumbered: i64,
+}
+
+impl ImportReport {
+    fn error(&mut self, line: usize, reason: impl Into<String>) {
+        self.invalid += 1;
+
+        if self.errors.len() < MAX_REPORTED_ERRORS {
+            self.errors.push(RowError {
+                line,
+                reason: reason.into(),
+            });
+        }
+    }
+}
+
+/// Imports attempts of a game into a channel. Nothing is written for dry runs.
+pub async fn run(
+    env: &Env,
+    broadcaster_user_id: &str,
+    game: &GameConfig,
+    format: Format,
+    input: &str,
+    dry_run: bool,
+) -> BotResult<ImportReport> {
+    let mut report = ImportReport {
+        dry_run,
+        game: game.name.clone(),
+        ..Default::default()
+    };
+
+    let rows = match format {
+        Format::Csv => parse_csv(input, &mut report),
+        Format::Ndjson => parse_ndjson(input, &mut report),
+    };
+
+    let rules = &game.rules;
+
+    let rows = rows
+        .into_iter()
+        .filter(|row| {
+            if rules.is_on_grid(row.value) {
+                return true;
+            }
+
+            report.error(
+                row.line,
+                format!("{} can't be generated by {}", row.value, game.name),
+            );
+            false
+        })
+        .collect::<Vec<_>>();
+
+    // Resolve logins to IDs, 100 at a time.
+    let mut logins = rows
+        .iter()
+        .filter_map(|row| match &row.chatter {
+            Chatter::Login(login) => Some(login.as_str()),
+            Chatter::Id(_) => None,
+        })
+        .collect::<Vec<_>>();
+    logins.sort_unstable();
+    logins.dedup();
+
+    let ids = if logins.is_empty() {
+        HashMap::new()
+    } else {
+        let http_client = HelixClient::with_client(FetchClient::default());
+        let credentials = get_credentials(env).await?;
+        let logins = logins
+            .iter()
+            .map(|login| UserNameRef::from_str(login))
+            .collect::<Vec<_>>();
+
+        get_user_ids(&http_client, &logins, &credentials).await?
+    };
+
+    let db_conn = D1Connection::new(env.d1(FORTYFIVE_DATA)?);
+
+    let (from, to) = rows.iter().fold((i64::MAX, i64::MIN), |(from, to), row| {
+        (from.min(row.timestamp), to.max(row.timestamp))
+    });
+
+    let mut existing = if rows.is_empty() {
+        HashSet::new()
+    } else {
+        db::attempt_keys_between(&db_conn, broadcaster_user_id, &game.name, from, to)
+            .await?
+            .into_iter()
+            .collect::<HashSet<_>>()
+    };
+
+    let mut unknown_users = HashSet::new();
+    let mut attempts = Vec::with_capacity(rows.len());
+    let mut earliest_perfect = None;
+
+    for row in rows {
+        let chatter_user_id = match row.chatter {
+            Chatter::Id(id) => id,
+            Chatter::Login(login) => match ids.get(&login) {
+                Some(id) => id.as_str().to_owned(),
+                None => {
+                    report.error(row.line, format!("{login} doesn't exist"));
+                    unknown_users.insert(login);
+                    continue;
+                }
+            },
+        };
+
+        // Also catches rows that are in the file twice.
+        if !existing.insert((chatter_user_id.clone(), row.timestamp)) {
+            report.duplicates += 1;
+            continue;
+        }
+
+        let difference = rules.difference(row.value);
+
+        if difference.is_zero() {
+            report.perfects += 1;
+            earliest_perfect = Some(
+                earliest_perfect.map_or(row.timestamp, |earliest: i64| earliest.min(row.timestamp)),
+            );
+        }
+
+        attempts.push(NewAttempt {
+            chatter_user_id,
+            forty_five_value: row
+                .value
+                .to_f64()
+                .expect("Failed to convert decimal to f64"),
+            forty_five_difference: difference
+                .to_f64()
+                .expect("Failed to convert decimal to f64"),
+            forty_five_timestamp: row.timestamp,
+        });
+    }
+
+    report.unknown_users = unknown_users.into_iter().collect();
+    report.unknown_users.sort_unstable();
+    report.imported = attempts.len();
+    report.epoch_before = db::current_epoch(&db_conn, broadcaster_user_id, &game.name).await?;
+    report.epoch_after = report.epoch_before + report.perfects as i64;
+
+    if let Some(earliest_perfect) = earliest_perfect {
+        report.renumbered =
+            db::count_attempts_after(&db_conn, broadcaster_user_id, &game.name, earliest_perfect)
+                .await?;
+    }
+
+    if !dry_run && !attempts.is_empty() {
+        let db = env.d1(FORTYFIVE_DATA)?;
+        db::import_attempts(&db, broadcaster_user_id, &game.name, &attempts).await?;
+    }
+
+    Ok(report)
+}
+
+fn parse_csv(input: &str, report: &mut ImportReport) -> Vec<Row> {
+    let mut lines = input
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line))
+        .filter(|(_, line)| !line.trim().is_empty());
+
+    let Some((header_line, header)) = lines.next() else {
+        return Vec::new();
+    };
+
+    let header = split_csv_line(header)
+        .into_iter()
+        .map(|column| column.trim().to_lowercase())
+        .collect::<Vec<_>>();
+
+    let column = |names: &[&str]| header.iter().position(|c| names.contains(&c.as_str()));
+
+    let user_id = column(&["user_id"]);
+    let login = column(&["login", "user", "user_login", "user_name", "username"]);
+    let value = column(&["value"]);
+    let timestamp = column(&["timestamp", "time", "date"]);
+
+    let (Some(value), Some(timestamp)) = (value, timestamp) else {
+        report.error(
+            header_line,
+            "the header needs a value and a timestamp column",
+        );
+        return Vec::new();
+    };
+
+    if user_id.is_none() && login.is_none() {
+        report.error(header_line, "the header needs a user_id or a login column");
+        return Vec::new();
+    }
+
+    let mut rows = Vec::new();
+
+    for (line, text) in lines {
+        report.rows += 1;
+
+        let fields = split_csv_line(text);
+        let field = |index: Option<usize>| {
+            index
+                .and_then(|index| fields.get(index))
+                .map(|field| field.trim())
+                .filter(|field| !field.is_empty())
+        };
+
+        let chatter = match (field(user_id), field(login)) {
+            (Some(id), _) => Chatter::Id(id.to_owned()),
+            (None, Some(login)) => Chatter::Login(normalize_login(login)),
+            (None, None) => {
+                report.error(line, "no user");
+                continue;
+            }
+        };
+
+        match parse_row(line, chatter, field(Some(value)), field(Some(timestamp))) {
+            Ok(row) => rows.push(row),
+            Err(reason) => report.error(line, reason),
+        }
+    }
+
+    rows
+}
+
+fn parse_ndjson(input: &str, report: &mut ImportReport) -> Vec<Row> {
+    let mut rows = Vec::new();
+
+    for (i, text) in input.lines().enumerate() {
+        let line = i + 1;
+
+        if text.trim().is_empty() {
+            continue;
+        }
+
+        report.rows += 1;
+
+        let object = match serde_json::from_str::<Value>(text) {
+            Ok(Value::Object(object)) => object,
+            Ok(_) => {
+                report.error(line, "not a JSON object");
+                continue;
+            }
+            Err(e) => {
+                report.error(line, e.to_string());
+                continue;
+            }
+        };
+
+        let field = |names: &[&str]| {
+            names
+                .iter()
+                .find_map(|name| object.get(*name))
+                .and_then(|value| match value {
+                    Value::String(value) => Some(value.trim().to_owned()),
+                    Value::Number(value) => Some(value.to_string()),
+                    _ => None,
+                })
+                .filter(|value| !value.is_empty())
+        };
+
+        let chatter = match (field(&["user_id"]), field(&["login", "user", "user_login"])) {
+            (Some(id), _) => Chatter::Id(id),
+            (None, Some(login)) => Chatter::Login(normalize_login(&login)),
+            (None, None) => {
+                report.error(line, "no user_id or login");
+                continue;
+            }
+        };
+
+        let value = field(&["value"]);
+        let timestamp = field(&["timestamp", "time", "date"]);
+
+        match parse_row(line, chatter, value.as_deref(), timestamp.as_deref()) {
+            Ok(row) => rows.push(row),
+            Err(reason) => report.error(line, reason),
+        }
+    }
+
+    rows
+}
+
+fn parse_row(
+    line: usize,
+    chatter: Chatter,
+    value: Option<&str>,
+    timestamp: Option<&str>,
+) -> Result<Row, String> {
+    let value = value.ok_or("no value")?;
+    let value = Decimal::from_str(value).map_err(|_| format!("{value:?} isn't a number"))?;
+
+    let timestamp = timestamp.ok_or("no timestamp")?;
+    let timestamp =
+        parse_timestamp(timestamp).ok_or_else(|| format!("{timestamp:?} isn't a timestamp"))?;
+
+    Ok(Row {
+        line,
+        chatter,
+        value,
+        timestamp,
+    })
+}
+
+fn normalize_login(login: &str) -> String {
+    login.trim_start_matches('@').to_lowercase()
+}
+
+/// Splits a CSV line, unquoting quoted fields.
+fn split_csv_line(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match (c, quoted) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            ('"', _) => quoted = !quoted,
+            (',', false) => fields.push(std::mem::take(&mut field)),
+            (c, _) => field.push(c),
+        }
+    }
+
+    fields.push(field);
+    fields
+}
+
+/// Reads a timestamp as milliseconds since the Unix epoch. Numbers are taken as seconds or
+/// milliseconds depending on their size, anything else has to be ISO 8601 such as
+/// `2024-12-31T18:00:00Z`, which is taken as UTC without an offset.
+fn parse_timestamp(timestamp: &str) -> Option<i64> {
+    if let Ok(number) = timestamp.parse::<i64>() {
+        // Seconds since the Unix epoch don't reach this until the year 5138.
+        return Some(if number.abs() >= 100_000_000_000 {
+            number
+        } else {
+            number * 1000
+        });
+    }
+
+    crate::time::parse_iso_timestamp(timestamp)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_csv() {
+        let mut report = ImportReport::default();
+        let rows = parse_csv(
+            "Login,Value,Timestamp\n\
+             @Someone,45.0,2024-12-31T18:00:00Z\n\
+             \n\
+             \"other\",\"12.5\",1735668000\n\
+             nobody,,1735668000\n\
+             third,abc,1735668000\n",
+            &mut report,
+        );
+
+        assert_eq!(report.rows, 4);
+        assert_eq!(report.invalid, 2);
+        assert_eq!(report.errors[0].line, 5);
+        assert_eq!(report.errors[1].line, 6);
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].line, 2);
+        assert_eq!(rows[0].chatter, Chatter::Login("someone".to_owned()));
+        assert_eq!(rows[0].value, dec!(45.0));
+        assert_eq!(rows[0].timestamp, 1_735_668_000_000);
+        assert_eq!(rows[1].line, 4);
+        assert_eq!(rows[1].chatter, Chatter::Login("other".to_owned()));
+        assert_eq!(rows[1].timestamp, 1_735_668_000_000);
+    }
+
+    #[test]
+    fn prefers_user_ids_in_csv() {
+        let mut report = ImportReport::default();
+        let rows = parse_csv(
+            "user_id,login,value,time\n123,someone,1,1735668000000\n",
+            &mut report,
+        );
+
+        assert_eq!(rows[0].chatter, Chatter::Id("123".to_owned()));
+        assert_eq!(rows[0].timestamp, 1_735_668_000_000);
+    }
+
+    #[test]
+    fn rejects_csv_without_required_columns() {
+        let mut report = ImportReport::default();
+        let rows = parse_csv("login,value\nsomeone,45\n", &mut report);
+
+        assert!(rows.is_empty());
+        assert_eq!(report.invalid, 1);
+        assert_eq!(report.errors[0].line, 1);
+    }
+
+    #[test]
+    fn parses_ndjson() {
+        let mut report = ImportReport::default();
+        let rows = parse_ndjson(
+            "{\"user_id\": 123, \"value\": 45, \"timestamp\": \"2024-12-31T18:00:00Z\"}\n\
+             \n\
+             {\"login\": \"@Someone\", \"value\": \"12.5\", \"time\": 1735668000}\n\
+             [1, 2]\n\
+             {\"value\": 1, \"timestamp\": 1735668000}\n\
+             {\"login\": \"x\", \"value\": 1, \"timestamp\": \"2025-02-31\"}\n\
+             not json\n",
+            &mut report,
+        );
+
+        assert_eq!(report.rows, 6);
+        assert_eq!(report.invalid, 4);
+        assert_eq!(
+            report
+                .errors
+                .iter()
+                .map(|error| error.line)
+                .collect::<Vec<_>>(),
+            [4, 5, 6, 7]
+        );
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].chatter, Chatter::Id("123".to_owned()));
+        assert_eq!(rows[0].value, dec!(45));
+        assert_eq!(rows[1].line, 3);
+        assert_eq!(rows[1].chatter, Chatter::Login("someone".to_owned()));
+        assert_eq!(rows[1].value, dec!(12.5));
+    }
+
+    #[test]
+    fn splits_quoted_csv_fields() {
+        assert_eq!(
+            split_csv_line("a,\"b,c\",\"d \"\"e\"\"\","),
+            ["a", "b,c", "d \"e\"", ""]
+        );
+    }
+
+    #[test]
+    fn parses_timestamps_of_any_precision() {
+        assert_eq!(parse_timestamp("1735668000"), Some(1_735_668_000_000));
+        assert_eq!(parse_timestamp("1735668000123"), Some(1_735_668_000_123));
+        assert_eq!(
+            parse_timestamp("2024-12-31T18:00:00Z"),
+            Some(1_735_668_000_000)
+        );
+        assert_eq!(parse_timestamp("2024-12-31T25:00:00Z"), None);
+    }
+}
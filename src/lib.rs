@@ -1,5 +1,6 @@
 //! A Twitch bot that generates a number between 0 and 90, with 0.005 increments.
-//! Simulates a 45 strafe in Minecraft.
+//! Simulates a 45 strafe in Minecraft. The range, increments and target can be changed per
+//! channel through [`config::FortyFiveConfig`].
 //!
 //! If you want to use the bot for your own purposes, change the variables defined in consts.rs,
 //! adopting it to your account. Change the KV and D1 values as well as these define which KV store
@@ -8,18 +9,67 @@
 //! (the value that the bot checks for when receiving events from Twitch). See the Twitch developer
 //! website for more information.
 //!
+//! The admin endpoints under `/admin` additionally need an ADMIN_TOKEN secret, which is sent as a
+//! bearer token.
+//!
+//! The read-only JSON API under `/api` is public and needs no additional setup.
+//!
+//! The dashboard under `/dashboard` lets broadcasters and their moderators log in with Twitch.
+//! `<worker URL>/dashboard/callback` has to be added as an OAuth redirect URL of the Twitch
+//! application for logging in to work.
+//!
+//! Webhooks need the Webhooks queue from wrangler.toml and a WEBHOOK_SECRET secret, which the
+//! signing secret of every channel is derived from.
+//!
+//! `!45 best stream` and [`config::CommandConfig::live_only`] need the channel's stream.online and
+//! stream.offline subscriptions, which `POST /admin/subscriptions/:broadcaster_user_id` creates.
+//!
+//! Channel point rewards that play games, see [`redemptions`], need the broadcaster to log in to
+//! the dashboard through `/dashboard/login?redemptions` and the subscriptions from the same admin
+//! endpoint.
+//!
+//! Bonus rolls for cheering and subscribing, see [`bonus`], need the broadcaster to log in to the
+//! dashboard through `/dashboard/login?supporters` and the subscriptions from the same admin
+//! endpoint.
+//!
+//! Whispered commands and responses, see [`whispers`], need the bot's account to log in to the
+//! dashboard through `/dashboard/login?whispers` and the subscription from `POST
+//! /admin/subscriptions`.
+//!
+//! Seasons are closed by the cron trigger from wrangler.toml, which announces their winners.
+//!
+//! The overlay under `/overlay` needs the Overlay Durable Object binding from wrangler.toml. Its
+//! URL is created from the dashboard.
+//!
 //! You must manually get the bot token for the first time and set it in the credentials KV. The key
 //! for setting the access token is defined by CREDENTIALS_ACCESS_TOKEN in consts.rs.
 
 use twitch_api::eventsub::Event;
 use worker::*;
 
+mod achievements;
+mod admin;
+mod api;
+mod bonus;
 mod commands;
 mod config;
+mod dashboard;
+mod db;
 mod error;
 mod event;
+mod export;
+mod import;
+mod messages;
+mod overlay;
 mod prelude;
+mod redemptions;
+mod seasons;
+mod streams;
+mod template;
+mod time;
 mod twitch;
+mod webhooks;
+mod whispers;
 
 #[event(fetch)]
 async fn fetch(req: Request, env: Env, ctx: Context) -> Result<Response> {
@@ -27,10 +77,72 @@ async fn fetch(req: Request, env: Env, ctx: Context) -> Result<Response> {
 
     Router::with_data(ctx)
         .post_async("/eventsub", eventsub)
+        .get_async("/admin/config/:broadcaster_user_id", admin::config)
+        .put_async("/admin/config/:broadcaster_user_id", admin::update_config)
+        .post_async("/admin/import/:broadcaster_user_id", admin::import)
+        .delete_async("/admin/users/:user_id", admin::purge_user)
+        .post_async("/admin/subscriptions", admin::subscribe_whispers)
+        .post_async(
+            "/admin/subscriptions/:broadcaster_user_id",
+            admin::subscribe,
+        )
+        .get_async("/api/channels/:broadcaster_login/best", api::best)
+        .get_async("/api/channels/:broadcaster_login/worst", api::worst)
+        .get_async("/api/channels/:broadcaster_login/hof", api::hof)
+        .get_async("/api/channels/:broadcaster_login/stats", api::stats)
+        .get_async(
+            "/api/channels/:broadcaster_login/users/:user_login",
+            api::user,
+        )
+        .options("/api/*path", api::preflight)
+        .get_async("/dashboard", dashboard::index)
+        .get_async("/dashboard/login", dashboard::login)
+        .get_async("/dashboard/callback", dashboard::callback)
+        .post_async("/dashboard/logout", dashboard::logout)
+        .get_async(
+            "/dashboard/channels/:broadcaster_user_id",
+            dashboard::channel,
+        )
+        .post_async(
+            "/dashboard/channels/:broadcaster_user_id/config",
+            dashboard::update_config,
+        )
+        .post_async(
+            "/dashboard/channels/:broadcaster_user_id/overlay",
+            dashboard::rotate_overlay_secret,
+        )
+        .get_async(
+            "/dashboard/channels/:broadcaster_user_id/export",
+            dashboard::export,
+        )
+        .post_async(
+            "/dashboard/channels/:broadcaster_user_id/import",
+            dashboard::import,
+        )
+        .get_async("/overlay/:broadcaster_user_id", overlay::page)
+        .get_async("/overlay/:broadcaster_user_id/ws", overlay::websocket)
         .run(req, env)
         .await
 }
 
+#[event(scheduled)]
+async fn scheduled(_event: ScheduledEvent, env: Env, _ctx: ScheduleContext) {
+    console_error_panic_hook::set_once();
+
+    if let Err(e) = seasons::close_ended(&env).await {
+        console_error!("Error closing seasons: {:?}", e);
+    }
+
+    if let Err(e) = commands::prune_claimed_messages(&env).await {
+        console_error!("Error pruning claimed messages: {:?}", e);
+    }
+}
+
+#[event(queue)]
+async fn queue(batch: MessageBatch<webhooks::Delivery>, _env: Env, _ctx: Context) -> Result<()> {
+    webhooks::consume(batch).await
+}
+
 async fn eventsub(
     mut req: Request,
     RouteContext { data, env, .. }: RouteContext<Context>,
@@ -75,10 +187,22 @@ async fn eventsub(
     input.push_str(&body);
 
     if event::verify_signature(key.to_string(), input, &buf) {
+        let now = Date::now().as_millis() as i64;
+
+        // Claims of notifications are only kept for so long, so older
+        // retries are acknowledged without handling them again.
+        if time::parse_iso_timestamp(&timestamp)
+            .is_none_or(|sent_at| now - sent_at > commands::CLAIMED_MESSAGE_MILLIS)
+        {
+            return Ok(Response::builder()
+                .with_status(204)
+                .body(ResponseBody::Empty));
+        }
+
         let parse_event = Event::parse(&body);
 
         if let Ok(event) = parse_event {
-            return event::event(env, data, event).await;
+            return event::event(env, data, event, message_id).await;
         }
     }
 
@@ -0,0 +1,396 @@
+//! Every response the bot sends, with the variables each of them can use. Channels pick the
+//! language of these through [`crate::config::CommandConfig::locale`], and can override any of
+//! them through [`crate::config::CommandConfig::messages`].
+
+use serde::{Deserialize, Serialize};
+
+use crate::template::{Template, Variable};
+
+mod de;
+mod en;
+mod es;
+mod fr;
+
+/// Languages the bot has bundled messages for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Locale {
+    #[default]
+    En,
+    De,
+    Es,
+    Fr,
+}
+
+impl Locale {
+    /// Picks the catalog for a language tag such as `de` or `pt-BR`. Only the language is looked
+    /// at, so regional variants share the catalog of their language.
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        let language = tag.split(['-', '_']).next()?.to_ascii_lowercase();
+
+        let locale = match language.as_str() {
+            "en" => Self::En,
+            "de" => Self::De,
+            "es" => Self::Es,
+            "fr" => Self::Fr,
+            _ => return None,
+        };
+
+        Some(locale)
+    }
+
+    /// Formats a number written with a `.` as the decimal separator for the locale.
+    pub fn localize_number(self, number: String) -> String {
+        match self {
+            Self::En => number,
+            Self::De | Self::Es | Self::Fr => number.replace('.', ","),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MessageKey {
+    /// A generated value that isn't a perfect hit.
+    Roll,
+    /// A generated value that hits the target.
+    Perfect,
+    Best,
+    Worst,
+    PersonalBest,
+    PersonalWorst,
+    /// The chatter has attempts, but none in the current epoch.
+    AttemptsWiped,
+    NoAttempts,
+    UserNotFound,
+    HallOfFame,
+    LatestHallOfFame,
+    NoHallOfFameForEpoch,
+    NoHallOfFame,
+    TimedOut,
+    Untimedout,
+    NotTimedOut,
+    /// The current value of a configuration key, from `!45 config get`.
+    ConfigValue,
+    ConfigSet,
+    ConfigReset,
+    ConfigUnknownKey,
+    ConfigInvalid,
+    /// Posted to Discord webhooks, see [`crate::webhooks`].
+    WebhookPerfect,
+    WebhookNewBest,
+    WebhookNewWorst,
+    WebhookEpochRollover,
+    /// From `!45 optout`, opted out chatters are ignored by the game.
+    OptedOut,
+    OptedIn,
+    /// From `!45 reset`, which ends the epoch without a perfect hit.
+    EpochReset,
+    /// From `!45 void`, the voided attempt no longer counts for records.
+    AttemptVoided,
+    NothingToVoid,
+    VoidUsage,
+    /// `!45 best` and friends for a period other than the current epoch, such as `!45 best season`.
+    PeriodBest,
+    PeriodWorst,
+    PeriodPersonalBest,
+    PeriodPersonalWorst,
+    NoAttemptsInPeriod,
+    NoSeason,
+    /// `!45 season`, with the leader of the game in the current season.
+    SeasonSummary,
+    SeasonSummaryEmpty,
+    SeasonAdded,
+    SeasonDeleted,
+    SeasonNotFound,
+    SeasonExists,
+    SeasonUsage,
+    /// Announced in chat when a season is over, once for every game that was played.
+    SeasonWinner,
+    SeasonOverNoWinner,
+    /// Names of periods, used as `{{ period }}` by `!45 best today` and friends.
+    PeriodToday,
+    PeriodWeek,
+    PeriodMonth,
+    PeriodAllTime,
+    /// Appended to a roll that unlocked an achievement, see [`crate::achievements`].
+    AchievementUnlocked,
+    /// `!45 achievements`, with the achievements joined by commas.
+    AchievementList,
+    NoAchievements,
+    /// Names of achievements, used as `{{ achievement }}`.
+    AchievementCloseStreak,
+    AchievementFirstOfTheDay,
+    AchievementHundredth,
+    AchievementMinimum,
+    AchievementMaximum,
+    AchievementPersonalBest,
+    /// `!45 best stream` while the channel is offline.
+    NotLive,
+    /// Name of the current broadcast as a period, used as `{{ period }}`.
+    PeriodStream,
+    /// `!45 stats`, counting every attempt of the chatter regardless of epochs.
+    Stats,
+    /// Whispered back for whispers the bot doesn't understand, see [`crate::whispers`].
+    WhisperUsage,
+}
+
+impl MessageKey {
+    /// The variables a template for this message can use.
+    pub fn variables(self) -> &'static [Variable] {
+        use Variable::*;
+
+        match self {
+            Self::Roll => &[User, Value, Difference, Epoch, Rank, Pb, Game, Target],
+            Self::Perfect => &[User, Value, Difference, Epoch, Game, Target],
+            Self::Best | Self::Worst => &[User, Value, Difference, Epoch, Game, Target],
+            Self::PersonalBest | Self::PersonalWorst => {
+                &[User, Value, Difference, Epoch, Rank, Game, Target]
+            }
+            Self::AttemptsWiped | Self::NoAttempts => &[User, Game, Command],
+            Self::UserNotFound => &[User],
+            Self::HallOfFame | Self::LatestHallOfFame => &[User, Epoch, Game, Target],
+            Self::NoHallOfFameForEpoch => &[Epoch, Game, Target],
+            Self::NoHallOfFame => &[Game, Target],
+            Self::TimedOut => &[User, Secs],
+            Self::Untimedout | Self::NotTimedOut => &[User],
+            Self::ConfigValue | Self::ConfigSet | Self::ConfigReset => &[Key, Value],
+            Self::ConfigUnknownKey => &[Key],
+            Self::ConfigInvalid => &[Key, Value, Error],
+            Self::WebhookPerfect => &[User, Value, Epoch, Game, Target],
+            Self::WebhookNewBest | Self::WebhookNewWorst => {
+                &[User, Value, Difference, Epoch, Game, Target]
+            }
+            Self::WebhookEpochRollover => &[User, Epoch, Game],
+            Self::OptedOut | Self::OptedIn => &[User, Command],
+            Self::EpochReset => &[Epoch, Game],
+            Self::AttemptVoided => &[User, Value, Game],
+            Self::NothingToVoid => &[],
+            Self::PeriodBest
+            | Self::PeriodWorst
+            | Self::PeriodPersonalBest
+            | Self::PeriodPersonalWorst => &[User, Value, Difference, Rank, Period, Game, Target],
+            Self::NoAttemptsInPeriod => &[Period, Game],
+            Self::NoSeason => &[],
+            Self::SeasonSummary => &[Period, Date, User, Value, Game],
+            Self::SeasonSummaryEmpty => &[Period, Date, Game],
+            Self::SeasonAdded
+            | Self::SeasonDeleted
+            | Self::SeasonNotFound
+            | Self::SeasonExists
+            | Self::SeasonOverNoWinner => &[Period],
+            Self::SeasonUsage | Self::VoidUsage => &[Command],
+            Self::SeasonWinner => &[Period, User, Value, Game, Target],
+            Self::PeriodToday | Self::PeriodWeek | Self::PeriodMonth | Self::PeriodAllTime => &[],
+            Self::AchievementUnlocked | Self::AchievementList => &[User, Achievement, Game],
+            Self::NoAchievements => &[User, Game],
+            Self::AchievementCloseStreak
+            | Self::AchievementFirstOfTheDay
+            | Self::AchievementHundredth
+            | Self::AchievementMinimum
+            | Self::AchievementMaximum
+            | Self::AchievementPersonalBest => &[],
+            Self::NotLive | Self::PeriodStream | Self::WhisperUsage => &[],
+            Self::Stats => &[User, Game, Attempts, Perfects],
+        }
+    }
+
+    /// The bundled message for a locale, falling back to English when the locale's catalog is
+    /// missing it.
+    pub fn default_message(self, locale: Locale) -> &'static str {
+        let message = match locale {
+            Locale::En => None,
+            Locale::De => de::message(self),
+            Locale::Es => es::message(self),
+            Locale::Fr => fr::message(self),
+        };
+
+        message.unwrap_or_else(|| en::message(self))
+    }
+
+    pub fn default_template(self, locale: Locale) -> Template {
+        Template::parse(self.default_message(locale), self.variables())
+            .expect("Default messages are valid templates")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use MessageKey::*;
+
+    const KEYS: &[MessageKey] = &[
+        Roll,
+        Perfect,
+        Best,
+        Worst,
+        PersonalBest,
+        PersonalWorst,
+        AttemptsWiped,
+        NoAttempts,
+        UserNotFound,
+        HallOfFame,
+        LatestHallOfFame,
+        NoHallOfFameForEpoch,
+        NoHallOfFame,
+        TimedOut,
+        Untimedout,
+        NotTimedOut,
+        ConfigValue,
+        ConfigSet,
+        ConfigReset,
+        ConfigUnknownKey,
+        ConfigInvalid,
+        WebhookPerfect,
+        WebhookNewBest,
+        WebhookNewWorst,
+        WebhookEpochRollover,
+        OptedOut,
+        OptedIn,
+        EpochReset,
+        AttemptVoided,
+        NothingToVoid,
+        VoidUsage,
+        PeriodBest,
+        PeriodWorst,
+        PeriodPersonalBest,
+        PeriodPersonalWorst,
+        NoAttemptsInPeriod,
+        NoSeason,
+        SeasonSummary,
+        SeasonSummaryEmpty,
+        SeasonAdded,
+        SeasonDeleted,
+        SeasonNotFound,
+        SeasonExists,
+        SeasonUsage,
+        SeasonWinner,
+        SeasonOverNoWinner,
+        PeriodToday,
+        PeriodWeek,
+        PeriodMonth,
+        PeriodAllTime,
+        AchievementUnlocked,
+        AchievementList,
+        NoAchievements,
+        AchievementCloseStreak,
+        AchievementFirstOfTheDay,
+        AchievementHundredth,
+        AchievementMinimum,
+        AchievementMaximum,
+        AchievementPersonalBest,
+        NotLive,
+        PeriodStream,
+        Stats,
+        WhisperUsage,
+    ];
+
+    const LOCALES: &[Locale] = &[Locale::En, Locale::De, Locale::Es, Locale::Fr];
+
+    /// Doesn't compile when a key is added, as a reminder to add it to [`KEYS`].
+    #[allow(dead_code)]
+    fn listed(key: MessageKey) {
+        match key {
+            Roll
+            | Perfect
+            | Best
+            | Worst
+            | PersonalBest
+            | PersonalWorst
+            | AttemptsWiped
+            | NoAttempts
+            | UserNotFound
+            | HallOfFame
+            | LatestHallOfFame
+            | NoHallOfFameForEpoch
+            | NoHallOfFame
+            | TimedOut
+            | Untimedout
+            | NotTimedOut
+            | ConfigValue
+            | ConfigSet
+            | ConfigReset
+            | ConfigUnknownKey
+            | ConfigInvalid
+            | WebhookPerfect
+            | WebhookNewBest
+            | WebhookNewWorst
+            | WebhookEpochRollover
+            | OptedOut
+            | OptedIn
+            | EpochReset
+            | AttemptVoided
+            | NothingToVoid
+            | VoidUsage
+            | PeriodBest
+            | PeriodWorst
+            | PeriodPersonalBest
+            | PeriodPersonalWorst
+            | NoAttemptsInPeriod
+            | NoSeason
+            | SeasonSummary
+            | SeasonSummaryEmpty
+            | SeasonAdded
+            | SeasonDeleted
+            | SeasonNotFound
+            | SeasonExists
+            | SeasonUsage
+            | SeasonWinner
+            | SeasonOverNoWinner
+            | PeriodToday
+            | PeriodWeek
+            | PeriodMonth
+            | PeriodAllTime
+            | AchievementUnlocked
+            | AchievementList
+            | NoAchievements
+            | AchievementCloseStreak
+            | AchievementFirstOfTheDay
+            | AchievementHundredth
+            | AchievementMinimum
+            | AchievementMaximum
+            | AchievementPersonalBest
+            | NotLive
+            | PeriodStream
+            | Stats
+            | WhisperUsage => {}
+        }
+    }
+
+    #[test]
+    fn keys_are_listed_once() {
+        let mut keys = KEYS.to_vec();
+        keys.sort();
+        keys.dedup();
+
+        assert_eq!(keys.len(), KEYS.len());
+    }
+
+    #[test]
+    fn every_message_is_a_valid_template() {
+        for &locale in LOCALES {
+            for &key in KEYS {
+                let message = key.default_message(locale);
+
+                if let Err(e) = Template::parse(message, key.variables()) {
+                    panic!("{key:?} in {locale:?} is invalid: {e}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn picks_locales_by_language() {
+        assert_eq!(Locale::from_tag("de"), Some(Locale::De));
+        assert_eq!(Locale::from_tag("FR_ca"), Some(Locale::Fr));
+        assert_eq!(Locale::from_tag("es-419"), Some(Locale::Es));
+        assert_eq!(Locale::from_tag("pt-BR"), None);
+        assert_eq!(Locale::from_tag(""), None);
+    }
+
+    #[test]
+    fn localizes_decimal_separators() {
+        assert_eq!(Locale::En.localize_number("45.005".to_owned()), "45.005");
+        assert_eq!(Locale::De.localize_number("45.005".to_owned()), "45,005");
+    }
+}
@@ -0,0 +1,119 @@
+//! German messages.
+
+use super::MessageKey;
+
+pub fn message(key: MessageKey) -> Option<&'static str> {
+    let message = match key {
+        MessageKey::Roll => "{{ user }}, {{ value }}",
+        MessageKey::Perfect => "{{ user }} hat eine perfekte {{ game }} erreicht!",
+        MessageKey::Best => "Aktuell beste {{ game }} von {{ user }}: {{ value }}",
+        MessageKey::Worst => "Aktuell schlechteste {{ game }} von {{ user }}: {{ value }}",
+        MessageKey::PersonalBest => {
+            "Persönliche Bestleistung {{ game }} von {{ user }}: {{ value }}"
+        }
+        MessageKey::PersonalWorst => {
+            "Persönlich schlechteste {{ game }} von {{ user }}: {{ value }}"
+        }
+        MessageKey::AttemptsWiped => {
+            "{{ user }} hat in diesem Kanal schon {{ command }} benutzt, aber seitdem gab es eine perfekte {{ game }} und die Werte wurden zurückgesetzt."
+        }
+        MessageKey::NoAttempts => "{{ user }} hat in diesem Kanal noch kein {{ command }} benutzt.",
+        MessageKey::UserNotFound => "Benutzer {{ user }} nicht gefunden.",
+        MessageKey::HallOfFame => "Perfekte {{ target }} #{{ epoch }} von: {{ user }}",
+        MessageKey::LatestHallOfFame => "Letzte perfekte {{ target }} von: {{ user }}",
+        MessageKey::NoHallOfFameForEpoch => {
+            "Keine perfekte {{ target }} in Epoche {{ epoch }} gefunden."
+        }
+        MessageKey::NoHallOfFame => "In diesem Kanal gibt es noch keine perfekte {{ target }}.",
+        MessageKey::TimedOut => "{{ user }} ist für {{ secs }} Sekunden von !45 ausgeschlossen.",
+        MessageKey::Untimedout => "!45-Sperre für {{ user }} aufgehoben.",
+        MessageKey::NotTimedOut => "{{ user }} ist derzeit nicht gesperrt.",
+        MessageKey::ConfigValue => "{{ key }} ist {{ value }}",
+        MessageKey::ConfigSet => "{{ key }} wurde auf {{ value }} gesetzt.",
+        MessageKey::ConfigReset => "{{ key }} wurde zurückgesetzt.",
+        MessageKey::ConfigUnknownKey => "Unbekannte Einstellung {{ key }}.",
+        MessageKey::ConfigInvalid => "Ungültiger Wert für {{ key }}: {{ error }}",
+        MessageKey::WebhookPerfect => {
+            "{{ user }} hat eine perfekte {{ game }} erreicht und Epoche {{ epoch }} beendet!"
+        }
+        MessageKey::WebhookNewBest => {
+            "Neue beste {{ game }} in Epoche {{ epoch }}: {{ user }} mit {{ value }} ({{ difference }} daneben)."
+        }
+        MessageKey::WebhookNewWorst => {
+            "Neue schlechteste {{ game }} in Epoche {{ epoch }}: {{ user }} mit {{ value }} ({{ difference }} daneben)."
+        }
+        MessageKey::WebhookEpochRollover => "Epoche {{ epoch }} von {{ game }} hat begonnen.",
+        MessageKey::OptedOut => {
+            "{{ user }}, deine Versuche werden nicht mehr gespeichert. Mit {{ command }} optin kannst du wieder mitspielen."
+        }
+        MessageKey::OptedIn => "{{ user }}, deine Versuche werden wieder gespeichert.",
+        MessageKey::EpochReset => {
+            "Ein Moderator hat {{ game }} zurückgesetzt, Epoche {{ epoch }} hat begonnen."
+        }
+        MessageKey::AttemptVoided => {
+            "Der {{ game }}-Versuch von {{ user }} mit {{ value }} wurde annulliert."
+        }
+        MessageKey::NothingToVoid => "Diesen Versuch gibt es nicht oder er wurde schon annulliert.",
+        MessageKey::VoidUsage => {
+            "Versuche werden mit {{ command }} void <Versuchs-ID> oder {{ command }} void @user last annulliert."
+        }
+        MessageKey::PeriodBest => "Beste {{ game }} von {{ period }} von {{ user }}: {{ value }}",
+        MessageKey::PeriodWorst => {
+            "Schlechteste {{ game }} von {{ period }} von {{ user }}: {{ value }}"
+        }
+        MessageKey::PeriodPersonalBest => {
+            "Persönliche Bestleistung bei {{ game }} von {{ period }} von {{ user }}: {{ value }}"
+        }
+        MessageKey::PeriodPersonalWorst => {
+            "Persönlich schlechteste {{ game }} von {{ period }} von {{ user }}: {{ value }}"
+        }
+        MessageKey::NoAttemptsInPeriod => "Noch keine {{ game }}-Versuche für {{ period }}.",
+        MessageKey::NoSeason => "Gerade läuft keine Saison.",
+        MessageKey::SeasonSummary => {
+            "Die Saison {{ period }} läuft bis {{ date }}. {{ user }} führt bei {{ game }} mit {{ value }}."
+        }
+        MessageKey::SeasonSummaryEmpty => {
+            "Die Saison {{ period }} läuft bis {{ date }}. Noch hat niemand {{ game }} gespielt."
+        }
+        MessageKey::SeasonAdded => "Saison {{ period }} hinzugefügt.",
+        MessageKey::SeasonDeleted => "Saison {{ period }} gelöscht.",
+        MessageKey::SeasonNotFound => "Es gibt keine Saison {{ period }}.",
+        MessageKey::SeasonExists => {
+            "Die Saison {{ period }} gibt es schon oder sie überschneidet sich mit der neuen."
+        }
+        MessageKey::SeasonUsage => {
+            "Saisons werden mit {{ command }} season add <erster Tag> <letzter Tag> <Name> hinzugefügt, Tage wie 2025-03-28."
+        }
+        MessageKey::SeasonWinner => {
+            "Die Saison {{ period }} ist vorbei! {{ user }} gewinnt {{ game }} mit {{ value }}."
+        }
+        MessageKey::SeasonOverNoWinner => {
+            "Die Saison {{ period }} ist vorbei, aber niemand hat gespielt."
+        }
+        MessageKey::PeriodToday => "heute",
+        MessageKey::PeriodWeek => "dieser Woche",
+        MessageKey::PeriodMonth => "diesem Monat",
+        MessageKey::PeriodAllTime => "allen Zeiten",
+        MessageKey::AchievementUnlocked => "{{ user }} hat {{ achievement }} freigeschaltet!",
+        MessageKey::AchievementList => "Erfolge von {{ user }} in {{ game }}: {{ achievement }}",
+        MessageKey::NoAchievements => {
+            "{{ user }} hat noch keine Erfolge in {{ game }} freigeschaltet."
+        }
+        MessageKey::AchievementCloseStreak => "Glückssträhne (3 knappe Würfe in Folge)",
+        MessageKey::AchievementFirstOfTheDay => "Frühaufsteher (erster Wurf des Tages)",
+        MessageKey::AchievementHundredth => "Zenturio (100 Würfe)",
+        MessageKey::AchievementMinimum => "Tiefpunkt (der niedrigste mögliche Wurf)",
+        MessageKey::AchievementMaximum => "Himmelhoch (der höchste mögliche Wurf)",
+        MessageKey::AchievementPersonalBest => "Neue Bestleistung",
+        MessageKey::NotLive => "Der Kanal ist gerade nicht live.",
+        MessageKey::PeriodStream => "diesem Stream",
+        MessageKey::Stats => {
+            "{{ user }} hat {{ game }} {{ attempts }} Mal gespielt, davon {{ perfects }} Mal perfekt."
+        }
+        MessageKey::WhisperUsage => {
+            "Flüstere mir pb, pw, stats oder achievements gefolgt von einem Kanal, z. B. \"stats somechannel\". Für andere Spiele als 45 gib das Spiel nach dem Kanal an."
+        }
+    };
+
+    Some(message)
+}
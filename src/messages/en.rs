@@ -0,0 +1,103 @@
+//! English messages, these are the defaults and what other languages fall back to.
+
+use super::MessageKey;
+
+pub fn message(key: MessageKey) -> &'static str {
+    match key {
+        MessageKey::Roll => "{{ user }}, {{ value }}",
+        MessageKey::Perfect => "{{ user }} has achieved perfect {{ game }}!",
+        MessageKey::Best => "Current best {{ game }} by {{ user }}: {{ value }}",
+        MessageKey::Worst => "Current worst {{ game }} by {{ user }}: {{ value }}",
+        MessageKey::PersonalBest => "Personal best {{ game }} by {{ user }}: {{ value }}",
+        MessageKey::PersonalWorst => "Personal worst {{ game }} by {{ user }}: {{ value }}",
+        MessageKey::AttemptsWiped => {
+            "User {{ user }} has done a {{ command }} in this channel, but a perfect {{ game }} has been achieved and such the values has been wiped."
+        }
+        MessageKey::NoAttempts => "User {{ user }} hasn't done a {{ command }} in this channel.",
+        MessageKey::UserNotFound => "User {{ user }} not found.",
+        MessageKey::HallOfFame => "Perfect {{ target }} #{{ epoch }} by: {{ user }}",
+        MessageKey::LatestHallOfFame => "Latest perfect {{ target }} by: {{ user }}",
+        MessageKey::NoHallOfFameForEpoch => {
+            "No perfect {{ target }}'s found with epoch of {{ epoch }}"
+        }
+        MessageKey::NoHallOfFame => "No perfect {{ target }}'s in this channel.",
+        MessageKey::TimedOut => "Timed out {{ user }} from !45's for {{ secs }} seconds.",
+        MessageKey::Untimedout => "Removed !45 timeout for {{ user }}.",
+        MessageKey::NotTimedOut => "{{ user }} is not currently timed out.",
+        MessageKey::ConfigValue => "{{ key }} is {{ value }}",
+        MessageKey::ConfigSet => "Set {{ key }} to {{ value }}.",
+        MessageKey::ConfigReset => "Reset {{ key }} to its default.",
+        MessageKey::ConfigUnknownKey => "Unknown setting {{ key }}.",
+        MessageKey::ConfigInvalid => "Invalid value for {{ key }}: {{ error }}",
+        MessageKey::WebhookPerfect => {
+            "{{ user }} hit a perfect {{ game }} and ended epoch {{ epoch }}!"
+        }
+        MessageKey::WebhookNewBest => {
+            "New best {{ game }} of epoch {{ epoch }}: {{ user }} with {{ value }} ({{ difference }} off)."
+        }
+        MessageKey::WebhookNewWorst => {
+            "New worst {{ game }} of epoch {{ epoch }}: {{ user }} with {{ value }} ({{ difference }} off)."
+        }
+        MessageKey::WebhookEpochRollover => "Epoch {{ epoch }} of {{ game }} has started.",
+        MessageKey::OptedOut => {
+            "{{ user }}, your rolls won't be recorded anymore. Use {{ command }} optin to play again."
+        }
+        MessageKey::OptedIn => "{{ user }}, your rolls are recorded again.",
+        MessageKey::EpochReset => "A moderator reset {{ game }}, epoch {{ epoch }} has started.",
+        MessageKey::AttemptVoided => "Voided the {{ game }} of {{ user }} with {{ value }}.",
+        MessageKey::NothingToVoid => "There's no such attempt to void.",
+        MessageKey::VoidUsage => {
+            "Void attempts with {{ command }} void <attempt id> or {{ command }} void @user last."
+        }
+        MessageKey::PeriodBest => "Best {{ game }} of {{ period }} by {{ user }}: {{ value }}",
+        MessageKey::PeriodWorst => "Worst {{ game }} of {{ period }} by {{ user }}: {{ value }}",
+        MessageKey::PeriodPersonalBest => {
+            "Personal best {{ game }} of {{ period }} by {{ user }}: {{ value }}"
+        }
+        MessageKey::PeriodPersonalWorst => {
+            "Personal worst {{ game }} of {{ period }} by {{ user }}: {{ value }}"
+        }
+        MessageKey::NoAttemptsInPeriod => "No {{ game }} attempts for {{ period }} yet.",
+        MessageKey::NoSeason => "There's no season running.",
+        MessageKey::SeasonSummary => {
+            "Season {{ period }} runs until {{ date }}. {{ user }} leads {{ game }} with {{ value }}."
+        }
+        MessageKey::SeasonSummaryEmpty => {
+            "Season {{ period }} runs until {{ date }}. Nobody has played {{ game }} yet."
+        }
+        MessageKey::SeasonAdded => "Added season {{ period }}.",
+        MessageKey::SeasonDeleted => "Deleted season {{ period }}.",
+        MessageKey::SeasonNotFound => "There's no season {{ period }}.",
+        MessageKey::SeasonExists => "Season {{ period }} already exists, or overlaps the new one.",
+        MessageKey::SeasonUsage => {
+            "Add seasons with {{ command }} season add <first day> <last day> <name>, with days like 2025-03-28."
+        }
+        MessageKey::SeasonWinner => {
+            "Season {{ period }} is over! {{ user }} wins {{ game }} with {{ value }}."
+        }
+        MessageKey::SeasonOverNoWinner => "Season {{ period }} is over, but nobody played.",
+        MessageKey::PeriodToday => "today",
+        MessageKey::PeriodWeek => "this week",
+        MessageKey::PeriodMonth => "this month",
+        MessageKey::PeriodAllTime => "all time",
+        MessageKey::AchievementUnlocked => "{{ user }} unlocked {{ achievement }}!",
+        MessageKey::AchievementList => {
+            "Achievements of {{ user }} in {{ game }}: {{ achievement }}"
+        }
+        MessageKey::NoAchievements => "{{ user }} hasn't unlocked any {{ game }} achievements yet.",
+        MessageKey::AchievementCloseStreak => "Hot Streak (3 close rolls in a row)",
+        MessageKey::AchievementFirstOfTheDay => "Early Bird (first roll of the day)",
+        MessageKey::AchievementHundredth => "Centurion (100 rolls)",
+        MessageKey::AchievementMinimum => "Rock Bottom (the lowest possible roll)",
+        MessageKey::AchievementMaximum => "Sky High (the highest possible roll)",
+        MessageKey::AchievementPersonalBest => "New Personal Best",
+        MessageKey::NotLive => "The channel isn't live right now.",
+        MessageKey::PeriodStream => "this stream",
+        MessageKey::Stats => {
+            "{{ user }} played {{ game }} {{ attempts }} times, {{ perfects }} of them perfect."
+        }
+        MessageKey::WhisperUsage => {
+            "Whisper me pb, pw, stats or achievements followed by a channel, e.g. \"stats somechannel\". Add a game after the channel for games other than 45."
+        }
+    }
+}
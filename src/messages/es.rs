@@ -0,0 +1,107 @@
+//! Spanish messages.
+
+use super::MessageKey;
+
+pub fn message(key: MessageKey) -> Option<&'static str> {
+    let message = match key {
+        MessageKey::Roll => "{{ user }}, {{ value }}",
+        MessageKey::Perfect => "¡{{ user }} ha conseguido un {{ game }} perfecto!",
+        MessageKey::Best => "Mejor {{ game }} actual por {{ user }}: {{ value }}",
+        MessageKey::Worst => "Peor {{ game }} actual por {{ user }}: {{ value }}",
+        MessageKey::PersonalBest => "Mejor {{ game }} personal de {{ user }}: {{ value }}",
+        MessageKey::PersonalWorst => "Peor {{ game }} personal de {{ user }}: {{ value }}",
+        MessageKey::AttemptsWiped => {
+            "{{ user }} ha usado {{ command }} en este canal, pero alguien consiguió un {{ game }} perfecto y los valores se reiniciaron."
+        }
+        MessageKey::NoAttempts => "{{ user }} no ha usado {{ command }} en este canal.",
+        MessageKey::UserNotFound => "No se encontró al usuario {{ user }}.",
+        MessageKey::HallOfFame => "{{ target }} perfecto #{{ epoch }} por: {{ user }}",
+        MessageKey::LatestHallOfFame => "Último {{ target }} perfecto por: {{ user }}",
+        MessageKey::NoHallOfFameForEpoch => {
+            "No hay ningún {{ target }} perfecto en la época {{ epoch }}."
+        }
+        MessageKey::NoHallOfFame => "Todavía no hay ningún {{ target }} perfecto en este canal.",
+        MessageKey::TimedOut => "{{ user }} no puede usar !45 durante {{ secs }} segundos.",
+        MessageKey::Untimedout => "Se quitó la restricción de !45 a {{ user }}.",
+        MessageKey::NotTimedOut => "{{ user }} no tiene ninguna restricción.",
+        MessageKey::ConfigValue => "{{ key }} es {{ value }}",
+        MessageKey::ConfigSet => "{{ key }} se cambió a {{ value }}.",
+        MessageKey::ConfigReset => "{{ key }} se restableció a su valor predeterminado.",
+        MessageKey::ConfigUnknownKey => "Ajuste desconocido {{ key }}.",
+        MessageKey::ConfigInvalid => "Valor no válido para {{ key }}: {{ error }}",
+        MessageKey::WebhookPerfect => {
+            "¡{{ user }} consiguió un {{ game }} perfecto y terminó la época {{ epoch }}!"
+        }
+        MessageKey::WebhookNewBest => {
+            "Nuevo mejor {{ game }} de la época {{ epoch }}: {{ user }} con {{ value }} (a {{ difference }})."
+        }
+        MessageKey::WebhookNewWorst => {
+            "Nuevo peor {{ game }} de la época {{ epoch }}: {{ user }} con {{ value }} (a {{ difference }})."
+        }
+        MessageKey::WebhookEpochRollover => "Ha empezado la época {{ epoch }} de {{ game }}.",
+        MessageKey::OptedOut => {
+            "{{ user }}, tus intentos ya no se guardarán. Usa {{ command }} optin para volver a jugar."
+        }
+        MessageKey::OptedIn => "{{ user }}, tus intentos vuelven a guardarse.",
+        MessageKey::EpochReset => "Un moderador reinició {{ game }}, empezó la época {{ epoch }}.",
+        MessageKey::AttemptVoided => "Se anuló el {{ game }} de {{ user }} con {{ value }}.",
+        MessageKey::NothingToVoid => "No hay ningún intento que anular.",
+        MessageKey::VoidUsage => {
+            "Anula intentos con {{ command }} void <id del intento> o {{ command }} void @user last."
+        }
+        MessageKey::PeriodBest => "Mejor {{ game }} de {{ period }} por {{ user }}: {{ value }}",
+        MessageKey::PeriodWorst => "Peor {{ game }} de {{ period }} por {{ user }}: {{ value }}",
+        MessageKey::PeriodPersonalBest => {
+            "Mejor {{ game }} personal de {{ period }} por {{ user }}: {{ value }}"
+        }
+        MessageKey::PeriodPersonalWorst => {
+            "Peor {{ game }} personal de {{ period }} por {{ user }}: {{ value }}"
+        }
+        MessageKey::NoAttemptsInPeriod => {
+            "Todavía no hay intentos de {{ game }} para {{ period }}."
+        }
+        MessageKey::NoSeason => "No hay ninguna temporada en curso.",
+        MessageKey::SeasonSummary => {
+            "La temporada {{ period }} dura hasta el {{ date }}. {{ user }} lidera {{ game }} con {{ value }}."
+        }
+        MessageKey::SeasonSummaryEmpty => {
+            "La temporada {{ period }} dura hasta el {{ date }}. Nadie ha jugado {{ game }} todavía."
+        }
+        MessageKey::SeasonAdded => "Se añadió la temporada {{ period }}.",
+        MessageKey::SeasonDeleted => "Se eliminó la temporada {{ period }}.",
+        MessageKey::SeasonNotFound => "No existe la temporada {{ period }}.",
+        MessageKey::SeasonExists => "La temporada {{ period }} ya existe o se solapa con la nueva.",
+        MessageKey::SeasonUsage => {
+            "Añade temporadas con {{ command }} season add <primer día> <último día> <nombre>, con días como 2025-03-28."
+        }
+        MessageKey::SeasonWinner => {
+            "¡La temporada {{ period }} ha terminado! {{ user }} gana {{ game }} con {{ value }}."
+        }
+        MessageKey::SeasonOverNoWinner => {
+            "La temporada {{ period }} ha terminado, pero nadie jugó."
+        }
+        MessageKey::PeriodToday => "hoy",
+        MessageKey::PeriodWeek => "esta semana",
+        MessageKey::PeriodMonth => "este mes",
+        MessageKey::PeriodAllTime => "todos los tiempos",
+        MessageKey::AchievementUnlocked => "¡{{ user }} desbloqueó {{ achievement }}!",
+        MessageKey::AchievementList => "Logros de {{ user }} en {{ game }}: {{ achievement }}",
+        MessageKey::NoAchievements => "{{ user }} aún no ha desbloqueado logros en {{ game }}.",
+        MessageKey::AchievementCloseStreak => "Racha (3 tiradas cercanas seguidas)",
+        MessageKey::AchievementFirstOfTheDay => "Madrugador (primera tirada del día)",
+        MessageKey::AchievementHundredth => "Centurión (100 tiradas)",
+        MessageKey::AchievementMinimum => "Fondo (la tirada más baja posible)",
+        MessageKey::AchievementMaximum => "Por las nubes (la tirada más alta posible)",
+        MessageKey::AchievementPersonalBest => "Nueva mejor marca personal",
+        MessageKey::NotLive => "El canal no está en directo ahora mismo.",
+        MessageKey::PeriodStream => "este directo",
+        MessageKey::Stats => {
+            "{{ user }} jugó {{ game }} {{ attempts }} veces, {{ perfects }} de ellas perfectas."
+        }
+        MessageKey::WhisperUsage => {
+            "Susúrrame pb, pw, stats o achievements seguido de un canal, p. ej. \"stats somechannel\". Añade un juego después del canal para juegos distintos de 45."
+        }
+    };
+
+    Some(message)
+}
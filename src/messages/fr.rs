@@ -0,0 +1,113 @@
+//! French messages.
+
+use super::MessageKey;
+
+pub fn message(key: MessageKey) -> Option<&'static str> {
+    let message = match key {
+        MessageKey::Roll => "{{ user }}, {{ value }}",
+        MessageKey::Perfect => "{{ user }} a réussi un {{ game }} parfait !",
+        MessageKey::Best => "Meilleur {{ game }} actuel par {{ user }} : {{ value }}",
+        MessageKey::Worst => "Pire {{ game }} actuel par {{ user }} : {{ value }}",
+        MessageKey::PersonalBest => "Record personnel {{ game }} de {{ user }} : {{ value }}",
+        MessageKey::PersonalWorst => "Pire {{ game }} personnel de {{ user }} : {{ value }}",
+        MessageKey::AttemptsWiped => {
+            "{{ user }} a déjà utilisé {{ command }} sur cette chaîne, mais un {{ game }} parfait a été réussi depuis et les valeurs ont été remises à zéro."
+        }
+        MessageKey::NoAttempts => {
+            "{{ user }} n'a pas encore utilisé {{ command }} sur cette chaîne."
+        }
+        MessageKey::UserNotFound => "Utilisateur {{ user }} introuvable.",
+        MessageKey::HallOfFame => "{{ target }} parfait n°{{ epoch }} par : {{ user }}",
+        MessageKey::LatestHallOfFame => "Dernier {{ target }} parfait par : {{ user }}",
+        MessageKey::NoHallOfFameForEpoch => {
+            "Aucun {{ target }} parfait trouvé pour l'époque {{ epoch }}."
+        }
+        MessageKey::NoHallOfFame => "Aucun {{ target }} parfait sur cette chaîne.",
+        MessageKey::TimedOut => "{{ user }} est exclu de !45 pendant {{ secs }} secondes.",
+        MessageKey::Untimedout => "Exclusion de !45 levée pour {{ user }}.",
+        MessageKey::NotTimedOut => "{{ user }} n'est pas exclu actuellement.",
+        MessageKey::ConfigValue => "{{ key }} vaut {{ value }}",
+        MessageKey::ConfigSet => "{{ key }} a été défini sur {{ value }}.",
+        MessageKey::ConfigReset => "{{ key }} a été réinitialisé.",
+        MessageKey::ConfigUnknownKey => "Paramètre inconnu {{ key }}.",
+        MessageKey::ConfigInvalid => "Valeur invalide pour {{ key }} : {{ error }}",
+        MessageKey::WebhookPerfect => {
+            "{{ user }} a réussi un {{ game }} parfait et a terminé l'époque {{ epoch }} !"
+        }
+        MessageKey::WebhookNewBest => {
+            "Nouveau meilleur {{ game }} de l'époque {{ epoch }} : {{ user }} avec {{ value }} (à {{ difference }})."
+        }
+        MessageKey::WebhookNewWorst => {
+            "Nouveau pire {{ game }} de l'époque {{ epoch }} : {{ user }} avec {{ value }} (à {{ difference }})."
+        }
+        MessageKey::WebhookEpochRollover => "L'époque {{ epoch }} de {{ game }} a commencé.",
+        MessageKey::OptedOut => {
+            "{{ user }}, tes essais ne seront plus enregistrés. Utilise {{ command }} optin pour rejouer."
+        }
+        MessageKey::OptedIn => "{{ user }}, tes essais sont de nouveau enregistrés.",
+        MessageKey::EpochReset => {
+            "Un modérateur a réinitialisé {{ game }}, l'époque {{ epoch }} a commencé."
+        }
+        MessageKey::AttemptVoided => "Le {{ game }} de {{ user }} avec {{ value }} a été annulé.",
+        MessageKey::NothingToVoid => "Il n'y a pas d'essai de ce type à annuler.",
+        MessageKey::VoidUsage => {
+            "Annule des essais avec {{ command }} void <id de l'essai> ou {{ command }} void @user last."
+        }
+        MessageKey::PeriodBest => {
+            "Meilleur {{ game }} de {{ period }} par {{ user }} : {{ value }}"
+        }
+        MessageKey::PeriodWorst => "Pire {{ game }} de {{ period }} par {{ user }} : {{ value }}",
+        MessageKey::PeriodPersonalBest => {
+            "Meilleur {{ game }} personnel de {{ period }} par {{ user }} : {{ value }}"
+        }
+        MessageKey::PeriodPersonalWorst => {
+            "Pire {{ game }} personnel de {{ period }} par {{ user }} : {{ value }}"
+        }
+        MessageKey::NoAttemptsInPeriod => "Pas encore d'essais de {{ game }} pour {{ period }}.",
+        MessageKey::NoSeason => "Aucune saison n'est en cours.",
+        MessageKey::SeasonSummary => {
+            "La saison {{ period }} dure jusqu'au {{ date }}. {{ user }} mène en {{ game }} avec {{ value }}."
+        }
+        MessageKey::SeasonSummaryEmpty => {
+            "La saison {{ period }} dure jusqu'au {{ date }}. Personne n'a encore joué à {{ game }}."
+        }
+        MessageKey::SeasonAdded => "Saison {{ period }} ajoutée.",
+        MessageKey::SeasonDeleted => "Saison {{ period }} supprimée.",
+        MessageKey::SeasonNotFound => "Il n'y a pas de saison {{ period }}.",
+        MessageKey::SeasonExists => "La saison {{ period }} existe déjà ou chevauche la nouvelle.",
+        MessageKey::SeasonUsage => {
+            "Ajoute des saisons avec {{ command }} season add <premier jour> <dernier jour> <nom>, avec des jours comme 2025-03-28."
+        }
+        MessageKey::SeasonWinner => {
+            "La saison {{ period }} est terminée ! {{ user }} remporte {{ game }} avec {{ value }}."
+        }
+        MessageKey::SeasonOverNoWinner => {
+            "La saison {{ period }} est terminée, mais personne n'a joué."
+        }
+        MessageKey::PeriodToday => "aujourd'hui",
+        MessageKey::PeriodWeek => "cette semaine",
+        MessageKey::PeriodMonth => "ce mois-ci",
+        MessageKey::PeriodAllTime => "tous les temps",
+        MessageKey::AchievementUnlocked => "{{ user }} a débloqué {{ achievement }} !",
+        MessageKey::AchievementList => "Succès de {{ user }} dans {{ game }} : {{ achievement }}",
+        MessageKey::NoAchievements => {
+            "{{ user }} n'a encore débloqué aucun succès dans {{ game }}."
+        }
+        MessageKey::AchievementCloseStreak => "Série chaude (3 lancers proches d'affilée)",
+        MessageKey::AchievementFirstOfTheDay => "Lève-tôt (premier lancer du jour)",
+        MessageKey::AchievementHundredth => "Centurion (100 lancers)",
+        MessageKey::AchievementMinimum => "Au plus bas (le lancer le plus bas possible)",
+        MessageKey::AchievementMaximum => "Au sommet (le lancer le plus haut possible)",
+        MessageKey::AchievementPersonalBest => "Nouveau record personnel",
+        MessageKey::NotLive => "La chaîne n'est pas en direct pour le moment.",
+        MessageKey::PeriodStream => "ce stream",
+        MessageKey::Stats => {
+            "{{ user }} a joué à {{ game }} {{ attempts }} fois, dont {{ perfects }} parfaites."
+        }
+        MessageKey::WhisperUsage => {
+            "Chuchote-moi pb, pw, stats ou achievements suivi d'une chaîne, p. ex. \"stats somechannel\". Ajoute un jeu après la chaîne pour les jeux autres que 45."
+        }
+    };
+
+    Some(message)
+}
@@ -0,0 +1,283 @@
+//! An overlay for OBS browser sources, showing the latest roll and the current best of a channel.
+//! The page connects to the channel's [`Overlay`] Durable Object with a WebSocket, which rolls
+//! are published to as they happen. Overlay URLs carry a per-channel secret, which is created and
+//! replaced through the dashboard.
+
+use serde::{Deserialize, Serialize};
+use worker::*;
+
+use crate::config::CREDENTIALS_KV;
+use crate::dashboard::html::escape;
+
+/// The Durable Object namespace of [`Overlay`].
+pub const OVERLAY_DO: &str = "Overlay";
+
+/// A roll as shown on the overlay, with values already formatted for the channel.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OverlayEvent {
+    pub game: String,
+    pub epoch: i64,
+    pub user_name: String,
+    pub value: String,
+    pub difference: String,
+    pub perfect: bool,
+    /// The best attempt of the epoch, including this one.
+    pub best: Option<OverlayBest>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OverlayBest {
+    pub user_name: String,
+    pub value: String,
+    pub difference: String,
+}
+
+fn secret_key(broadcaster_user_id: &str) -> String {
+    format!("overlay;broadcaster={broadcaster_user_id}")
+}
+
+/// The overlay secret of a channel, `None` if the channel never set up the overlay.
+pub async fn secret(env: &Env, broadcaster_user_id: &str) -> Result<Option<String>> {
+    Ok(env
+        .kv(CREDENTIALS_KV)?
+        .get(&secret_key(broadcaster_user_id))
+        .text()
+        .await?)
+}
+
+/// Replaces the overlay secret of a channel, disconnecting overlays that used the old one.
+pub async fn set_secret(env: &Env, broadcaster_user_id: &str, secret: &str) -> Result<()> {
+    env.kv(CREDENTIALS_KV)?
+        .put(&secret_key(broadcaster_user_id), secret)?
+        .execute()
+        .await?;
+
+    let request = Request::new_with_init(
+        "https://overlay/disconnect",
+        RequestInit::new().with_method(Method::Post),
+    )?;
+
+    stub(env, broadcaster_user_id)?
+        .fetch_with_request(request)
+        .await?;
+
+    Ok(())
+}
+
+/// Sends a roll to the connected overlays of a channel. Check for a [`secret`] first, channels
+/// without one have no overlays to send to.
+pub async fn publish(env: &Env, broadcaster_user_id: &str, event: &OverlayEvent) -> Result<()> {
+    let headers = Headers::new();
+    headers.set("Content-Type", "application/json")?;
+
+    let request = Request::new_with_init(
+        "https://overlay/publish",
+        RequestInit::new()
+            .with_method(Method::Post)
+            .with_headers(headers)
+            .with_body(Some(serde_json::to_string(event)?.into())),
+    )?;
+
+    stub(env, broadcaster_user_id)?
+        .fetch_with_request(request)
+        .await?;
+
+    Ok(())
+}
+
+fn stub(env: &Env, broadcaster_user_id: &str) -> Result<Stub> {
+    env.durable_object(OVERLAY_DO)?
+        .id_from_name(broadcaster_user_id)?
+        .get_stub()
+}
+
+/// Whether the `secret` query parameter of the request matches the channel's overlay secret.
+async fn is_authorized(req: &Request, env: &Env, broadcaster_user_id: &str) -> Result<bool> {
+    let provided = req
+        .url()?
+        .query_pairs()
+        .find(|(key, _)| key == "secret")
+        .map(|(_, value)| value.into_owned());
+
+    let (Some(provided), Some(secret)) = (provided, secret(env, broadcaster_user_id).await?) else {
+        return Ok(false);
+    };
+
+    Ok(constant_time_eq::constant_time_eq(
+        provided.as_bytes(),
+        secret.as_bytes(),
+    ))
+}
+
+/// `GET /overlay/:broadcaster_user_id?secret=...`: The page to add as a browser source.
+pub async fn page(req: Request, ctx: RouteContext<Context>) -> Result<Response> {
+    let Some(broadcaster_user_id) = ctx.param("broadcaster_user_id") else {
+        return Ok(Response::builder()
+            .with_status(400)
+            .body(ResponseBody::Empty));
+    };
+
+    if !is_authorized(&req, &ctx.env, broadcaster_user_id).await? {
+        return Ok(Response::builder()
+            .with_status(401)
+            .body(ResponseBody::Empty));
+    }
+
+    let html = OVERLAY_HTML.replace("{{ channel }}", &escape(broadcaster_user_id));
+
+    Response::from_html(html)
+}
+
+/// `GET /overlay/:broadcaster_user_id/ws?secret=...`: The WebSocket the overlay page listens on.
+pub async fn websocket(req: Request, ctx: RouteContext<Context>) -> Result<Response> {
+    let Some(broadcaster_user_id) = ctx.param("broadcaster_user_id") else {
+        return Ok(Response::builder()
+            .with_status(400)
+            .body(ResponseBody::Empty));
+    };
+
+    if req.headers().get("Upgrade")?.as_deref() != Some("websocket") {
+        return Ok(Response::builder()
+            .with_status(426)
+            .body(ResponseBody::Empty));
+    }
+
+    if !is_authorized(&req, &ctx.env, broadcaster_user_id).await? {
+        return Ok(Response::builder()
+            .with_status(401)
+            .body(ResponseBody::Empty));
+    }
+
+    stub(&ctx.env, broadcaster_user_id)?
+        .fetch_with_request(req)
+        .await
+}
+
+/// Keeps the WebSockets of a channel's overlays and the last roll, so overlays that connect show
+/// something right away. WebSockets are hibernated between rolls.
+#[durable_object]
+pub struct Overlay {
+    state: State,
+}
+
+/// Storage key of the last published event.
+const LAST_EVENT: &str = "last";
+
+impl DurableObject for Overlay {
+    fn new(state: State, _env: Env) -> Self {
+        Self { state }
+    }
+
+    async fn fetch(&self, mut req: Request) -> Result<Response> {
+        if req.headers().get("Upgrade")?.as_deref() == Some("websocket") {
+            let pair = WebSocketPair::new()?;
+            self.state.accept_web_socket(&pair.server);
+
+            if let Some(last) = self.state.storage().get::<OverlayEvent>(LAST_EVENT).await? {
+                pair.server.send(&last)?;
+            }
+
+            return Response::from_websocket(pair.client);
+        }
+
+        match req.path().as_str() {
+            "/publish" => {
+                let event: OverlayEvent = req.json().await?;
+                self.state.storage().put(LAST_EVENT, &event).await?;
+
+                for ws in self.state.get_websockets() {
+                    // Sockets that went away are cleaned up when they close.
+                    let _ = ws.send(&event);
+                }
+            }
+            "/disconnect" => {
+                for ws in self.state.get_websockets() {
+                    let _ = ws.close(Some(1008), Some("The overlay URL was replaced"));
+                }
+            }
+            _ => {
+                return Ok(Response::builder()
+                    .with_status(404)
+                    .body(ResponseBody::Empty));
+            }
+        }
+
+        Ok(Response::builder()
+            .with_status(204)
+            .body(ResponseBody::Empty))
+    }
+
+    async fn websocket_message(
+        &self,
+        _ws: WebSocket,
+        _message: WebSocketIncomingMessage,
+    ) -> Result<()> {
+        // The overlay only listens.
+        Ok(())
+    }
+
+    async fn websocket_close(
+        &self,
+        ws: WebSocket,
+        code: usize,
+        reason: String,
+        _was_clean: bool,
+    ) -> Result<()> {
+        ws.close(Some(code as u16), Some(reason))
+    }
+}
+
+const OVERLAY_HTML: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<style>
+body { margin: 0; font-family: system-ui, sans-serif; color: #fff; text-shadow: 0 0 4px #000; background: transparent; }
+#overlay { padding: 1rem; font-size: 2rem; }
+#best { font-size: 1.25rem; opacity: 0.85; }
+.perfect #latest { color: #ffd700; }
+</style>
+</head>
+<body>
+<div id="overlay">
+<div id="latest"></div>
+<div id="best"></div>
+</div>
+<script>
+const secret = new URLSearchParams(location.search).get("secret");
+const url = new URL("/overlay/{{ channel }}/ws", location.href);
+url.protocol = location.protocol === "https:" ? "wss:" : "ws:";
+url.searchParams.set("secret", secret);
+
+let delay = 1000;
+
+function connect() {
+  const ws = new WebSocket(url);
+
+  ws.onopen = () => { delay = 1000; };
+
+  ws.onmessage = (message) => {
+    const event = JSON.parse(message.data);
+
+    document.getElementById("latest").textContent =
+      `${event.user_name}: ${event.value}`;
+    document.getElementById("best").textContent = event.best
+      ? `Best: ${event.best.user_name} ${event.best.value}`
+      : "";
+    document.getElementById("overlay").className = event.perfect ? "perfect" : "";
+  };
+
+  ws.onclose = (close) => {
+    // A replaced overlay URL won't work again.
+    if (close.code === 1008) return;
+
+    setTimeout(connect, delay);
+    delay = Math.min(delay * 2, 30000);
+  };
+}
+
+connect();
+</script>
+</body>
+</html>
+"#;
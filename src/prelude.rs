@@ -1,5 +1,7 @@
 pub use crate::config::*;
 pub use crate::error::*;
+pub use crate::messages::*;
+pub use crate::template::*;
 pub use crate::twitch::*;
 pub use twitch_api::{HelixClient, eventsub::channel::ChannelChatMessageV1Payload, types::*};
 pub use worker::{Env, console_debug, console_error, console_log};
@@ -0,0 +1,160 @@
+//! Playing games with channel points. Rewards are mapped to games through
+//! [`crate::config::CommandConfig::rewards`], and redeeming one runs `!45 gen` for the chatter.
+//! Redemptions are fulfilled once the game was played, and refunded when it wasn't, e.g. because
+//! the chatter is timed out.
+//!
+//! Fulfilling and refunding needs a token of the broadcaster, which they grant by logging in to the
+//! dashboard through `/dashboard/login?redemptions`. Twitch only lets the bot do so for rewards
+//! created with its client ID, redemptions of other rewards are left for the broadcaster.
+
+use twitch_api::eventsub::{
+    Transport,
+    channel::{
+        ChannelPointsCustomRewardRedemptionAddV1, ChannelPointsCustomRewardRedemptionAddV1Payload,
+        channel_points_custom_reward_redemption::RedemptionStatus,
+    },
+};
+use twitch_api::helix::points::{
+    CustomRewardRedemptionStatus, UpdateRedemptionStatusBody, UpdateRedemptionStatusRequest,
+};
+
+use crate::commands::{CallableV2, Context, Deferred, claim_message, forty_five::Generate};
+use crate::prelude::*;
+
+fn token_key(broadcaster_user_id: &str) -> String {
+    format!("redemptions_token:{broadcaster_user_id}")
+}
+
+/// Keeps the token a broadcaster granted for managing redemptions.
+pub async fn store_token(
+    env: &Env,
+    broadcaster_user_id: &str,
+    access_token: &str,
+    refresh_token: &str,
+) -> BotResult<()> {
+    store_user_token(
+        env,
+        &token_key(broadcaster_user_id),
+        access_token,
+        refresh_token,
+    )
+    .await
+}
+
+/// Subscribes to redemptions of a channel's rewards, delivered to `callback`. Twitch only accepts
+/// this once the broadcaster granted the token.
+pub async fn subscribe(env: &Env, broadcaster_user_id: &UserId, callback: &str) -> BotResult<()> {
+    let http_client = HelixClient::with_client(FetchClient::default());
+    let token = get_credentials(env).await?;
+    let secret = env.secret("HMAC_SECRET")?.to_string();
+
+    http_client
+        .create_eventsub_subscription(
+            ChannelPointsCustomRewardRedemptionAddV1::broadcaster_user_id(
+                broadcaster_user_id.clone(),
+            ),
+            Transport::webhook(callback, secret),
+            &token,
+        )
+        .await?;
+
+    Ok(())
+}
+
+pub async fn redeem(env: Env, payload: ChannelPointsCustomRewardRedemptionAddV1Payload) {
+    let config = CommandConfig::load(&env, &payload.broadcaster_user_id).await;
+
+    let Some(game) = config
+        .rewards
+        .get(payload.reward.id.as_str())
+        .and_then(|game| config.game(game))
+    else {
+        // Not a reward of the bot.
+        return;
+    };
+
+    if !claim_message(&env, payload.id.as_str()).await {
+        // Twitch delivered the redemption again.
+        return;
+    }
+
+    let broadcaster_user_id = payload.broadcaster_user_id.clone();
+    let reward_id = payload.reward.id.clone();
+    let redemption_id = payload.id.clone();
+
+    // Rewards that skip the request queue are fulfilled right away, and
+    // can't be refunded.
+    let pending = matches!(payload.status, RedemptionStatus::Unfulfilled);
+
+    let deferred = Deferred::default();
+
+    let context = Context {
+        env: env.clone(),
+        payload,
+        config,
+        game,
+        deferred: deferred.clone(),
+    };
+
+    let result = Generate {}.call(context).await;
+
+    let played = match result {
+        Ok(Some(message)) => match send_message(&env, &broadcaster_user_id, &message).await {
+            Ok(()) => true,
+            Err(e) => {
+                // The game was played and recorded, so the points were spent.
+                console_error!("Error sending redemption result: {:?}", e);
+                true
+            }
+        },
+        Ok(None) => false,
+        Err(e) => {
+            // Generate only fails before the roll is recorded, so nothing was played.
+            console_error!("Error playing redemption: {:?}", e);
+            false
+        }
+    };
+
+    deferred.run().await;
+
+    if !pending {
+        return;
+    }
+
+    let status = if played {
+        CustomRewardRedemptionStatus::Fulfilled
+    } else {
+        CustomRewardRedemptionStatus::Canceled
+    };
+
+    let updated: BotResult<()> = async {
+        let Some(token) = user_token(&env, &token_key(broadcaster_user_id.as_str())).await? else {
+            console_log!(
+                "No redemptions token for {}, leaving redemption {} as is",
+                broadcaster_user_id,
+                redemption_id
+            );
+            return Ok(());
+        };
+
+        let http_client = HelixClient::with_client(FetchClient::default());
+        let request =
+            UpdateRedemptionStatusRequest::new(&broadcaster_user_id, &reward_id, &redemption_id);
+
+        http_client
+            .req_patch(request, UpdateRedemptionStatusBody::status(status), &token)
+            .await?;
+
+        Ok(())
+    }
+    .await;
+
+    if let Err(e) = updated {
+        console_error!(
+            "Error updating redemption {} of {}: {:?}",
+            redemption_id,
+            broadcaster_user_id,
+            e
+        );
+    }
+}
@@ -0,0 +1,125 @@
+//! Closing seasons once they're over, which runs on a schedule. Winners of every game played in a
+//! season are announced in the channel's chat, see [`crate::db::Season`].
+
+use sqlx_d1::D1Connection;
+
+use crate::db::{self, Season};
+use crate::prelude::*;
+
+/// Announces the winners of every season that ended since the last run.
+pub async fn close_ended(env: &Env) -> BotResult<()> {
+    let db_conn = D1Connection::new(env.d1(FORTYFIVE_DATA)?);
+
+    let now: i64 = web_time::SystemTime::now()
+        .duration_since(web_time::SystemTime::UNIX_EPOCH)?
+        .as_millis()
+        .try_into()?;
+
+    for season in db::seasons_to_close(&db_conn, now).await? {
+        // Closed before announcing, so a failing announcement isn't
+        // repeated on every run.
+        db::close_season(&db_conn, season.id, now).await?;
+
+        if let Err(e) = announce(env, &db_conn, &season).await {
+            console_error!(
+                "Error announcing the end of season {} of {}: {:?}",
+                season.name,
+                season.broadcaster_user_id,
+                e
+            );
+        }
+    }
+
+    Ok(())
+}
+
+async fn announce(env: &Env, db_conn: &D1Connection, season: &Season) -> BotResult<()> {
+    let broadcaster_user_id = UserId::new(season.broadcaster_user_id.clone());
+    let config = CommandConfig::load(env, &broadcaster_user_id).await;
+    let locale = config.locale();
+
+    let mut winners = Vec::new();
+
+    for game in config.all_games() {
+        let winner = db::optional(
+            db::period_record(
+                db_conn,
+                broadcaster_user_id.as_str(),
+                &game.name,
+                season.starts_at,
+                season.ends_at,
+                None,
+                false,
+            )
+            .await,
+        )?;
+
+        if let Some(winner) = winner {
+            winners.push((game, winner));
+        }
+    }
+
+    let http_client = HelixClient::with_client(FetchClient::default());
+    let token = get_credentials(env).await?;
+
+    let mut user_ids = winners
+        .iter()
+        .map(|(_, winner)| winner.chatter_user_id.as_str())
+        .collect::<Vec<_>>();
+    user_ids.sort_unstable();
+    user_ids.dedup();
+
+    let user_ids = user_ids
+        .into_iter()
+        .map(UserIdRef::from_str)
+        .collect::<Vec<_>>();
+
+    let names = get_display_names(&http_client, &user_ids, &token).await?;
+
+    let messages = if winners.is_empty() {
+        let vars = Vars::new().with(Variable::Period, &season.name);
+
+        vec![
+            config
+                .template(MessageKey::SeasonOverNoWinner)
+                .render(&vars),
+        ]
+    } else {
+        winners
+            .iter()
+            .map(|(game, winner)| {
+                let user_name = names
+                    .get(&winner.chatter_user_id)
+                    .unwrap_or(&winner.chatter_user_id);
+
+                let vars = Vars::new()
+                    .with(Variable::Period, &season.name)
+                    .with(Variable::User, user_name)
+                    .with(
+                        Variable::Value,
+                        game.rules.format_value(winner.forty_five_value, locale),
+                    )
+                    .with(Variable::Game, &game.name)
+                    .with(
+                        Variable::Target,
+                        game.rules.format_value(game.rules.target, locale),
+                    );
+
+                config.template(MessageKey::SeasonWinner).render(&vars)
+            })
+            .collect()
+    };
+
+    for message in messages {
+        http_client
+            .send_chat_message(
+                &broadcaster_user_id,
+                UserId::from_static(BOT_USER_ID),
+                &*message,
+                &token,
+            )
+            .await?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,95 @@
+//! Tracking when channels are live, from stream.online and stream.offline events. Attempts are
+//! tagged with the stream they were made during, for `!45 best stream`, and channels can choose to
+//! only play while live through [`crate::config::CommandConfig::live_only`].
+
+use sqlx_d1::D1Connection;
+use twitch_api::eventsub::{
+    Transport,
+    stream::{StreamOfflineV1, StreamOfflineV1Payload, StreamOnlineV1, StreamOnlineV1Payload},
+};
+
+use crate::db;
+use crate::prelude::*;
+
+/// Subscribes to stream.online and stream.offline of a channel, delivered to `callback`. Twitch
+/// rejects subscriptions that already exist.
+pub async fn subscribe(env: &Env, broadcaster_user_id: &UserId, callback: &str) -> BotResult<()> {
+    let http_client = HelixClient::with_client(FetchClient::default());
+    let token = get_credentials(env).await?;
+    let secret = env.secret("HMAC_SECRET")?.to_string();
+
+    http_client
+        .create_eventsub_subscription(
+            StreamOnlineV1::broadcaster_user_id(broadcaster_user_id.clone()),
+            Transport::webhook(callback, secret.clone()),
+            &token,
+        )
+        .await?;
+
+    http_client
+        .create_eventsub_subscription(
+            StreamOfflineV1::broadcaster_user_id(broadcaster_user_id.clone()),
+            Transport::webhook(callback, secret),
+            &token,
+        )
+        .await?;
+
+    Ok(())
+}
+
+pub async fn online(env: Env, payload: StreamOnlineV1Payload) {
+    let started: BotResult<()> = async {
+        let db_conn = D1Connection::new(env.d1(FORTYFIVE_DATA)?);
+
+        let started_at = match crate::time::parse_iso_timestamp(payload.started_at.as_str()) {
+            Some(started_at) => started_at,
+            None => web_time::SystemTime::now()
+                .duration_since(web_time::SystemTime::UNIX_EPOCH)?
+                .as_millis()
+                .try_into()?,
+        };
+
+        db::start_stream(
+            &db_conn,
+            payload.broadcaster_user_id.as_str(),
+            payload.id.as_str(),
+            started_at,
+        )
+        .await?;
+
+        Ok(())
+    }
+    .await;
+
+    if let Err(e) = started {
+        console_error!(
+            "Error recording the stream of {}: {:?}",
+            payload.broadcaster_user_id,
+            e
+        );
+    }
+}
+
+pub async fn offline(env: Env, payload: StreamOfflineV1Payload) {
+    let ended: BotResult<()> = async {
+        let db_conn = D1Connection::new(env.d1(FORTYFIVE_DATA)?);
+
+        let now: i64 = web_time::SystemTime::now()
+            .duration_since(web_time::SystemTime::UNIX_EPOCH)?
+            .as_millis()
+            .try_into()?;
+
+        db::end_streams(&db_conn, payload.broadcaster_user_id.as_str(), now).await?;
+
+        Ok(())
+    }
+    .await;
+
+    if let Err(e) = ended {
+        console_error!(
+            "Error ending the stream of {}: {:?}",
+            payload.broadcaster_user_id,
+            e
+        );
+    }
+}
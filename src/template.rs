@@ -0,0 +1,221 @@
+//! A small template language for the bot's responses. Templates are plain text with variables
+//! written as `{{ user }}`, which are replaced when rendering.
+
+use crate::error::TemplateError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Variable {
+    /// Display name of the chatter the response is about.
+    User,
+    /// A generated value, formatted with the precision of the game.
+    Value,
+    /// How far the value is from the target.
+    Difference,
+    Epoch,
+    /// Position of the chatter's personal best (or worst) in the current epoch.
+    Rank,
+    /// The chatter's personal best in the current epoch.
+    Pb,
+    /// Name of the game, e.g. `45`.
+    Game,
+    /// The command that starts the game, e.g. `!45`.
+    Command,
+    /// The value chatters are aiming for.
+    Target,
+    /// Length of a timeout in seconds.
+    Secs,
+    /// A configuration key, e.g. `forty_five.perfect_45_message`.
+    Key,
+    /// Why something the chatter asked for failed.
+    Error,
+    /// What records are taken from instead of the current epoch, e.g. the name of a season.
+    Period,
+    /// A date such as the end of a season, e.g. `2025-03-28`.
+    Date,
+    /// Name of an achievement, e.g. `Hundredth attempt`.
+    Achievement,
+    /// Number of attempts a chatter made.
+    Attempts,
+    /// Number of those attempts that hit the target.
+    Perfects,
+}
+
+impl Variable {
+    fn from_name(name: &str) -> Option<Self> {
+        let variable = match name {
+            "user" => Self::User,
+            "value" => Self::Value,
+            "difference" => Self::Difference,
+            "epoch" => Self::Epoch,
+            "rank" => Self::Rank,
+            "pb" => Self::Pb,
+            "game" => Self::Game,
+            "command" => Self::Command,
+            "target" => Self::Target,
+            "secs" => Self::Secs,
+            "key" => Self::Key,
+            "error" => Self::Error,
+            "period" => Self::Period,
+            "date" => Self::Date,
+            "achievement" => Self::Achievement,
+            "attempts" => Self::Attempts,
+            "perfects" => Self::Perfects,
+            _ => return None,
+        };
+
+        Some(variable)
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Segment {
+    Text(String),
+    Variable(Variable),
+}
+
+#[derive(Debug, Clone)]
+pub struct Template {
+    segments: Vec<Segment>,
+}
+
+impl Template {
+    /// Parses a template, rejecting variables that aren't in `allowed`.
+    pub fn parse(source: &str, allowed: &[Variable]) -> Result<Self, TemplateError> {
+        let mut segments = Vec::new();
+        let mut rest = source;
+
+        while let Some(start) = rest.find("{{") {
+            if start > 0 {
+                segments.push(Segment::Text(rest[..start].to_owned()));
+            }
+
+            let after = &rest[start + 2..];
+            let end = after.find("}}").ok_or(TemplateError::Unclosed)?;
+            let name = after[..end].trim();
+
+            let variable = Variable::from_name(name)
+                .ok_or_else(|| TemplateError::UnknownVariable(name.to_owned()))?;
+
+            if !allowed.contains(&variable) {
+                return Err(TemplateError::UnavailableVariable(name.to_owned()));
+            }
+
+            segments.push(Segment::Variable(variable));
+            rest = &after[end + 2..];
+        }
+
+        if !rest.is_empty() {
+            segments.push(Segment::Text(rest.to_owned()));
+        }
+
+        Ok(Self { segments })
+    }
+
+    /// Whether the template contains a variable, to skip computing values that aren't shown.
+    pub fn uses(&self, variable: Variable) -> bool {
+        self.segments
+            .iter()
+            .any(|segment| matches!(segment, Segment::Variable(v) if *v == variable))
+    }
+
+    /// Renders the template, variables without a value are left empty.
+    pub fn render(&self, vars: &Vars) -> String {
+        let mut output = String::new();
+
+        for segment in &self.segments {
+            match segment {
+                Segment::Text(text) => output.push_str(text),
+                Segment::Variable(variable) => {
+                    if let Some(value) = vars.get(*variable) {
+                        output.push_str(value);
+                    }
+                }
+            }
+        }
+
+        output
+    }
+}
+
+/// Values for the variables of a template.
+#[derive(Debug, Default)]
+pub struct Vars(Vec<(Variable, String)>);
+
+impl Vars {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with(mut self, variable: Variable, value: impl ToString) -> Self {
+        self.0.push((variable, value.to_string()));
+        self
+    }
+
+    fn get(&self, variable: Variable) -> Option<&str> {
+        self.0
+            .iter()
+            .find(|(v, _)| *v == variable)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALLOWED: &[Variable] = &[Variable::User, Variable::Value];
+
+    #[test]
+    fn renders_variables() {
+        let template = Template::parse("{{ user }} got {{value}}!", ALLOWED).unwrap();
+        let vars = Vars::new()
+            .with(Variable::User, "someone")
+            .with(Variable::Value, "45.000");
+
+        assert_eq!(template.render(&vars), "someone got 45.000!");
+        assert!(template.uses(Variable::Value));
+        assert!(!template.uses(Variable::Epoch));
+    }
+
+    #[test]
+    fn leaves_missing_values_empty() {
+        let template = Template::parse("[{{ user }}]", ALLOWED).unwrap();
+
+        assert_eq!(template.render(&Vars::new()), "[]");
+    }
+
+    #[test]
+    fn keeps_plain_text() {
+        let template = Template::parse("no variables } here", ALLOWED).unwrap();
+
+        assert_eq!(template.render(&Vars::new()), "no variables } here");
+    }
+
+    #[test]
+    fn rejects_unknown_variables() {
+        assert!(matches!(
+            Template::parse("{{ chatter_user_name }}", ALLOWED),
+            Err(TemplateError::UnknownVariable(name)) if name == "chatter_user_name"
+        ));
+    }
+
+    #[test]
+    fn rejects_unavailable_variables() {
+        assert!(matches!(
+            Template::parse("{{ epoch }}", ALLOWED),
+            Err(TemplateError::UnavailableVariable(name)) if name == "epoch"
+        ));
+    }
+
+    #[test]
+    fn rejects_unclosed_variables() {
+        assert!(matches!(
+            Template::parse("{{ user }} got {{ value", ALLOWED),
+            Err(TemplateError::Unclosed)
+        ));
+        assert!(matches!(
+            Template::parse("{{ user }", ALLOWED),
+            Err(TemplateError::Unclosed)
+        ));
+    }
+}
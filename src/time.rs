@@ -0,0 +1,214 @@
+//! Conversions between milliseconds since the Unix epoch and calendar dates, for the few places
+//! that read or write dates. Dates are in UTC except for [`calendar_span`] which follows a
+//! channel's timezone.
+
+use chrono::{DateTime, Datelike, Days, Months, NaiveDate, NaiveDateTime, SecondsFormat, TimeZone};
+use chrono_tz::Tz;
+
+pub const DAY_MILLIS: i64 = 86_400_000;
+
+/// Formats milliseconds since the Unix epoch as an ISO 8601 timestamp in UTC, e.g.
+/// `2025-03-28T12:34:56.789Z`.
+pub fn iso_timestamp(millis: i64) -> String {
+    DateTime::from_timestamp_millis(millis)
+        .unwrap_or_default()
+        .to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+/// Formats the UTC date of milliseconds since the Unix epoch, e.g. `2025-03-28`.
+pub fn iso_date(millis: i64) -> String {
+    DateTime::from_timestamp_millis(millis)
+        .unwrap_or_default()
+        .format("%Y-%m-%d")
+        .to_string()
+}
+
+/// Reads a date such as `2025-03-28` as days since 1970-01-01.
+pub fn parse_date(date: &str) -> Option<i64> {
+    let date = NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d").ok()?;
+
+    Some(
+        date.signed_duration_since(DateTime::UNIX_EPOCH.date_naive())
+            .num_days(),
+    )
+}
+
+/// Reads an ISO 8601 timestamp such as `2024-12-31T18:00:00Z` as milliseconds since the Unix
+/// epoch. Timestamps without an offset are taken as UTC.
+pub fn parse_iso_timestamp(timestamp: &str) -> Option<i64> {
+    let timestamp = timestamp.trim();
+
+    if let Ok(time) = DateTime::parse_from_rfc3339(timestamp) {
+        return Some(time.timestamp_millis());
+    }
+
+    [
+        "%Y-%m-%dT%H:%M:%S%.f",
+        "%Y-%m-%d %H:%M:%S%.f",
+        "%Y-%m-%dT%H:%M",
+    ]
+    .into_iter()
+    .find_map(|format| NaiveDateTime::parse_from_str(timestamp, format).ok())
+    .map(|time| time.and_utc().timestamp_millis())
+}
+
+/// A calendar span in a timezone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Calendar {
+    Day,
+    /// Weeks start on Monday.
+    Week,
+    Month,
+}
+
+/// The day, week or month `now` is in, as milliseconds since the Unix epoch from inclusive to
+/// exclusive. Both are midnight in the timezone, or the first moment after it when midnight is
+/// skipped by a daylight saving change.
+pub fn calendar_span(calendar: Calendar, now: i64, timezone: Tz) -> (i64, i64) {
+    let today = DateTime::from_timestamp_millis(now)
+        .unwrap_or_default()
+        .with_timezone(&timezone)
+        .date_naive();
+
+    let (first, next) = match calendar {
+        Calendar::Day => (today, today + Days::new(1)),
+        Calendar::Week => {
+            let monday = today - Days::new(today.weekday().num_days_from_monday().into());
+            (monday, monday + Days::new(7))
+        }
+        Calendar::Month => {
+            let first = today.with_day(1).unwrap_or(today);
+            (first, first + Months::new(1))
+        }
+    };
+
+    (
+        local_midnight(first, timezone),
+        local_midnight(next, timezone),
+    )
+}
+
+fn local_midnight(date: NaiveDate, timezone: Tz) -> i64 {
+    let midnight = date.and_hms_opt(0, 0, 0).unwrap_or_default();
+
+    match timezone.from_local_datetime(&midnight).earliest() {
+        Some(midnight) => midnight.timestamp_millis(),
+        // Midnight doesn't exist that day, daylight saving time starts
+        // at midnight and skips an hour at most.
+        None => timezone
+            .from_local_datetime(&(midnight + chrono::TimeDelta::hours(1)))
+            .earliest()
+            .map(|time| time.timestamp_millis())
+            .unwrap_or_else(|| midnight.and_utc().timestamp_millis()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn millis(timestamp: &str) -> i64 {
+        DateTime::parse_from_rfc3339(timestamp)
+            .unwrap()
+            .timestamp_millis()
+    }
+
+    #[test]
+    fn parses_dates() {
+        assert_eq!(parse_date("1970-01-01"), Some(0));
+        assert_eq!(parse_date(" 2024-02-29 "), Some(19_782));
+        assert_eq!(parse_date("1969-12-31"), Some(-1));
+        assert_eq!(parse_date("2025-02-31"), None);
+        assert_eq!(parse_date("2025-02-29"), None);
+        assert_eq!(parse_date("2025-13-01"), None);
+        assert_eq!(parse_date("28.03.2025"), None);
+    }
+
+    #[test]
+    fn parses_timestamps() {
+        assert_eq!(parse_iso_timestamp("1970-01-01T00:00:01Z"), Some(1000));
+        assert_eq!(
+            parse_iso_timestamp("2024-12-31T18:00:00.250+01:00"),
+            Some(millis("2024-12-31T17:00:00.250Z"))
+        );
+        assert_eq!(
+            parse_iso_timestamp("2024-12-31 18:00:00"),
+            Some(millis("2024-12-31T18:00:00Z"))
+        );
+        assert_eq!(
+            parse_iso_timestamp("2024-12-31T18:00"),
+            Some(millis("2024-12-31T18:00:00Z"))
+        );
+        assert_eq!(parse_iso_timestamp("2024-12-31T99:00:00Z"), None);
+        assert_eq!(parse_iso_timestamp("2024-12-31T18:75:00Z"), None);
+        assert_eq!(parse_iso_timestamp("2025-02-31T18:00:00Z"), None);
+        assert_eq!(parse_iso_timestamp("yesterday"), None);
+    }
+
+    #[test]
+    fn formats_timestamps() {
+        let time = millis("2025-03-28T12:34:56.789Z");
+
+        assert_eq!(iso_timestamp(time), "2025-03-28T12:34:56.789Z");
+        assert_eq!(iso_date(time), "2025-03-28");
+        assert_eq!(iso_date(-1), "1969-12-31");
+        assert_eq!(parse_iso_timestamp(&iso_timestamp(time)), Some(time));
+    }
+
+    #[test]
+    fn calendar_spans_follow_daylight_saving() {
+        let berlin: Tz = "Europe/Berlin".parse().unwrap();
+        // Clocks go forward at 02:00 on Sunday, 2025-03-30.
+        let now = millis("2025-03-30T12:00:00Z");
+
+        assert_eq!(
+            calendar_span(Calendar::Day, now, berlin),
+            (
+                millis("2025-03-29T23:00:00Z"),
+                millis("2025-03-30T22:00:00Z")
+            )
+        );
+        assert_eq!(
+            calendar_span(Calendar::Week, now, berlin),
+            (
+                millis("2025-03-23T23:00:00Z"),
+                millis("2025-03-30T22:00:00Z")
+            )
+        );
+        assert_eq!(
+            calendar_span(Calendar::Month, now, berlin),
+            (
+                millis("2025-02-28T23:00:00Z"),
+                millis("2025-03-31T22:00:00Z")
+            )
+        );
+    }
+
+    #[test]
+    fn calendar_spans_start_after_skipped_midnights() {
+        let santiago: Tz = "America/Santiago".parse().unwrap();
+        // Clocks go from 00:00 straight to 01:00 on 2024-09-08.
+        let now = millis("2024-09-08T12:00:00Z");
+
+        assert_eq!(
+            calendar_span(Calendar::Day, now, santiago),
+            (
+                millis("2024-09-08T04:00:00Z"),
+                millis("2024-09-09T03:00:00Z")
+            )
+        );
+    }
+
+    #[test]
+    fn calendar_spans_default_to_utc_days() {
+        let now = millis("2025-03-28T12:34:56Z");
+
+        assert_eq!(
+            calendar_span(Calendar::Day, now, Tz::UTC),
+            (
+                millis("2025-03-28T00:00:00Z"),
+                millis("2025-03-29T00:00:00Z")
+            )
+        );
+    }
+}
@@ -1,12 +1,18 @@
+use std::collections::HashMap;
 use std::io::ErrorKind;
 
-use crate::config::{CREDENTIALS_ACCESS_TOKEN, CREDENTIALS_KV};
+use crate::config::{BOT_USER_ID, CREDENTIALS_ACCESS_TOKEN, CREDENTIALS_KV};
 use http::{Method as HttpMethod, Response};
+use serde::{Deserialize, Serialize};
 use twitch_api::{
     HelixClient,
     client::{BoxedFuture, Bytes, Client},
+    helix::{ClientRequestError, users::GetUsersRequest},
+    types::{UserId, UserIdRef, UserNameRef},
+};
+use twitch_oauth2::{
+    AccessToken, AppAccessToken, ClientId, ClientSecret, RefreshToken, TwitchToken, UserToken,
 };
-use twitch_oauth2::{AccessToken, AppAccessToken, ClientId, ClientSecret};
 use worker::{
     CfProperties, Env, Fetch, Headers, Method, Request, RequestInit,
     send::{SendFuture, SendWrapper},
@@ -64,6 +70,140 @@ pub async fn get_credentials(env: &Env) -> worker::Result<AppAccessToken> {
     Ok(token)
 }
 
+/// A user token in [`CREDENTIALS_KV`], granted through the dashboard.
+#[derive(Serialize, Deserialize)]
+struct StoredToken {
+    access_token: String,
+    refresh_token: String,
+}
+
+/// Keeps a user token in [`CREDENTIALS_KV`] under `key`.
+pub async fn store_user_token(
+    env: &Env,
+    key: &str,
+    access_token: &str,
+    refresh_token: &str,
+) -> crate::error::BotResult<()> {
+    let stored = StoredToken {
+        access_token: access_token.to_owned(),
+        refresh_token: refresh_token.to_owned(),
+    };
+
+    env.kv(CREDENTIALS_KV)?
+        .put(key, serde_json::to_string(&stored)?)?
+        .execute()
+        .await?;
+
+    Ok(())
+}
+
+/// The user token stored under `key`, refreshed if it expired. `None` if it was never granted.
+pub async fn user_token(env: &Env, key: &str) -> crate::error::BotResult<Option<UserToken>> {
+    let credentials = env.kv(CREDENTIALS_KV)?;
+
+    let Some(stored) = credentials.get(key).json::<StoredToken>().await? else {
+        return Ok(None);
+    };
+
+    let http_client = HelixClient::with_client(FetchClient::default());
+
+    let token = UserToken::from_existing_or_refresh_token(
+        &http_client,
+        AccessToken::new(stored.access_token.clone()),
+        RefreshToken::new(stored.refresh_token.clone()),
+        ClientId::new(env.secret("TW_CLIENT_ID")?.to_string()),
+        ClientSecret::new(env.secret("TW_CLIENT_SECRET")?.to_string()),
+    )
+    .await
+    .map_err(|e| worker::Error::RustError(e.to_string()))?;
+
+    if token.access_token.secret() != stored.access_token {
+        store_user_token(
+            env,
+            key,
+            token.access_token.secret(),
+            token
+                .refresh_token
+                .as_ref()
+                .map_or(stored.refresh_token.as_str(), |refresh| refresh.secret()),
+        )
+        .await?;
+    }
+
+    Ok(Some(token))
+}
+
+/// Sends a message to a channel's chat as the bot, for responses that don't answer a command.
+pub async fn send_message(
+    env: &Env,
+    broadcaster_user_id: &UserIdRef,
+    message: &str,
+) -> crate::error::BotResult<()> {
+    let token = get_credentials(env).await?;
+    let http_client = HelixClient::with_client(FetchClient::default());
+
+    http_client
+        .send_chat_message(
+            broadcaster_user_id,
+            UserId::from_static(BOT_USER_ID),
+            message,
+            &token,
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Looks up the display names of users by their IDs, users that don't exist anymore are left out.
+pub async fn get_display_names(
+    http_client: &HelixClient<'_, FetchClient>,
+    user_ids: &[&UserIdRef],
+    token: &impl TwitchToken,
+) -> Result<HashMap<String, String>, ClientRequestError<worker::Error>> {
+    let mut names = HashMap::with_capacity(user_ids.len());
+
+    // Twitch takes at most 100 IDs per request.
+    for chunk in user_ids.chunks(100) {
+        let response = http_client
+            .req_get(GetUsersRequest::ids(chunk), token)
+            .await?;
+
+        names.extend(
+            response
+                .data
+                .into_iter()
+                .map(|user| (user.id.take(), user.display_name.take())),
+        );
+    }
+
+    Ok(names)
+}
+
+/// Looks up the IDs of users by their logins, keyed by the lowercase login. Logins that don't
+/// exist are left out.
+pub async fn get_user_ids(
+    http_client: &HelixClient<'_, FetchClient>,
+    logins: &[&UserNameRef],
+    token: &impl TwitchToken,
+) -> Result<HashMap<String, UserId>, ClientRequestError<worker::Error>> {
+    let mut ids = HashMap::with_capacity(logins.len());
+
+    for chunk in logins.chunks(100) {
+        let response = http_client
+            .req_get(GetUsersRequest::logins(chunk), token)
+            .await?;
+
+        ids.extend(
+            response
+                .data
+                .into_iter()
+                .map(|user| (user.login.take().to_lowercase(), user.id)),
+        );
+    }
+
+    Ok(ids)
+}
+
 #[derive(Default, Clone)]
 pub struct FetchClient {
     pub ttl: Option<u32>,
@@ -0,0 +1,223 @@
+//! Outgoing webhooks for perfect hits, new records and epoch rollovers, configured per channel
+//! through [`crate::config::CommandConfig::webhooks`].
+//!
+//! Deliveries are put on the Webhooks queue and sent by the queue consumer, which retries them
+//! when the receiver fails, so slow or broken receivers never hold up chat responses.
+//!
+//! JSON deliveries are signed the same way Twitch signs EventSub messages: the
+//! `Next45-Message-Signature` header is `sha256=` followed by the hex HMAC-SHA256 of the
+//! `Next45-Message-Id` and `Next45-Message-Timestamp` headers and the body, in that order. The key
+//! is the channel's signing secret shown on the dashboard.
+
+use serde::{Deserialize, Serialize};
+use worker::{Env, Fetch, Headers, MessageBatch, Method, Request, RequestInit};
+
+use crate::prelude::*;
+
+/// The queue deliveries are sent through.
+pub const WEBHOOKS_QUEUE: &str = "Webhooks";
+
+/// Something that happened in a game, sent to the webhooks that want it.
+#[derive(Debug, Clone, Serialize)]
+pub struct WebhookPayload {
+    pub event: WebhookEvent,
+    pub broadcaster_user_id: String,
+    pub game: String,
+    /// The epoch the attempt was in, or the epoch that started for rollovers.
+    pub epoch: i64,
+    /// The chatter of the attempt, or the moderator for rollovers from `!45 reset`.
+    pub user_id: String,
+    pub user_name: String,
+    /// The value and difference of the attempt, formatted with the precision of the game. Empty
+    /// for rollovers from `!45 reset`.
+    pub value: String,
+    pub difference: String,
+    /// Milliseconds since the Unix epoch.
+    pub timestamp: i64,
+}
+
+/// A request ready to be sent, signed when it was queued.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Delivery {
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+/// The events an attempt causes, given the best and worst difference of its epoch before it.
+pub fn events_for(difference: f64, previous: Option<(f64, f64)>) -> Vec<WebhookEvent> {
+    if difference == 0.0 {
+        return vec![WebhookEvent::Perfect, WebhookEvent::EpochRollover];
+    }
+
+    // The first attempt of an epoch is trivially its best and worst.
+    let Some((best, worst)) = previous else {
+        return Vec::new();
+    };
+
+    let mut events = Vec::new();
+
+    if difference < best {
+        events.push(WebhookEvent::NewBest);
+    }
+
+    if difference > worst {
+        events.push(WebhookEvent::NewWorst);
+    }
+
+    events
+}
+
+/// The key deliveries of a channel are signed with, derived from the WEBHOOK_SECRET secret so
+/// that it doesn't have to be stored.
+pub fn signing_secret(env: &Env, broadcaster_user_id: &str) -> worker::Result<String> {
+    let key = env.secret("WEBHOOK_SECRET")?.to_string();
+
+    Ok(hex::encode(hmac_sha256::HMAC::mac(
+        broadcaster_user_id,
+        key,
+    )))
+}
+
+/// Queues the payloads for every webhook of the channel that wants them.
+pub async fn dispatch(
+    env: &Env,
+    config: &CommandConfig,
+    payloads: &[WebhookPayload],
+) -> BotResult<()> {
+    let queue = env.queue(WEBHOOKS_QUEUE)?;
+
+    for payload in payloads {
+        for webhook in config
+            .webhooks
+            .iter()
+            .filter(|webhook| webhook.wants(payload.event))
+        {
+            let delivery = match webhook.format {
+                WebhookFormat::Json => signed(env, webhook, payload)?,
+                WebhookFormat::Discord => discord(config, webhook, payload)?,
+            };
+
+            queue.send(delivery).await?;
+        }
+    }
+
+    Ok(())
+}
+
+fn signed(env: &Env, webhook: &WebhookConfig, payload: &WebhookPayload) -> BotResult<Delivery> {
+    let body = serde_json::to_string(payload)?;
+    let id = crate::dashboard::session::random_token()?;
+    let timestamp = payload.timestamp.to_string();
+
+    let mut input = String::with_capacity(id.len() + timestamp.len() + body.len());
+    input.push_str(&id);
+    input.push_str(&timestamp);
+    input.push_str(&body);
+
+    let key = signing_secret(env, &payload.broadcaster_user_id)?;
+    let signature = hex::encode(hmac_sha256::HMAC::mac(input, key));
+
+    Ok(Delivery {
+        url: webhook.url.clone(),
+        headers: vec![
+            ("Next45-Message-Id".to_owned(), id),
+            ("Next45-Message-Timestamp".to_owned(), timestamp),
+            (
+                "Next45-Message-Signature".to_owned(),
+                format!("sha256={signature}"),
+            ),
+        ],
+        body,
+    })
+}
+
+#[derive(Serialize)]
+struct DiscordMessage {
+    content: String,
+}
+
+fn discord(
+    config: &CommandConfig,
+    webhook: &WebhookConfig,
+    payload: &WebhookPayload,
+) -> BotResult<Delivery> {
+    let key = match payload.event {
+        WebhookEvent::Perfect => MessageKey::WebhookPerfect,
+        WebhookEvent::NewBest => MessageKey::WebhookNewBest,
+        WebhookEvent::NewWorst => MessageKey::WebhookNewWorst,
+        WebhookEvent::EpochRollover => MessageKey::WebhookEpochRollover,
+    };
+
+    let target = config
+        .game(&payload.game)
+        .map(|game| game.rules.format_value(game.rules.target, config.locale()))
+        .unwrap_or_default();
+
+    let vars = Vars::new()
+        .with(Variable::User, &payload.user_name)
+        .with(Variable::Value, &payload.value)
+        .with(Variable::Difference, &payload.difference)
+        .with(Variable::Epoch, payload.epoch)
+        .with(Variable::Game, &payload.game)
+        .with(Variable::Target, target);
+
+    Ok(Delivery {
+        url: webhook.url.clone(),
+        headers: Vec::new(),
+        body: serde_json::to_string(&DiscordMessage {
+            content: config.template(key).render(&vars),
+        })?,
+    })
+}
+
+/// Sends queued deliveries. Failed deliveries are retried by the queue, except for ones the
+/// receiver rejected, which would fail the same way again.
+pub async fn consume(batch: MessageBatch<Delivery>) -> worker::Result<()> {
+    for message in batch.messages()? {
+        let delivery = message.body();
+
+        match send(delivery).await {
+            Ok(status) if (200..300).contains(&status) => message.ack(),
+            Ok(status) if (400..500).contains(&status) && status != 429 => {
+                console_error!(
+                    "Webhook {} rejected a delivery with {}, dropping it",
+                    delivery.url,
+                    status
+                );
+                message.ack();
+            }
+            Ok(status) => {
+                console_error!("Webhook {} failed with {}, retrying", delivery.url, status);
+                message.retry();
+            }
+            Err(e) => {
+                console_error!("Webhook {} failed: {}, retrying", delivery.url, e);
+                message.retry();
+            }
+        }
+    }
+
+    Ok(())
+}
+
+async fn send(delivery: &Delivery) -> worker::Result<u16> {
+    let headers = Headers::new();
+    headers.set("Content-Type", "application/json")?;
+
+    for (name, value) in &delivery.headers {
+        headers.set(name, value)?;
+    }
+
+    let request_init = RequestInit {
+        method: Method::Post,
+        headers,
+        body: Some(delivery.body.as_str().into()),
+        ..Default::default()
+    };
+
+    let request = Request::new_with_init(&delivery.url, &request_init)?;
+    let response = Fetch::Request(request).send().await?;
+
+    Ok(response.status_code())
+}
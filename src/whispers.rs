@@ -0,0 +1,189 @@
+//! Answering commands in whispers instead of chat. Channels can whisper the responses of some
+//! commands to the chatter through [`crate::config::CommandConfig::whispered`], and chatters can
+//! whisper the bot `pb`, `pw`, `stats` or `achievements` followed by a channel and a game, e.g.
+//! `stats somechannel 22.5`. Without a channel, the one the chatter played in last is used.
+//!
+//! Twitch only lets users whisper, so the bot's account has to log in to the dashboard through
+//! `/dashboard/login?whispers` for the bot to send and receive whispers.
+
+use sqlx_d1::D1Connection;
+use twitch_api::eventsub::{
+    Transport,
+    user::{UserWhisperMessageV1, UserWhisperMessageV1Payload},
+};
+use twitch_api::helix::whispers::{SendWhisperBody, SendWhisperRequest};
+
+use crate::commands::{
+    CallableV2, Context, Deferred, Invocation,
+    forty_five::{
+        achievements::Achievements,
+        best_worst::{PersonalBest, PersonalWorst},
+        stats::Stats,
+    },
+};
+use crate::db;
+use crate::prelude::*;
+
+/// Where the bot's token is kept in [`CREDENTIALS_KV`].
+const TOKEN_KEY: &str = "whispers_token";
+
+/// Keeps the token the bot's account granted for whispering.
+pub async fn store_token(env: &Env, access_token: &str, refresh_token: &str) -> BotResult<()> {
+    store_user_token(env, TOKEN_KEY, access_token, refresh_token).await
+}
+
+/// Whispers `message` to a user, false if the bot's account never granted the token.
+pub async fn send(env: &Env, to_user_id: &UserIdRef, message: &str) -> BotResult<bool> {
+    let Some(token) = user_token(env, TOKEN_KEY).await? else {
+        return Ok(false);
+    };
+
+    let http_client = HelixClient::with_client(FetchClient::default());
+    let request = SendWhisperRequest::new(UserId::from_static(BOT_USER_ID), to_user_id);
+
+    http_client
+        .req_post(request, SendWhisperBody::new(message), &token)
+        .await?;
+
+    Ok(true)
+}
+
+/// Subscribes to whispers sent to the bot, delivered to `callback`. Twitch only accepts this once
+/// the bot's account granted the token.
+pub async fn subscribe(env: &Env, callback: &str) -> BotResult<()> {
+    let http_client = HelixClient::with_client(FetchClient::default());
+    let token = get_credentials(env).await?;
+    let secret = env.secret("HMAC_SECRET")?.to_string();
+
+    http_client
+        .create_eventsub_subscription(
+            UserWhisperMessageV1::new(UserId::from_static(BOT_USER_ID)),
+            Transport::webhook(callback, secret),
+            &token,
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// The chatter a whispered command is run for, in the channel they named.
+struct Whisperer {
+    broadcaster_user_id: UserId,
+    user_id: UserId,
+    user_name: DisplayName,
+}
+
+impl Invocation for Whisperer {
+    fn broadcaster_user_id(&self) -> &UserId {
+        &self.broadcaster_user_id
+    }
+
+    fn chatter_user_id(&self) -> &UserId {
+        &self.user_id
+    }
+
+    fn chatter_user_name(&self) -> &DisplayName {
+        &self.user_name
+    }
+}
+
+pub async fn answer(env: Env, payload: UserWhisperMessageV1Payload) {
+    if payload.from_user_id == UserId::from_static(BOT_USER_ID) {
+        return;
+    }
+
+    let answered: BotResult<()> = async {
+        let Some(reply) = reply(&env, &payload).await? else {
+            return Ok(());
+        };
+
+        if !send(&env, &payload.from_user_id, &reply).await? {
+            console_log!("No whispers token, not answering {}", payload.from_user_id);
+        }
+
+        Ok(())
+    }
+    .await;
+
+    if let Err(e) = answered {
+        console_error!(
+            "Error answering the whisper of {}: {:?}",
+            payload.from_user_id,
+            e
+        );
+    }
+}
+
+/// Runs a whispered command, `<command> [channel] [game]`.
+async fn reply(env: &Env, payload: &UserWhisperMessageV1Payload) -> BotResult<Option<String>> {
+    let mut words = payload.whisper.text.split_whitespace();
+    let command = words.next().unwrap_or_default().to_lowercase();
+    let channel = words.next();
+    let game = words.next();
+
+    let usage = || {
+        CommandConfig::default()
+            .template(MessageKey::WhisperUsage)
+            .render(&Vars::new())
+    };
+
+    if !matches!(command.as_str(), "pb" | "pw" | "stats" | "achievements") {
+        return Ok(Some(usage()));
+    }
+
+    let broadcaster_user_id = match channel {
+        Some(channel) => {
+            let login = channel.trim_start_matches(['@', '#']).to_lowercase();
+
+            let token = get_credentials(env).await?;
+            let http_client = HelixClient::with_client(FetchClient::default());
+
+            let user = http_client.get_user_from_login(&login, &token).await?;
+
+            let Some(user) = user else {
+                let vars = Vars::new().with(Variable::User, channel);
+                return Ok(Some(
+                    CommandConfig::default()
+                        .template(MessageKey::UserNotFound)
+                        .render(&vars),
+                ));
+            };
+
+            user.id
+        }
+        None => {
+            let db_conn = D1Connection::new(env.d1(FORTYFIVE_DATA)?);
+
+            match db::last_played_channel(&db_conn, payload.from_user_id.as_str()).await? {
+                Some(broadcaster_user_id) => UserId::new(broadcaster_user_id),
+                None => return Ok(Some(usage())),
+            }
+        }
+    };
+
+    let config = CommandConfig::load(env, &broadcaster_user_id).await;
+
+    let Some(game) = config.game(game.unwrap_or(DEFAULT_GAME)) else {
+        return Ok(Some(usage()));
+    };
+
+    let context = Context {
+        env: env.clone(),
+        payload: Whisperer {
+            broadcaster_user_id,
+            user_id: payload.from_user_id.clone(),
+            user_name: payload.from_user_name.clone(),
+        },
+        config,
+        game,
+        // The commands that can be whispered only look things up.
+        deferred: Deferred::default(),
+    };
+
+    match command.as_str() {
+        "pb" => PersonalBest { args: Vec::new() }.call(context).await,
+        "pw" => PersonalWorst { args: Vec::new() }.call(context).await,
+        "stats" => Stats { chatter: None }.call(context).await,
+        _ => Achievements { chatter: None }.call(context).await,
+    }
+}